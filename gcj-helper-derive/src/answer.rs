@@ -0,0 +1,235 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Implementation of `#[derive(Answer)]`.
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::{Attribute, Data, DeriveInput, Error, Fields, Ident, LitInt, LitStr, Result};
+
+/// How a single field is formatted.
+struct FieldFormat {
+    /// The number of digits after the decimal point, if specified.
+    precision: Option<usize>,
+    /// The minimum number of significant digits, if specified.
+    significant: Option<usize>,
+    /// The number of significant digits in scientific notation, if specified.
+    scientific: Option<usize>,
+    /// Whether the field is a sequence whose elements are written in turn.
+    list: bool,
+    /// Whether each element of the field is written on its own line.
+    multiline: bool,
+    /// The separator between elements of a sequence, if different from the container's.
+    separator: Option<String>,
+}
+
+/// Generates a `Display` implementation for a struct or enum.
+pub fn expand(ast: &DeriveInput) -> Result<TokenStream2> {
+    let separator = container_separator(&ast.attrs)?;
+    let name = &ast.ident;
+    let arms = match ast.data {
+        Data::Struct(ref s) => {
+            let (pattern, body) = expand_fields(quote!(#name), &s.fields, &separator)?;
+            vec![quote! { #pattern => { #body } }]
+        }
+        Data::Enum(ref e) => {
+            let mut arms = Vec::new();
+            for variant in &e.variants {
+                let ident = &variant.ident;
+                let mut text = variant_text(&variant.attrs)?;
+                if let Fields::Unit = variant.fields {
+                    text = text.or_else(|| Some(ident.to_string()));
+                }
+                let arm = match (text, &variant.fields) {
+                    (Some(text), &Fields::Unit) => quote! {
+                        #name::#ident => f.write_str(concat!(" ", #text, "\n")),
+                    },
+                    (Some(_), _) => {
+                        return Err(Error::new_spanned(
+                            variant,
+                            "`text` may only be used on unit variants",
+                        ))
+                    }
+                    (None, fields) => {
+                        let (pattern, body) =
+                            expand_fields(quote!(#name::#ident), fields, &separator)?;
+                        quote! { #pattern => { #body } }
+                    }
+                };
+                arms.push(arm);
+            }
+            arms
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                ast,
+                "`Answer` cannot be derived for unions",
+            ))
+        }
+    };
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::std::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                match *self {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+/// Generates a match pattern binding each field, and code that writes the fields in turn.
+fn expand_fields(
+    path: TokenStream2,
+    fields: &Fields,
+    separator: &str,
+) -> Result<(TokenStream2, TokenStream2)> {
+    let mut bindings = Vec::new();
+    let mut writes = Vec::new();
+    let mut first = true;
+    let mut after_multiline = false;
+    for (i, field) in fields.iter().enumerate() {
+        let binding = Ident::new(&format!("__gcj_field_{}", i), Span::call_site());
+        let format = field_format(&field.attrs)?;
+        let write_item = match (format.precision, format.significant, format.scientific) {
+            (Some(p), None, None) => quote! { write!(f, "{:.*}", #p, item)?; },
+            (None, Some(d), None) => quote! {
+                write!(f, "{}", ::gcj_helper::Significant(f64::from(*item), #d))?;
+            },
+            (None, None, Some(d)) => quote! {
+                write!(f, "{}", ::gcj_helper::Scientific(f64::from(*item), #d))?;
+            },
+            (None, None, None) => quote! { write!(f, "{}", item)?; },
+            _ => {
+                return Err(Error::new_spanned(
+                    field,
+                    "only one of `precision`, `significant` and `scientific` may be used",
+                ))
+            }
+        };
+        let write = if format.multiline {
+            quote! {
+                for item in #binding {
+                    f.write_str("\n")?;
+                    #write_item
+                }
+            }
+        } else {
+            let prefix = if first {
+                " "
+            } else if after_multiline {
+                "\n"
+            } else {
+                separator
+            };
+            if format.list {
+                let list_separator = format.separator.as_ref().map_or(separator, |s| s.as_str());
+                quote! {
+                    f.write_str(#prefix)?;
+                    for (i, item) in #binding.iter().enumerate() {
+                        if i > 0 {
+                            f.write_str(#list_separator)?;
+                        }
+                        #write_item
+                    }
+                }
+            } else {
+                quote! {
+                    f.write_str(#prefix)?;
+                    let item = #binding;
+                    #write_item
+                }
+            }
+        };
+        first = false;
+        after_multiline = format.multiline;
+        writes.push(write);
+        bindings.push((field.ident.clone(), binding));
+    }
+    let pattern = match *fields {
+        Fields::Named(_) => {
+            let names = bindings.iter().map(|(name, _)| name);
+            let idents = bindings.iter().map(|(_, binding)| binding);
+            quote! { #path { #(#names: ref #idents),* } }
+        }
+        Fields::Unnamed(_) => {
+            let idents = bindings.iter().map(|(_, binding)| binding);
+            quote! { #path(#(ref #idents),*) }
+        }
+        Fields::Unit => quote! { #path },
+    };
+    let body = quote! {
+        #(#writes)*
+        f.write_str("\n")
+    };
+    Ok((pattern, body))
+}
+
+/// Reads the `separator` attribute of a struct or enum, defaulting to a single space.
+fn container_separator(attrs: &[Attribute]) -> Result<String> {
+    let mut separator = " ".to_owned();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("gcj")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("separator") {
+                separator = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("unsupported gcj attribute"))
+            }
+        })?;
+    }
+    Ok(separator)
+}
+
+/// Reads the `text` attribute of an enum variant.
+fn variant_text(attrs: &[Attribute]) -> Result<Option<String>> {
+    let mut text = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("gcj")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("text") {
+                text = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("unsupported gcj attribute"))
+            }
+        })?;
+    }
+    Ok(text)
+}
+
+/// Reads the formatting attributes of a field.
+fn field_format(attrs: &[Attribute]) -> Result<FieldFormat> {
+    let mut format = FieldFormat {
+        precision: None,
+        significant: None,
+        scientific: None,
+        list: false,
+        multiline: false,
+        separator: None,
+    };
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("gcj")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("precision") {
+                format.precision = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+            } else if meta.path.is_ident("significant") {
+                format.significant = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+            } else if meta.path.is_ident("scientific") {
+                format.scientific = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+            } else if meta.path.is_ident("list") {
+                format.list = true;
+            } else if meta.path.is_ident("multiline") {
+                format.multiline = true;
+            } else if meta.path.is_ident("separator") {
+                format.separator = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else {
+                return Err(meta.error("unsupported gcj attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(format)
+}
@@ -0,0 +1,270 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Procedural macros for `gcj-helper`.
+//!
+//! This crate is an implementation detail of `gcj-helper`, and should not be used directly;
+//! instead, enable the `derive` feature of `gcj-helper`, which re-exports these macros.
+
+#![forbid(warnings)]
+#![forbid(missing_copy_implementations)]
+#![forbid(missing_debug_implementations)]
+#![forbid(missing_docs)]
+#![forbid(trivial_casts)]
+#![forbid(trivial_numeric_casts)]
+#![forbid(unsafe_code)]
+#![forbid(unused_extern_crates)]
+#![forbid(unused_import_braces)]
+#![deny(unused_qualifications)]
+#![forbid(unused_results)]
+#![forbid(variant_size_differences)]
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+mod answer;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::{Data, DeriveInput, Error, Expr, Field, Fields, Ident, Item, LitStr, Result};
+
+/// How a single field is laid out in the input file.
+enum Layout {
+    /// The field is read via its own `FromInput` implementation.
+    Default,
+    /// The field is parsed from an entire line of text.
+    Line,
+    /// The field is a `Vec` of the given number of values.
+    Count(Expr),
+    /// The field is a grid with the given number of rows, of characters rather than bytes if
+    /// the flag is set.
+    Grid(Expr, bool),
+}
+
+/// Derives `FromInput` for a struct.
+///
+/// Fields are read in declaration order. By default, each field is read via its own `FromInput`
+/// implementation; the `#[gcj(...)]` attribute can be used to describe other layouts:
+///
+/// * `#[gcj(line)]` parses the field from an entire line of text, via `FromStr`.
+/// * `#[gcj(count = "n")]` reads a `Vec` of `n` values, where `n` is an expression that may refer
+///   to earlier fields by name.
+/// * `#[gcj(grid(rows = "r"))]` reads a `Vec<Vec<u8>>` from `r` lines of text, where `r` is an
+///   expression that may refer to earlier fields by name. `#[gcj(grid(rows = "r", chars))]` reads
+///   a `Vec<Vec<char>>` instead, for grids containing non-ASCII symbols.
+#[proc_macro_derive(FromInput, attributes(gcj))]
+pub fn derive_from_input(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as DeriveInput);
+    match expand_from_input(&ast) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derives `Display` for a struct or enum representing the result for one test case.
+///
+/// The result is formatted as expected by `TestEngine::run()`: it begins with a space and ends with
+/// a newline, and fields are written in declaration order, separated by a single space. The
+/// `#[gcj(...)]` attribute customises the format:
+///
+/// * `#[gcj(separator = ", ")]` on the struct or enum changes the separator between fields.
+/// * `#[gcj(text = "IMPOSSIBLE")]` on a unit enum variant specifies the text written for it; by
+///   default, the name of the variant is written.
+/// * `#[gcj(precision = 6)]` on a field writes it (or each of its elements) with the given number
+///   of digits after the decimal point.
+/// * `#[gcj(significant = 9)]` on a field writes it (or each of its elements) with at least the
+///   given number of significant digits, via `gcj_helper::Significant`, and
+///   `#[gcj(scientific = 9)]` writes it in scientific notation with the given number of
+///   significant digits, via `gcj_helper::Scientific`. Such fields must be convertible to `f64`
+///   with `f64::from()`.
+/// * `#[gcj(list)]` on a field writes each element of a sequence (e.g. a `Vec`) in turn,
+///   separated by the field's `separator` attribute, or the container's separator by default.
+/// * `#[gcj(multiline)]` on a field writes each element of a sequence on its own line. Fields
+///   following a multi-line field begin on a new line.
+#[proc_macro_derive(Answer, attributes(gcj))]
+pub fn derive_answer(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as DeriveInput);
+    match answer::expand(&ast) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Generates a `main` function that executes a solution.
+///
+/// This attribute may be applied to either of the following:
+///
+/// * An implementation of `gcj_helper::Problem`, in which case `main` executes it via
+///   `TestEngine::run_problem()`.
+/// * A function taking a `&mut InputReader`, which reads one test case and returns its result. If
+///   the function is itself named `main`, it is renamed.
+///
+/// In both cases, input and output file paths are obtained via `TestEngine::from_args()`.
+#[proc_macro_attribute]
+pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let attr = TokenStream2::from(attr);
+    let item = syn::parse_macro_input!(item as Item);
+    let result = if attr.is_empty() {
+        expand_main(item)
+    } else {
+        Err(Error::new_spanned(attr, "`main` does not accept arguments"))
+    };
+    match result {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Generates a `main` function for a `Problem` implementation or a solution function.
+fn expand_main(item: Item) -> Result<TokenStream2> {
+    match item {
+        Item::Impl(imp) => {
+            if imp.trait_.is_none() {
+                return Err(Error::new_spanned(
+                    imp,
+                    "`main` must be applied to an implementation of `Problem`",
+                ));
+            }
+            let self_ty = &imp.self_ty;
+            Ok(quote! {
+                #imp
+
+                fn main() {
+                    ::gcj_helper::TestEngine::from_args().run_problem::<#self_ty>()
+                }
+            })
+        }
+        Item::Fn(mut f) => {
+            if f.sig.ident == "main" {
+                f.sig.ident = Ident::new("__gcj_main", f.sig.ident.span());
+            }
+            let ident = &f.sig.ident;
+            Ok(quote! {
+                #f
+
+                fn main() {
+                    ::gcj_helper::TestEngine::from_args().run(
+                        |input| #ident(input),
+                        |result| result.to_string(),
+                    )
+                }
+            })
+        }
+        item => Err(Error::new_spanned(
+            item,
+            "`main` must be applied to a function or an implementation of `Problem`",
+        )),
+    }
+}
+
+/// Generates a `FromInput` implementation for a struct.
+fn expand_from_input(ast: &DeriveInput) -> Result<TokenStream2> {
+    let fields = match ast.data {
+        Data::Struct(ref s) => &s.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                ast,
+                "`FromInput` can only be derived for structs",
+            ))
+        }
+    };
+    let reader = Ident::new("__gcj_reader", Span::call_site());
+    let mut bindings = Vec::new();
+    let mut reads = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let binding = match field.ident {
+            Some(ref ident) => ident.clone(),
+            None => Ident::new(&format!("__gcj_field_{}", i), Span::call_site()),
+        };
+        let ty = &field.ty;
+        let read = match field_layout(field)? {
+            Layout::Default => quote! {
+                ::gcj_helper::FromInput::from_input(#reader)
+            },
+            Layout::Line => quote! {
+                {
+                    let line = #reader.read_next_line();
+                    line.trim_end_matches('\r').parse().unwrap_or_else(|_| {
+                        panic!("could not parse {:?} as `{}`", line, stringify!(#ty))
+                    })
+                }
+            },
+            Layout::Count(n) => quote! {
+                #reader.read_vec((#n) as usize)
+            },
+            Layout::Grid(rows, false) => quote! {
+                #reader.read_grid((#rows) as usize)
+            },
+            Layout::Grid(rows, true) => quote! {
+                #reader.read_char_grid((#rows) as usize)
+            },
+        };
+        reads.push(quote! {
+            let #binding: #ty = #read;
+        });
+        bindings.push(binding);
+    }
+    let name = &ast.ident;
+    let construct = match *fields {
+        Fields::Named(_) => quote! { #name { #(#bindings: #bindings),* } },
+        Fields::Unnamed(_) => quote! { #name(#(#bindings),*) },
+        Fields::Unit => quote! { #name },
+    };
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::gcj_helper::FromInput for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn from_input(#reader: &mut ::gcj_helper::InputReader) -> Self {
+                #(#reads)*
+                #construct
+            }
+        }
+    })
+}
+
+/// Determines the layout of a field from its `#[gcj(...)]` attributes.
+fn field_layout(field: &Field) -> Result<Layout> {
+    let mut layout = Layout::Default;
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("gcj")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("line") {
+                layout = Layout::Line;
+                Ok(())
+            } else if meta.path.is_ident("count") {
+                layout = Layout::Count(meta.value()?.parse::<LitStr>()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("grid") {
+                let mut rows = None;
+                let mut chars = false;
+                meta.parse_nested_meta(|grid| {
+                    if grid.path.is_ident("rows") {
+                        rows = Some(grid.value()?.parse::<LitStr>()?.parse()?);
+                        Ok(())
+                    } else if grid.path.is_ident("chars") {
+                        chars = true;
+                        Ok(())
+                    } else {
+                        Err(grid.error("unsupported grid attribute"))
+                    }
+                })?;
+                match rows {
+                    Some(rows) => {
+                        layout = Layout::Grid(rows, chars);
+                        Ok(())
+                    }
+                    None => Err(meta.error("grid attribute requires `rows`")),
+                }
+            } else {
+                Err(meta.error("unsupported gcj attribute"))
+            }
+        })?;
+    }
+    Ok(layout)
+}
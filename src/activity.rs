@@ -0,0 +1,131 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Tracking which test cases are being solved, for reports written from other threads.
+
+use super::format_duration;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Whether test cases are being tracked.
+static TRACKING: AtomicBool = AtomicBool::new(false);
+
+/// The number of test cases solved so far.
+static SOLVED: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of test cases in the run, or zero if this is not known.
+static TOTAL: AtomicUsize = AtomicUsize::new(0);
+
+/// When each test case being solved was started.
+static RUNNING: Mutex<BTreeMap<usize, Instant>> = Mutex::new(BTreeMap::new());
+
+/// When the run started, if test cases are being tracked.
+static STARTED: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Tracks test cases for the duration of a run, if enabled.
+#[derive(Debug)]
+pub struct Tracking {
+    /// Whether tracking was enabled for the run.
+    enabled: bool,
+}
+
+impl Tracking {
+    /// Starts tracking test cases, if enabled, until the returned value is dropped.
+    pub fn begin(enabled: bool) -> Tracking {
+        if enabled {
+            SOLVED.store(0, Ordering::SeqCst);
+            TOTAL.store(0, Ordering::SeqCst);
+            lock_running().clear();
+            *STARTED.lock().expect("could not lock run start time") = Some(Instant::now());
+            TRACKING.store(true, Ordering::SeqCst);
+        }
+        Tracking { enabled: enabled }
+    }
+}
+
+impl Drop for Tracking {
+    fn drop(&mut self) {
+        if self.enabled {
+            TRACKING.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Records the number of test cases in the run, or zero if this is not known.
+pub fn set_total(total: usize) {
+    TOTAL.store(total, Ordering::SeqCst);
+}
+
+/// Records that a test case is about to be solved.
+pub fn case_started(case: usize) {
+    if TRACKING.load(Ordering::SeqCst) {
+        let _ = lock_running().insert(case, Instant::now());
+    }
+}
+
+/// Records that a test case has been solved.
+pub fn case_finished(case: usize) {
+    if TRACKING.load(Ordering::SeqCst) {
+        let _ = lock_running().remove(&case);
+        let _ = SOLVED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Returns the number of test cases solved so far.
+pub fn solved() -> usize {
+    SOLVED.load(Ordering::SeqCst)
+}
+
+/// Returns each test case being solved along with how long it has been running, longest first.
+pub fn running() -> Vec<(usize, Duration)> {
+    let mut running: Vec<(usize, Duration)> = lock_running()
+        .iter()
+        .map(|(&case, start)| (case, start.elapsed()))
+        .collect();
+    running.sort_by_key(|&(_, time)| Reverse(time));
+    running
+}
+
+/// Describes how far the run has got, e.g. `"37 of 100 test cases solved in 12.345s"`.
+pub fn describe_progress() -> String {
+    let elapsed = STARTED
+        .lock()
+        .expect("could not lock run start time")
+        .map_or(Duration::new(0, 0), |start| start.elapsed());
+    match TOTAL.load(Ordering::SeqCst) {
+        0 => format!("{} test cases solved in {}", solved(), format_duration(elapsed)),
+        total => format!(
+            "{} of {} test cases solved in {}",
+            solved(),
+            total,
+            format_duration(elapsed)
+        ),
+    }
+}
+
+/// Describes the test cases being solved, longest-running first, e.g.
+/// `"Case #12 (10.200s), Case #40 (0.500s)"`, listing at most `limit` of them.
+pub fn describe_running(limit: usize) -> String {
+    let running = running();
+    let mut list: Vec<String> = running
+        .iter()
+        .take(limit)
+        .map(|&(case, time)| format!("Case #{} ({})", case, format_duration(time)))
+        .collect();
+    if running.len() > limit {
+        list.push(format!("and {} more", running.len() - limit));
+    }
+    list.join(", ")
+}
+
+/// Locks the map of test cases being solved.
+fn lock_running() -> MutexGuard<'static, BTreeMap<usize, Instant>> {
+    RUNNING.lock().expect("could not lock running test cases")
+}
@@ -0,0 +1,100 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Pinning the threads that solve test cases in parallel mode to specific logical CPUs.
+//!
+//! If the `affinity` feature is enabled on Linux, each thread of the pool used in parallel mode
+//! pins itself to one of the requested logical CPUs before solving its first test case.
+//! Otherwise, pinning does nothing.
+
+#[cfg(feature = "affinity")]
+use super::{Input, Output, TestEngine};
+#[cfg(all(target_os = "linux", feature = "affinity"))]
+use nix::sched::{self, CpuSet};
+#[cfg(all(target_os = "linux", feature = "affinity"))]
+use nix::unistd::Pid;
+#[cfg(all(target_os = "linux", feature = "affinity"))]
+use std::cell::Cell;
+#[cfg(all(target_os = "linux", feature = "affinity"))]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(all(target_os = "linux", feature = "affinity"))]
+thread_local! {
+    /// Whether this thread has been pinned to a logical CPU.
+    static PINNED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Assigns the threads of a pool to logical CPUs, in the order they start solving test cases.
+#[derive(Debug)]
+pub struct Pinning {
+    /// The logical CPUs threads are pinned to, in turn.
+    #[cfg_attr(not(all(target_os = "linux", feature = "affinity")), allow(dead_code))]
+    cores: Vec<usize>,
+    /// The number of threads pinned so far.
+    #[cfg(all(target_os = "linux", feature = "affinity"))]
+    pinned: AtomicUsize,
+}
+
+#[cfg(feature = "affinity")]
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Pins the threads solving test cases in parallel mode to the given logical CPUs, numbered
+    /// from zero, one thread per CPU in turn.
+    ///
+    /// Keeping each thread on one core stops the scheduler from migrating it mid-run, which
+    /// reduces run-to-run timing variance when benchmarking a solution against the time limit,
+    /// particularly on laptops with aggressive power management. Unless `TestEngine::threads()`
+    /// is also set, one thread is started per CPU given; with more threads than CPUs, several
+    /// threads share each CPU. Pinning to a subset of the cores, such as the physical cores
+    /// rather than their hyperthreads, leaves the rest free for the rest of the system.
+    ///
+    /// This method is only available if the `affinity` feature is enabled, and has no effect
+    /// except on Linux.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `cores` is empty. The run panics if a thread cannot be pinned,
+    /// e.g. because a logical CPU does not exist.
+    pub fn pin_threads<C: Into<Vec<usize>>>(mut self, cores: C) -> TestEngine<I, O> {
+        let cores = cores.into();
+        assert!(!cores.is_empty(), "threads must be pinned to at least one logical CPU");
+        self.settings.pin_threads = Some(cores);
+        self
+    }
+}
+
+impl Pinning {
+    /// Starts assigning threads to the given logical CPUs.
+    pub fn new(cores: Vec<usize>) -> Pinning {
+        Pinning {
+            cores: cores,
+            #[cfg(all(target_os = "linux", feature = "affinity"))]
+            pinned: AtomicUsize::new(0),
+        }
+    }
+
+    /// Pins the calling thread to the next logical CPU in turn, unless it is already pinned.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the thread cannot be pinned.
+    #[cfg(all(target_os = "linux", feature = "affinity"))]
+    pub fn pin_current_thread(&self) {
+        if PINNED.with(Cell::get) {
+            return;
+        }
+        let core = self.cores[self.pinned.fetch_add(1, Ordering::SeqCst) % self.cores.len()];
+        let mut set = CpuSet::new();
+        set.set(core)
+            .and_then(|_| sched::sched_setaffinity(Pid::from_raw(0), &set))
+            .unwrap_or_else(|e| panic!("could not pin thread to logical CPU {}: {}", core, e));
+        PINNED.with(|pinned| pinned.set(true));
+    }
+
+    /// Pins the calling thread to the next logical CPU in turn, unless it is already pinned.
+    #[cfg(not(all(target_os = "linux", feature = "affinity")))]
+    pub fn pin_current_thread(&self) {}
+}
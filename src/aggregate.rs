@@ -0,0 +1,93 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Problems whose output ends with an aggregate over every test case, such as a total.
+
+use super::{read_input, Input, InputReader, Output, TestEngine};
+use std::cell::{Cell, RefCell};
+use std::fmt::{self, Display, Formatter};
+
+/// A test result paired with what was recorded from its test case for the aggregate.
+struct Recorded<R, T> {
+    /// The test result.
+    result: R,
+    /// What was recorded from the test case, until it is added to the aggregate.
+    entry: Cell<Option<T>>,
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Consumes the test engine, executing a parser and solver once per test case, then writing
+    /// an aggregate computed over every test case after the last test result.
+    ///
+    /// This is intended for problems that, in addition to the per-case answers, ask for a final
+    /// line summarising the whole input, such as a total across all test cases. As each test case
+    /// is solved, `record` is called with its data and the solver's result, and returns what the
+    /// aggregate needs from that test case; like the solver, it may be called from several
+    /// threads at once in parallel mode. Once every test result has been written, `finish` is
+    /// called with the values returned by `record`, in test case order, and its result is written
+    /// to the end of the output file, without a `"Case #N:"` header, so it should usually end with
+    /// a newline. Test cases that were not solved, such as those given placeholder answers once
+    /// the run budget was spent, have no entry. If the run is interrupted, nothing is written.
+    ///
+    /// The aggregate is also returned, so that it can be used after the run, e.g. as the score of
+    /// an optimisation problem.
+    ///
+    /// # Panics
+    ///
+    /// This method panics in the event of an I/O error.
+    pub fn run_aggregated<
+        D: Sized + Send + Sync,
+        R: Display + Sized + Send,
+        T: Send,
+        A: Display,
+        P: Fn(&mut InputReader) -> D,
+        S: Fn(&D) -> R + Sync,
+        G: Fn(&D, &R) -> T + Sync,
+        F: FnOnce(Vec<T>) -> A
+    >
+        (
+        self,
+        p: P,
+        s: S,
+        record: G,
+        finish: F,
+    ) -> A {
+        let text = read_input(self.input, self.settings.input_encoding);
+        let entries = RefCell::new(Vec::new());
+        let aggregate = RefCell::new(None);
+        let _ = Self::execute_text(
+            self.output,
+            self.settings,
+            &text,
+            |reader| Some(p(reader)),
+            |d| {
+                let result = s(d);
+                let entry = record(d, &result);
+                Recorded {
+                    result: result,
+                    entry: Cell::new(Some(entry)),
+                }
+            },
+            |_, r, _| entries.borrow_mut().extend(r.entry.take()),
+            || {
+                let value = finish(entries.replace(Vec::new()));
+                let trailer = value.to_string();
+                *aggregate.borrow_mut() = Some(value);
+                Some(trailer)
+            },
+        );
+        aggregate
+            .into_inner()
+            .expect("run finished without computing its aggregate")
+    }
+}
+
+impl<R: Display, T> Display for Recorded<R, T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.result.fmt(f)
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Metadata attached to test cases by solvers, written to a sidecar file.
+
+use super::{logging, paths, report, Input, Output, TestEngine};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Write};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+/// The annotations attached to each test case during the current run, as key-value pairs in the
+/// order their keys were first attached.
+static ANNOTATIONS: Mutex<BTreeMap<usize, Vec<(String, String)>>> = Mutex::new(BTreeMap::new());
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Writes the annotations attached to test cases with `annotate_case()` to a sidecar file at
+    /// `path` once the run finishes.
+    ///
+    /// If the path ends in `.csv`, the file is a CSV file with a `case` column followed by one
+    /// column per annotation key, in the order the keys were first used; otherwise, it is a JSON
+    /// array holding one object per annotated test case, e.g.
+    /// `{"case": 3, "algorithm": "greedy", "confidence": "0.8"}`. Annotation values are written as
+    /// strings, and test cases without annotations are omitted. This keeps metadata such as
+    /// confidence scores, the algorithm used or notes for a postmortem out of the output file.
+    pub fn annotations_file<P: Into<PathBuf>>(mut self, path: P) -> TestEngine<I, O> {
+        self.settings.annotations_file = Some(path.into());
+        self
+    }
+}
+
+/// Attaches an annotation to the test case currently being parsed or solved on this thread, such
+/// as `annotate_case("algorithm", "greedy")` or `annotate_case("confidence", score)`.
+///
+/// Annotations are written to the sidecar file set with `TestEngine::annotations_file()` once the
+/// run finishes, rather than to the output file. Attaching an annotation with a key already used
+/// for the same test case replaces its value. Outside of a test case, this does nothing.
+pub fn annotate_case<V: Display>(key: &str, value: V) {
+    let case = match logging::current_case() {
+        Some(case) => case,
+        None => return,
+    };
+    let value = value.to_string();
+    let mut annotations = lock_annotations();
+    let entries = annotations.entry(case).or_default();
+    match entries.iter_mut().find(|entry| entry.0 == key) {
+        Some(entry) => entry.1 = value,
+        None => entries.push((key.to_owned(), value)),
+    }
+}
+
+/// Discards the annotations attached during a previous run.
+pub fn clear() {
+    lock_annotations().clear();
+}
+
+/// Writes the annotations attached during the current run to a sidecar file, as CSV if its path
+/// ends in `.csv` and as JSON otherwise.
+pub fn save(path: &Path) -> io::Result<()> {
+    let annotations = lock_annotations();
+    let csv = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    let text = if csv {
+        let mut keys: Vec<&str> = Vec::new();
+        for entries in annotations.values() {
+            for (key, _) in entries {
+                if !keys.contains(&key.as_str()) {
+                    keys.push(key);
+                }
+            }
+        }
+        let mut text = String::from("case");
+        for key in &keys {
+            text.push(',');
+            push_csv_field(&mut text, key);
+        }
+        text.push('\n');
+        for (case, entries) in annotations.iter() {
+            let _ = write!(text, "{}", case);
+            for key in &keys {
+                text.push(',');
+                if let Some((_, value)) = entries.iter().find(|entry| entry.0 == *key) {
+                    push_csv_field(&mut text, value);
+                }
+            }
+            text.push('\n');
+        }
+        text
+    } else {
+        let mut text = String::from("[");
+        for (i, (case, entries)) in annotations.iter().enumerate() {
+            let _ = write!(text, "{}\n  {{\"case\": {}", if i == 0 { "" } else { "," }, case);
+            for (key, value) in entries {
+                text.push_str(", ");
+                report::push_json_string(&mut text, key);
+                text.push_str(": ");
+                report::push_json_string(&mut text, value);
+            }
+            text.push('}');
+        }
+        text.push_str(if annotations.is_empty() { "]\n" } else { "\n]\n" });
+        text
+    };
+    paths::write_file(path, text)
+}
+
+/// Appends a field to a CSV record, quoting it if it contains a comma, a quote or a line break.
+fn push_csv_field(csv: &mut String, field: &str) {
+    if field.contains([',', '"', '\n', '\r']) {
+        csv.push('"');
+        csv.push_str(&field.replace('"', "\"\""));
+        csv.push('"');
+    } else {
+        csv.push_str(field);
+    }
+}
+
+/// Locks the annotations attached during the current run.
+fn lock_annotations() -> MutexGuard<'static, BTreeMap<usize, Vec<(String, String)>>> {
+    ANNOTATIONS.lock().expect("could not lock case annotations")
+}
@@ -0,0 +1,124 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Auxiliary data files shipped with a problem, loaded once per run.
+
+use super::{paths, Input, InputReader, Output, TestEngine};
+use std::fmt::Display;
+use std::fs;
+use std::path::PathBuf;
+use std::str;
+
+/// The auxiliary data files registered with `TestEngine::aux_file()`, loaded into memory once
+/// per run and shared by the parser and solver of every test case.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AuxData {
+    /// The name and contents of each file, in the order they were registered.
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Registers an auxiliary data file at `path`, such as a dictionary or a precomputed table
+    /// shipped with the problem, under `name`.
+    ///
+    /// Registered files are read by `TestEngine::run_with_aux()` before the first test case is
+    /// parsed, once per run however many test cases use them, and are then passed by reference
+    /// to the parser and solver, in both sequential and parallel mode. Registering another file
+    /// under the same name replaces it.
+    pub fn aux_file<N: Into<String>, P: Into<PathBuf>>(
+        mut self,
+        name: N,
+        path: P,
+    ) -> TestEngine<I, O> {
+        let name = name.into();
+        self.settings.aux_files.retain(|(other, _)| *other != name);
+        self.settings.aux_files.push((name, path.into()));
+        self
+    }
+
+    /// Consumes the test engine, loading the auxiliary data files registered with
+    /// `TestEngine::aux_file()`, then executing a parser and solver once per test case, each of
+    /// which receives the loaded files.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if an auxiliary data file cannot be read, or in the event of an I/O
+    /// error.
+    pub fn run_with_aux<
+        D: Sized + Send + Sync,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader, &AuxData) -> D,
+        S: Fn(&D, &AuxData) -> R + Sync
+    >
+        (
+        self,
+        p: P,
+        s: S,
+    ) {
+        let aux = AuxData::load(&self.settings.aux_files);
+        let _ = self.execute(|reader| Some(p(reader, &aux)), |d| s(d, &aux), |_, _, _| {});
+    }
+}
+
+impl AuxData {
+    /// Returns the contents of the auxiliary data file registered under `name`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if no file was registered under `name`.
+    pub fn bytes(&self, name: &str) -> &[u8] {
+        match self.files.iter().find(|(other, _)| other == name) {
+            Some((_, contents)) => contents,
+            None => panic!("no auxiliary data file registered as `{}`", name),
+        }
+    }
+
+    /// Returns the contents of the auxiliary data file registered under `name`, as text.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if no file was registered under `name`, or if the file is not valid
+    /// UTF-8.
+    pub fn text(&self, name: &str) -> &str {
+        str::from_utf8(self.bytes(name)).unwrap_or_else(|e| {
+            panic!("auxiliary data file `{}` is not valid UTF-8: {}", name, e)
+        })
+    }
+
+    /// Returns an input reader over the auxiliary data file registered under `name`, so that it
+    /// can be parsed in the same way as an input file.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if no file was registered under `name`, or if the file is not valid
+    /// UTF-8.
+    pub fn reader(&self, name: &str) -> InputReader<'_> {
+        InputReader::new(self.text(name))
+    }
+
+    /// Reads the given auxiliary data files into memory.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if any of the files cannot be read.
+    fn load(files: &[(String, PathBuf)]) -> AuxData {
+        let mut loaded = Vec::with_capacity(files.len());
+        for (name, path) in files {
+            let path = paths::expand_home(path);
+            let contents = fs::read(&path).unwrap_or_else(|e| {
+                panic!(
+                    "could not read auxiliary data file `{}` at `{}`: {}",
+                    name,
+                    path.display(),
+                    e
+                )
+            });
+            loaded.push((name.clone(), contents));
+        }
+        AuxData { files: loaded }
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Benchmarking solvers over a full input file.
+
+use super::{format_duration, read_input, CaseIter, CaseMode, FromReader, Input, InputEncoding,
+            InputReader, Output, TestEngine, ToWriter};
+use std::fmt::Display;
+use std::io;
+use std::time::{Duration, Instant};
+
+/// A recorded input file held in memory, for benchmarking a parser and solver with an external
+/// harness such as criterion inside `cargo bench`.
+///
+/// `RecordedInput::run()` solves the whole input file once, and suits a whole-run benchmark such
+/// as `b.iter(|| input.run(&parse, &solve))`. For per-case benchmarks, `RecordedInput::cases()`
+/// parses every test case up front, so that each one can be benchmarked on its own, e.g. by
+/// calling `b.iter(|| solve(&cases[i]))` in a benchmark named after test case `i + 1`. Neither
+/// method depends on a particular harness, so any benchmarking crate can be used.
+#[derive(Clone, Debug)]
+pub struct RecordedInput {
+    /// The text of the input file.
+    text: String,
+    /// How the test cases in the input file are delimited.
+    case_mode: CaseMode,
+}
+
+impl RecordedInput {
+    /// Reads an input file into memory, expecting it to begin with the number of test cases.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the input file cannot be read, or is not valid UTF-8.
+    pub fn open<I: Input>(input: I) -> RecordedInput {
+        RecordedInput::from_text(read_input(input, InputEncoding::Utf8))
+    }
+
+    /// Creates a recorded input file from its text, expecting it to begin with the number of test
+    /// cases.
+    pub fn from_text<S: Into<String>>(text: S) -> RecordedInput {
+        RecordedInput {
+            text: text.into(),
+            case_mode: CaseMode::Counted,
+        }
+    }
+
+    /// Sets how the test cases in the input file are delimited, as with `TestEngine::case_mode()`.
+    pub fn case_mode(mut self, mode: CaseMode) -> RecordedInput {
+        self.case_mode = mode;
+        self
+    }
+
+    /// Parses every test case in the input file, returning the data for each test case in order.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the number of test cases cannot be read.
+    pub fn cases<'a, D, P: Fn(&mut InputReader<'a>) -> D>(&'a self, p: P) -> Vec<D> {
+        let mut reader = InputReader::new(&self.text);
+        let mut cases = CaseIter::new(self.case_mode, &mut reader);
+        let mut data = Vec::with_capacity(0);
+        data.reserve_exact(cases.size_hint());
+        while let Some(d) = cases.next_case(&mut reader, &|reader| Some(p(reader))) {
+            data.push(d);
+        }
+        data
+    }
+
+    /// Parses and solves every test case in the input file with a default test engine, formatting
+    /// each test result as usual but discarding the output, and returns the number of test cases
+    /// solved.
+    ///
+    /// Test cases are solved in parallel if the `parallel` feature is enabled.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the number of test cases cannot be read.
+    pub fn run<
+        D: Sized + Send + Sync,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader) -> D,
+        S: Fn(&D) -> R + Sync
+    >
+        (
+        &self,
+        p: P,
+        s: S,
+    ) -> usize {
+        TestEngine::with_io(self.text.as_bytes(), io::sink())
+            .case_mode(self.case_mode)
+            .execute(|reader| Some(p(reader)), s, |_, _, _| {})
+    }
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Consumes the test engine, benchmarking a parser and solver over the full input file.
+    ///
+    /// The input file is read into memory and solved once as a warm-up, writing test results to
+    /// the output file as usual. It is then solved a further `runs` times, discarding the test
+    /// results. Afterwards, the minimum, median and maximum total time per run (including
+    /// parsing) is written to standard error, along with the distribution of solver time per test
+    /// case, using the median time for each test case across all runs.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `runs` is zero, or in the event of an I/O error.
+    pub fn run_benchmark<
+        D: Sized + Send + Sync,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader) -> D,
+        S: Fn(&D) -> R + Sync
+    >
+        (
+        self,
+        runs: usize,
+        p: P,
+        s: S,
+    ) {
+        assert!(runs > 0, "input must be benchmarked at least once");
+        let input = read_input(self.input, self.settings.input_encoding);
+        let mut settings = self.settings;
+        settings.input_encoding = InputEncoding::Utf8;
+        let _ = TestEngine {
+            input: FromReader(input.as_bytes()),
+            output: self.output,
+            settings: settings.clone(),
+        }.execute(|reader| Some(p(reader)), &s, |_, _, _| {});
+        let mut totals = Vec::with_capacity(0);
+        totals.reserve_exact(runs);
+        let mut case_times: Vec<Vec<Duration>> = Vec::new();
+        for _ in 0..runs {
+            let start = Instant::now();
+            let _ = TestEngine {
+                input: FromReader(input.as_bytes()),
+                output: ToWriter(io::sink()),
+                settings: settings.clone(),
+            }.execute(|reader| Some(p(reader)), &s, |case, _, time| {
+                if case_times.len() < case {
+                    case_times.resize(case, Vec::new());
+                }
+                case_times[case - 1].push(time.solve);
+            });
+            totals.push(start.elapsed());
+        }
+        totals.sort();
+        eprintln!("benchmark: {} runs after 1 warm-up run", runs);
+        eprintln!(
+            "total time: min {}, median {}, max {}",
+            format_duration(totals[0]),
+            format_duration(median(&totals)),
+            format_duration(totals[totals.len() - 1]),
+        );
+        let mut per_case: Vec<(Duration, usize)> = case_times
+            .iter_mut()
+            .enumerate()
+            .map(|(i, times)| {
+                times.sort();
+                (median(times), i + 1)
+            })
+            .collect();
+        per_case.sort();
+        if let Some(&(slowest, slowest_case)) = per_case.last() {
+            let times: Vec<Duration> = per_case.iter().map(|&(t, _)| t).collect();
+            eprintln!(
+                "time per case: min {}, median {}, p90 {}, max {} (Case #{})",
+                format_duration(times[0]),
+                format_duration(median(&times)),
+                format_duration(times[(times.len() - 1) * 9 / 10]),
+                format_duration(slowest),
+                slowest_case,
+            );
+        }
+    }
+}
+
+/// Returns the median of a sorted, non-empty slice of durations.
+fn median(sorted: &[Duration]) -> Duration {
+    sorted[sorted.len() / 2]
+}
@@ -0,0 +1,238 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `cargo gcj bundle`, which combines a solution and `gcj-helper` into a single source file.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The directory containing the `gcj-helper` sources this binary was built from.
+const LIB_SRC: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src");
+
+/// The usage message for this subcommand.
+const USAGE: &str = "usage: cargo gcj bundle <solution.rs> [<output.rs>] [--lib-src <dir>]";
+
+/// Combines a solution and `gcj-helper` into one self-contained source file, for judges that
+/// only accept single-file submissions.
+///
+/// Modules declared by the solution (`mod foo;`) are inlined recursively, and `gcj-helper` itself
+/// is inlined as a module named `gcj_helper`, so existing `use gcj_helper::...` imports keep
+/// working. `#[cfg(...)]` attributes are preserved as written; since the bundle is compiled
+/// without Cargo features, it uses the sequential code paths of both the solution and
+/// `gcj-helper`. Procedural macros cannot be bundled, so solutions using `#[gcj_helper::main]` or
+/// the derive macros are rejected.
+///
+/// The bundle is written to the output path if one is given, or standard output otherwise.
+/// `--lib-src` overrides where the `gcj-helper` sources are read from, which defaults to the
+/// sources this binary was built from.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut paths = Vec::new();
+    let mut lib_src = PathBuf::from(LIB_SRC);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--lib-src" {
+            lib_src = PathBuf::from(args.next().ok_or_else(|| USAGE.to_owned())?);
+        } else {
+            paths.push(arg);
+        }
+    }
+    let (solution, output) = match paths[..] {
+        [solution] => (solution, None),
+        [solution, output] => (solution, Some(output)),
+        _ => return Err(USAGE.to_owned()),
+    };
+    let path = Path::new(solution);
+    let solution = inline_modules(&read(path)?, path, true)?;
+    for marker in &["#[gcj_helper::main]", "FromInput)]", "Answer)]"] {
+        if solution.contains(marker) {
+            return Err(format!(
+                "solution uses a procedural macro (`{}`), which cannot be bundled",
+                marker
+            ));
+        }
+    }
+    let path = lib_src.join("lib.rs");
+    let lib = inline_modules(&rewrite_library_root(&read(&path)?), &path, true)?;
+    let lib: String = lib.lines()
+        .map(|line| redirect_crate_paths(line) + "\n")
+        .collect();
+    let bundle = assemble(&solution, &lib);
+    match output {
+        Some(path) => {
+            fs::write(path, bundle).map_err(|e| format!("could not write `{}`: {}", path, e))
+        }
+        None => {
+            print!("{}", bundle);
+            Ok(())
+        }
+    }
+}
+
+/// Reads a source file into a string.
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("could not read `{}`: {}", path.display(), e))
+}
+
+/// Recursively replaces each `mod name;` declaration in the source of the file at `path` with the
+/// contents of the corresponding file, wrapped in `mod name { ... }`.
+///
+/// `is_dir_owner` is `true` for crate roots and `mod.rs` files, whose submodules live in the same
+/// directory rather than in a directory named after the file.
+fn inline_modules(source: &str, path: &Path, is_dir_owner: bool) -> Result<String, String> {
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let child_dir = if is_dir_owner {
+        dir.to_path_buf()
+    } else {
+        dir.join(path.file_stem().unwrap_or_default())
+    };
+    let mut out = String::with_capacity(0);
+    out.reserve_exact(source.len());
+    for line in source.lines() {
+        match module_declaration(line) {
+            Some((prefix, name)) => {
+                let flat = child_dir.join(format!("{}.rs", name));
+                let (file, owner) = if flat.is_file() {
+                    (flat, false)
+                } else {
+                    (child_dir.join(name).join("mod.rs"), true)
+                };
+                out.push_str(prefix);
+                out.push_str("mod ");
+                out.push_str(name);
+                out.push_str(" {\n");
+                out.push_str(&inline_modules(&read(&file)?, &file, owner)?);
+                out.push_str("}\n");
+            }
+            None => {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// If a line is an out-of-line module declaration such as `pub mod foo;`, returns the text before
+/// `mod` and the module name.
+fn module_declaration(line: &str) -> Option<(&str, &str)> {
+    let trimmed = line.trim();
+    let name = trimmed.strip_suffix(';')?;
+    let (prefix, name) = match name.rfind("mod ") {
+        Some(i) => (&name[..i], name[i + 4..].trim()),
+        None => return None,
+    };
+    let valid_prefix = matches!(prefix.trim(), "" | "pub" | "pub(crate)");
+    let valid_name = !name.is_empty() && name.chars().all(|c| c == '_' || c.is_alphanumeric());
+    if valid_prefix && valid_name {
+        Some((&line[..line.len() - line.trim_start().len() + prefix.len()], name))
+    } else {
+        None
+    }
+}
+
+/// Adapts the `gcj-helper` crate root for use as a module.
+///
+/// Crate-level attributes and documentation are removed, and crate-relative `use` declarations
+/// are made relative to the module.
+fn rewrite_library_root(lib: &str) -> String {
+    let modules: Vec<&str> = lib.lines()
+        .filter_map(|line| module_declaration(line).map(|(_, name)| name))
+        .collect();
+    let mut out = String::with_capacity(0);
+    out.reserve_exact(lib.len());
+    for line in lib.lines() {
+        if line.starts_with("#![") || line.starts_with("//!") {
+            continue;
+        }
+        let mut line = line.to_owned();
+        for keyword in &["use ", "pub use "] {
+            if modules.iter().any(|m| line.starts_with(&format!("{}{}::", keyword, m))) {
+                line = format!("{}self::{}", keyword, &line[keyword.len()..]);
+            }
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Rewrites `$crate::item` to `$crate::gcj_helper::item`, except where `item` is a macro.
+fn redirect_crate_paths(line: &str) -> String {
+    let mut out = String::with_capacity(0);
+    out.reserve_exact(line.len());
+    let mut rest = line;
+    while let Some(i) = rest.find("$crate::") {
+        let (before, after) = rest.split_at(i + "$crate::".len());
+        out.push_str(before);
+        let ident_len = after
+            .find(|c: char| c != '_' && !c.is_alphanumeric())
+            .unwrap_or(after.len());
+        if !after[ident_len..].starts_with('!') {
+            out.push_str("gcj_helper::");
+        }
+        rest = after;
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Assembles the final bundle from the inlined solution and library.
+///
+/// The solution's crate-level attributes and documentation must precede all items, so they are
+/// hoisted above the library module, along with any comments before them, such as a license
+/// header. `extern crate gcj_helper;` declarations are removed, since the library is now
+/// a module of the same name, and imports from it are made crate-relative so that they resolve
+/// from inlined submodules under any edition.
+fn assemble(solution: &str, lib: &str) -> String {
+    let mut header = String::new();
+    let mut body = String::new();
+    let mut in_header = true;
+    let mut pending_attr: Option<&str> = None;
+    for line in solution.lines() {
+        let trimmed = line.trim();
+        let is_comment = trimmed.starts_with("//") && !trimmed.starts_with("///");
+        if in_header && (trimmed.starts_with("#![") || is_comment) {
+            header.push_str(line);
+            header.push('\n');
+            continue;
+        }
+        if in_header && trimmed.is_empty() {
+            continue;
+        }
+        in_header = false;
+        if trimmed == "#[macro_use]" {
+            pending_attr = Some(line);
+            continue;
+        }
+        if trimmed == "extern crate gcj_helper;" {
+            pending_attr = None;
+            continue;
+        }
+        let line = line.replacen("use gcj_helper::", "use crate::gcj_helper::", 1);
+        if let Some(attr) = pending_attr.take() {
+            body.push_str(attr);
+            body.push('\n');
+        }
+        body.push_str(&line);
+        body.push('\n');
+    }
+    let mut bundle = String::with_capacity(0);
+    bundle.reserve_exact(header.len() + lib.len() + body.len() + 256);
+    bundle.push_str(&header);
+    bundle.push_str(
+        "// This file was generated by `cargo gcj bundle`, and includes the source code of\n\
+         // `gcj-helper` (https://github.com/FaultyRAM/gcj-helper-rs), licensed under the Apache\n\
+         // License, Version 2.0 or the MIT license, at your option.\n\n\
+         #[macro_use]\n\
+         #[allow(dead_code, unused_imports, unused_macros)]\n\
+         mod gcj_helper {\n",
+    );
+    bundle.push_str(lib);
+    bundle.push_str("}\n\n");
+    bundle.push_str(&body);
+    bundle
+}
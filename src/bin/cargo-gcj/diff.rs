@@ -0,0 +1,111 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `cargo gcj diff`, which compares two output files case by case.
+
+use gcj_helper::pair_cases;
+use std::fs;
+
+/// The usage message for this subcommand.
+const USAGE: &str = "usage: cargo gcj diff <expected.out> <actual.out>";
+
+/// The maximum width of the left-hand column.
+const MAX_COLUMN_WIDTH: usize = 60;
+
+/// Compares two output files by `"Case #N:"` block, printing the expected and actual lines of
+/// each mismatched test case side by side.
+///
+/// Since test cases are paired by their case numbers and compared independently, a mismatch in
+/// one case (even one with a different number of lines), or a case missing from or extra in the
+/// actual output, does not affect the comparison of later cases. Missing and extra cases are
+/// listed separately from mismatched ones. Line endings and trailing whitespace are ignored.
+/// Returns an error if any test case differs, is missing or is extra.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let (expected_path, actual_path) = match args {
+        [expected, actual] => (expected, actual),
+        _ => return Err(USAGE.to_owned()),
+    };
+    let expected = normalize(&read(expected_path)?);
+    let actual = normalize(&read(actual_path)?);
+    let pairs = pair_cases(&expected, &actual);
+    let mut mismatches = 0;
+    let mut missing = Vec::new();
+    let mut extra = Vec::new();
+    for &pair in &pairs {
+        match pair {
+            (Some(e), Some(a)) => {
+                let (e, a) = (e.trim_end(), a.trim_end());
+                if e != a {
+                    mismatches += 1;
+                    print_side_by_side(&case_label(e), e, a, expected_path, actual_path);
+                }
+            }
+            (Some(e), None) => missing.push(case_label(e)),
+            (None, Some(a)) => extra.push(case_label(a)),
+            (None, None) => {}
+        }
+    }
+    if !missing.is_empty() {
+        println!("missing from {}: {}", actual_path, missing.join(", "));
+    }
+    if !extra.is_empty() {
+        println!("not in {}: {}", expected_path, extra.join(", "));
+    }
+    let case_count = pairs.len();
+    if mismatches == 0 && missing.is_empty() && extra.is_empty() {
+        eprintln!("all {} cases match", case_count);
+        Ok(())
+    } else {
+        Err(format!(
+            "{} of {} cases differ, {} missing, {} extra",
+            mismatches,
+            case_count,
+            missing.len(),
+            extra.len()
+        ))
+    }
+}
+
+/// Reads an output file into a string.
+fn read(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("could not read `{}`: {}", path, e))
+}
+
+/// Normalizes line endings to `"\n"`, and removes trailing whitespace from each line.
+fn normalize(output: &str) -> String {
+    output.lines().map(|line| line.trim_end().to_owned() + "\n").collect()
+}
+
+/// Returns a label for a block, taken from its `"Case #N:"` header if it has one.
+fn case_label(block: &str) -> String {
+    match block.strip_prefix("Case #").and_then(|rest| rest.split(':').next()) {
+        Some(n) => format!("Case #{}", n),
+        None => "text before the first case".to_owned(),
+    }
+}
+
+/// Prints two blocks of lines side by side, marking lines that differ with `|`.
+fn print_side_by_side(label: &str, expected: &str, actual: &str, left: &str, right: &str) {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let width = expected
+        .iter()
+        .map(|line| line.chars().count())
+        .chain(Some(left.chars().count()))
+        .max()
+        .unwrap_or(0)
+        .min(MAX_COLUMN_WIDTH);
+    println!("{}:", label);
+    println!("  {:width$}   {}", left, right, width = width);
+    for i in 0..expected.len().max(actual.len()) {
+        let e = expected.get(i).cloned().unwrap_or("");
+        let a = actual.get(i).cloned().unwrap_or("");
+        let marker = if e == a { ' ' } else { '|' };
+        println!("  {:width$} {} {}", e, marker, a, width = width);
+    }
+    println!();
+}
@@ -0,0 +1,98 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `cargo gcj fetch`, which downloads input files into the `inputs/` directory.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The usage message for this subcommand.
+const USAGE: &str = "usage: cargo gcj fetch <url>... [--token <token>] [--cookie <cookie>]";
+
+/// The directory input files are downloaded to.
+const INPUT_DIR: &str = "inputs";
+
+/// Downloads one or more input files into `inputs/`, named after the last segment of each URL.
+///
+/// Requests are authenticated with a bearer token given by `--token` or the `GCJ_TOKEN`
+/// environment variable, and/or a cookie string given by `--cookie` or the `GCJ_COOKIE`
+/// environment variable, as required by the judge. Downloads are performed by `curl`, which must
+/// be installed. Once finished, the command needed to run the solution on each file is printed;
+/// inputs downloaded under their Code Jam names can also be found with
+/// `TestEngine::from_attempt("inputs", ...)`.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut urls = Vec::new();
+    let mut token = env::var("GCJ_TOKEN").ok();
+    let mut cookie = env::var("GCJ_COOKIE").ok();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--token" => token = Some(args.next().ok_or_else(|| USAGE.to_owned())?.clone()),
+            "--cookie" => cookie = Some(args.next().ok_or_else(|| USAGE.to_owned())?.clone()),
+            _ => urls.push(arg),
+        }
+    }
+    if urls.is_empty() {
+        return Err(USAGE.to_owned());
+    }
+    fs::create_dir_all(INPUT_DIR).map_err(|e| format!("could not create `{}`: {}", INPUT_DIR, e))?;
+    let mut paths = Vec::with_capacity(0);
+    paths.reserve_exact(urls.len());
+    for url in urls {
+        let path = Path::new(INPUT_DIR).join(file_name(url)?);
+        download(url, &path, token.as_ref(), cookie.as_ref())?;
+        eprintln!("downloaded `{}`", path.display());
+        paths.push(path);
+    }
+    for path in paths {
+        eprintln!(
+            "run with: cargo run --release -- {} {}",
+            path.display(),
+            path.with_extension("out").display()
+        );
+    }
+    Ok(())
+}
+
+/// Determines the file name to save a URL's contents under, from its last path segment.
+fn file_name(url: &str) -> Result<PathBuf, String> {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    match path.rfind('/') {
+        Some(i) if i + 1 < path.len() && !path[..i].ends_with('/') => {
+            Ok(PathBuf::from(&path[i + 1..]))
+        }
+        _ => Err(format!("could not determine a file name for `{}`", url)),
+    }
+}
+
+/// Downloads a URL to a file using `curl`.
+fn download(
+    url: &str,
+    path: &Path,
+    token: Option<&String>,
+    cookie: Option<&String>,
+) -> Result<(), String> {
+    let mut curl = Command::new("curl");
+    let _ = curl.args(["--fail", "--silent", "--show-error", "--location", "--output"])
+        .arg(path);
+    if let Some(token) = token {
+        let _ = curl.arg("--header").arg(format!("Authorization: Bearer {}", token));
+    }
+    if let Some(cookie) = cookie {
+        let _ = curl.arg("--cookie").arg(cookie);
+    }
+    let status = curl.arg(url)
+        .status()
+        .map_err(|e| format!("could not run curl: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("could not download `{}`", url))
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `cargo gcj history`, which lists and compares the runs recorded in a run history file.
+
+use gcj_helper::{RunRecord, HISTORY_FILE_NAME};
+
+/// The usage message for this subcommand.
+const USAGE: &str = "usage: cargo gcj history [<history-file>] [<run> <run>]";
+
+/// Lists every run recorded in a run history file (`.gcj-history` by default), numbered from 1,
+/// comparing each with the run before it, or compares two runs given by number.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let (path, pair) = match args {
+        [] => (HISTORY_FILE_NAME, None),
+        [path] => (path.as_str(), None),
+        [a, b] => (HISTORY_FILE_NAME, Some((a, b))),
+        [path, a, b] => (path.as_str(), Some((a, b))),
+        _ => return Err(USAGE.to_owned()),
+    };
+    let records = RunRecord::load_history(path);
+    if records.is_empty() {
+        return Err(format!("no runs recorded in `{}`", path));
+    }
+    if let Some((a, b)) = pair {
+        let earlier = find(&records, a)?;
+        let later = find(&records, b)?;
+        println!("run {}: {}", a, earlier.summary());
+        println!("run {}: {}", b, later.summary());
+        println!("run {} vs run {}: {}", b, a, later.compare(earlier));
+        return Ok(());
+    }
+    for (i, record) in records.iter().enumerate() {
+        match i.checked_sub(1).map(|j| &records[j]) {
+            Some(previous) => println!(
+                "run {}: {} ({})",
+                i + 1,
+                record.summary(),
+                record.compare(previous)
+            ),
+            None => println!("run {}: {}", i + 1, record.summary()),
+        }
+    }
+    if let Some((i, _)) = records
+        .iter()
+        .enumerate()
+        .filter(|&(_, record)| record.status == "ok")
+        .min_by_key(|&(_, record)| record.wall_time)
+    {
+        println!("fastest successful run: run {}", i + 1);
+    }
+    Ok(())
+}
+
+/// Finds a run by its number, counting from 1.
+fn find<'a>(records: &'a [RunRecord], number: &str) -> Result<&'a RunRecord, String> {
+    number
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| records.get(i))
+        .ok_or_else(|| format!("no run numbered `{}`; there are {} runs", number, records.len()))
+}
@@ -0,0 +1,58 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `cargo gcj`, a Cargo subcommand providing tools for writing Google Code Jam solutions.
+
+#[cfg(target_os = "wasi")]
+compile_error!("`cargo gcj` runs other processes, which WASI does not support");
+
+extern crate gcj_helper;
+
+mod bundle;
+mod diff;
+mod fetch;
+mod history;
+mod new;
+mod validate;
+mod watch;
+
+use std::env;
+use std::process;
+
+/// The usage message printed when no valid subcommand is given.
+const USAGE: &str = "usage: cargo gcj <command> [<args>...]
+
+commands:
+    bundle <solution.rs> [<output.rs>]    combine a solution and gcj-helper into one file
+    diff <expected.out> <actual.out>      compare two output files case by case
+    fetch <url>...                        download input files into `inputs/`
+    history [<file>] [<run> <run>]        list or compare the runs in a run history file
+    new <name>                            create a new solution crate
+    validate <input.in> <output.out>      check an output file's structure before uploading
+    watch [<crate-dir>]                   re-check samples whenever the solution changes";
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    // When invoked via `cargo gcj`, Cargo passes the subcommand name as the first argument.
+    if args.first().map(|a| a.as_str()) == Some("gcj") {
+        let _ = args.remove(0);
+    }
+    let result = match args.first().map(|a| a.as_str()) {
+        Some("bundle") => bundle::run(&args[1..]),
+        Some("diff") => diff::run(&args[1..]),
+        Some("fetch") => fetch::run(&args[1..]),
+        Some("history") => history::run(&args[1..]),
+        Some("new") => new::run(&args[1..]),
+        Some("validate") => validate::run(&args[1..]),
+        Some("watch") => watch::run(&args[1..]),
+        _ => Err(USAGE.to_owned()),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `cargo gcj new`, which scaffolds a new solution crate.
+
+use std::fs;
+use std::path::Path;
+
+/// The template for `Cargo.toml`; `{name}` is replaced with the crate name.
+const CARGO_TOML: &str = r#"[package]
+name = "{name}"
+version = "0.1.0"
+publish = false
+
+[dependencies]
+gcj-helper = { version = "0.5", features = ["derive"] }
+"#;
+
+/// The template for `src/main.rs`.
+const MAIN_RS: &str = r#"extern crate gcj_helper;
+
+use gcj_helper::{InputReader, Problem, Sample, TestEngine};
+
+struct Solution;
+
+#[gcj_helper::main]
+impl Problem for Solution {
+    type Data = String;
+    type Result = String;
+
+    fn parse(input: &mut InputReader) -> String {
+        input.read_next_line().to_owned()
+    }
+
+    fn solve(data: &String) -> String {
+        format!(" {}\n", data)
+    }
+}
+
+#[test]
+fn samples() {
+    const SAMPLES: &[Sample] = gcj_helper::include_samples!("../samples/sample");
+    assert!(TestEngine::stdio().check_samples(SAMPLES, &Solution::parse, &Solution::solve));
+}
+"#;
+
+/// The template for `gcj.toml`, the problem manifest.
+const GCJ_TOML: &str = r#"# Read by `TestEngine::from_manifest()` and `cargo gcj watch`.
+format = "gcj"
+samples = ["samples/sample"]
+# input = "inputs/A-large.in"
+# time_limit = 20
+"#;
+
+/// The placeholder sample input file, which the `Problem` stub solves.
+const SAMPLE_IN: &str = "2\nhello\nworld\n";
+
+/// The expected output of the placeholder sample input file.
+const SAMPLE_OUT: &str = "Case #1: hello\nCase #2: world\n";
+
+/// The `.gitignore` for the new crate.
+const GITIGNORE: &str = "target/\nCargo.lock\ninputs/\n*.out\n!samples/*.out\n";
+
+/// Creates a new solution crate named after the first argument, in a directory of the same name.
+///
+/// The crate contains a `Problem` stub wired up with `#[gcj_helper::main]`, a `gcj.toml` manifest,
+/// a placeholder sample input and output file under `samples/`, which the stub passes until they
+/// are replaced with the problem's samples, and a test checking the solution against the samples.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let name = match args {
+        [name] => name,
+        _ => return Err("usage: cargo gcj new <name>".to_owned()),
+    };
+    let root = Path::new(name);
+    if root.exists() {
+        return Err(format!("destination `{}` already exists", root.display()));
+    }
+    let files = [
+        ("Cargo.toml", CARGO_TOML.replace("{name}", name)),
+        (".gitignore", GITIGNORE.to_owned()),
+        ("gcj.toml", GCJ_TOML.to_owned()),
+        ("src/main.rs", MAIN_RS.to_owned()),
+        ("samples/sample.in", SAMPLE_IN.to_owned()),
+        ("samples/sample.out", SAMPLE_OUT.to_owned()),
+    ];
+    for &(path, ref contents) in &files {
+        let path = root.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("could not create `{}`: {}", parent.display(), e))?;
+        }
+        fs::write(&path, contents)
+            .map_err(|e| format!("could not write `{}`: {}", path.display(), e))?;
+    }
+    eprintln!("created solution crate `{}`", name);
+    Ok(())
+}
@@ -0,0 +1,41 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `cargo gcj validate`, which checks the structure of an output file before it is uploaded.
+
+use gcj_helper::validate_output;
+use std::fs;
+
+/// The usage message for this subcommand.
+const USAGE: &str = "usage: cargo gcj validate <input.in> <output.out>";
+
+/// Checks that an output file has one `"Case #N:"` header per test case in the input file,
+/// numbered in order with none missing or extra, and ends with a newline, printing each problem
+/// found. Returns an error if any problem is found.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let (input_path, output_path) = match args {
+        [input, output] => (input, output),
+        _ => return Err(USAGE.to_owned()),
+    };
+    let input = read(input_path)?;
+    let output = read(output_path)?;
+    let problems = validate_output(&input, &output);
+    if problems.is_empty() {
+        eprintln!("`{}` looks valid", output_path);
+        Ok(())
+    } else {
+        for problem in &problems {
+            println!("{}", problem);
+        }
+        Err(format!("found {} problems in `{}`", problems.len(), output_path))
+    }
+}
+
+/// Reads an input or output file into a string.
+fn read(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("could not read `{}`: {}", path, e))
+}
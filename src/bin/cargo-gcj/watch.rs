@@ -0,0 +1,165 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `cargo gcj watch`, which re-checks a solution against its samples whenever it changes.
+
+use gcj_helper::{mismatched_cases, Manifest, MANIFEST_FILE_NAME};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// The usage message for this subcommand.
+const USAGE: &str = "usage: cargo gcj watch [<crate-dir>]";
+
+/// How often watched files are polled for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Watches a solution crate created by `cargo gcj new`, rebuilding it and checking it against
+/// each sample whenever `Cargo.toml`, `gcj.toml`, a source file or a sample file changes.
+///
+/// The samples listed in `gcj.toml` are used if it exists; otherwise, each `samples/<name>.in`
+/// file with a matching `samples/<name>.out` file is used. Each sample input is passed to the
+/// solution via `cargo run -- <input> <output>`, with output going to `target/gcj-watch/`. The
+/// expected and actual results of any mismatched test cases are then printed. This function only
+/// returns on error.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let dir = match args {
+        [] => PathBuf::from("."),
+        [dir] => PathBuf::from(dir),
+        _ => return Err(USAGE.to_owned()),
+    };
+    if !dir.join("Cargo.toml").is_file() {
+        return Err(format!("`{}` does not contain a Cargo.toml", dir.display()));
+    }
+    let mut last_change = None;
+    loop {
+        let change = latest_modification(&dir);
+        if change != last_change {
+            last_change = change;
+            check(&dir)?;
+            eprintln!("watching for changes...");
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Returns the most recent modification time of any watched file in a solution crate.
+fn latest_modification(dir: &Path) -> Option<SystemTime> {
+    let mut latest = modified(&dir.join("Cargo.toml")).max(modified(&dir.join(MANIFEST_FILE_NAME)));
+    for subdir in &["src", "samples"] {
+        latest = latest.max(latest_in_dir(&dir.join(subdir)));
+    }
+    latest
+}
+
+/// Returns the most recent modification time of any file in a directory tree.
+fn latest_in_dir(dir: &Path) -> Option<SystemTime> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return None,
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                latest_in_dir(&path)
+            } else {
+                modified(&path)
+            }
+        })
+        .max()
+        .and_then(|latest| latest)
+}
+
+/// Returns the modification time of a file, if it can be determined.
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Rebuilds a solution crate and checks it against each of its samples.
+fn check(dir: &Path) -> Result<(), String> {
+    eprintln!("building...");
+    if !cargo(dir, &["build", "--quiet"])? {
+        return Ok(());
+    }
+    let out_dir = dir.join("target").join("gcj-watch");
+    // Paths are passed to `cargo run` in absolute form, since the solution runs in `dir`.
+    let out_dir = fs::create_dir_all(&out_dir)
+        .and_then(|_| out_dir.canonicalize())
+        .map_err(|e| format!("could not create `{}`: {}", out_dir.display(), e))?;
+    let samples = find_samples(dir);
+    if samples.is_empty() {
+        eprintln!("no samples found");
+    }
+    for input in samples {
+        let name = input.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        let output = out_dir.join(format!("{}.out", name));
+        let _ = fs::remove_file(&output);
+        let input = input.canonicalize().unwrap_or(input);
+        let args = [
+            "run",
+            "--quiet",
+            "--",
+            &input.to_string_lossy(),
+            &output.to_string_lossy(),
+        ];
+        if !cargo(dir, &args)? {
+            eprintln!("sample {}: FAILED (solution exited with an error)", name);
+            continue;
+        }
+        let expected = fs::read_to_string(input.with_extension("out"))
+            .map_err(|e| format!("could not read expected output for `{}`: {}", name, e))?;
+        let actual = fs::read_to_string(&output).unwrap_or_default();
+        let mismatches = mismatched_cases(&expected, &actual);
+        if mismatches.is_empty() {
+            eprintln!("sample {}: ok", name);
+        } else {
+            eprintln!("sample {}: FAILED", name);
+            for (expected, actual) in mismatches {
+                eprintln!("  expected: {:?}", expected);
+                eprintln!("    actual: {:?}", actual);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the input file path of each sample in a solution crate.
+fn find_samples(dir: &Path) -> Vec<PathBuf> {
+    let manifest = dir.join(MANIFEST_FILE_NAME);
+    if manifest.is_file() {
+        return Manifest::load(manifest)
+            .samples
+            .iter()
+            .map(|stem| stem.with_extension("in"))
+            .collect();
+    }
+    let mut samples: Vec<PathBuf> = match fs::read_dir(dir.join("samples")) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension() == Some("in".as_ref()))
+            .filter(|path| path.with_extension("out").is_file())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    samples.sort();
+    samples
+}
+
+/// Runs Cargo in a solution crate, returning whether it succeeded.
+fn cargo(dir: &Path, args: &[&str]) -> Result<bool, String> {
+    Command::new("cargo")
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .map(|status| status.success())
+        .map_err(|e| format!("could not run cargo: {}", e))
+}
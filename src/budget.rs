@@ -0,0 +1,91 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Ending a run early, with placeholder answers, once its wall-clock budget is spent.
+
+use super::{format_duration, logging, Clock, Input, Output, TestEngine};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The text written in place of the test result of each test case not started within the run
+/// budget, by default.
+pub const DEFAULT_PLACEHOLDER_ANSWER: &str = " 0\n";
+
+/// Tracks a run's wall-clock budget, if it has one.
+#[derive(Clone, Debug)]
+pub struct Budget {
+    /// The budget, when the run began, and the clock it is measured by, if the run has a budget.
+    limit: Option<(Duration, Duration, Arc<dyn Clock>)>,
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Sets a budget for the wall time of the run: once `budget` has elapsed since the run began,
+    /// no further test cases are started, and a placeholder answer is written for each remaining
+    /// test case.
+    ///
+    /// This produces a complete output file, which may at least score the easy test cases, rather
+    /// than nothing when a submission window is about to close; choose a budget that leaves time
+    /// for the test cases in progress to finish. Test cases already being solved when the budget
+    /// runs out are still finished and written. A warning stating how many test cases received
+    /// placeholders is written to standard error and included in the run summary and JSON report.
+    ///
+    /// To count the remaining test cases of an input file without a case count, they are parsed
+    /// but not solved. By default, runs have no budget.
+    pub fn run_budget(mut self, budget: Duration) -> TestEngine<I, O> {
+        self.settings.run_budget = Some(budget);
+        self
+    }
+
+    /// Sets the text written in place of the test result of each test case not started within the
+    /// run budget set with `TestEngine::run_budget()`.
+    ///
+    /// As with any other test result, the text is written immediately after the `"Case #N:"`
+    /// header, so it should usually begin with a space and end with a newline. The default is
+    /// `" 0\n"`.
+    pub fn placeholder_answer<S: Into<String>>(mut self, text: S) -> TestEngine<I, O> {
+        self.settings.placeholder_answer = text.into();
+        self
+    }
+}
+
+impl Budget {
+    /// Starts tracking the budget of a run beginning now, as measured by `clock`, if it has one.
+    pub fn start(budget: Option<Duration>, clock: &Arc<dyn Clock>) -> Budget {
+        Budget {
+            limit: budget.map(|budget| (budget, clock.monotonic(), Arc::clone(clock))),
+        }
+    }
+
+    /// Returns `true` if the run's budget has been spent, so that no further test cases should be
+    /// started.
+    pub fn exhausted(&self) -> bool {
+        self.limit.as_ref().is_some_and(|&(budget, start, ref clock)| {
+            clock.monotonic().saturating_sub(start) >= budget
+        })
+    }
+
+    /// Reports that the run's budget was spent after `solved` test cases, leaving `skipped` test
+    /// cases with placeholder answers, returning the warning raised.
+    pub fn report(&self, solved: usize, skipped: usize) -> String {
+        let budget = self
+            .limit
+            .as_ref()
+            .map_or(Duration::new(0, 0), |&(budget, _, _)| budget);
+        let warning = format!(
+            concat!(
+                "run budget of {} spent after {} test cases; ",
+                "wrote placeholder answers for {} test cases"
+            ),
+            format_duration(budget),
+            solved,
+            skipped
+        );
+        eprintln!("warning: {}", warning);
+        logging::warn(&warning);
+        warning
+    }
+}
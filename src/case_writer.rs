@@ -0,0 +1,112 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Solvers that write their test results piece by piece.
+
+use super::{Input, InputReader, Output, TestEngine};
+use std::fmt::{self, Arguments, Display, Formatter, Write};
+
+/// The test result of one test case, written piece by piece by a solver passed to
+/// `TestEngine::run_streaming()`.
+///
+/// Text is usually written with the `write_case!` and `writeln_case!` macros, which take the same
+/// arguments as `write!` and `writeln!`. The `"Case #N:"` header is written by the test engine, so
+/// the first piece of text written directly after it is preceded by a space, e.g.
+/// `write_case!(w, "{} {}", a, b)` produces `"Case #N: a b"`; text beginning with a newline, such
+/// as that written by `writeln_case!(w)`, is not. If the test result does not end with a newline,
+/// one is added once the solver returns.
+///
+/// `CaseWriter` also implements `fmt::Write`, for writing text exactly as given.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CaseWriter {
+    /// The test result written so far.
+    answer: String,
+}
+
+impl CaseWriter {
+    /// Creates an empty test result.
+    pub fn new() -> CaseWriter {
+        CaseWriter::default()
+    }
+
+    /// Appends formatted text to the test result, preceded by a space if nothing has been written
+    /// yet and the text does not begin with a newline.
+    ///
+    /// This is normally called via `write_case!`.
+    pub fn write_answer(&mut self, args: Arguments) {
+        let start = self.answer.len();
+        let _ = self.answer.write_fmt(args);
+        if start == 0 && !self.answer.is_empty() && !self.answer.starts_with(['\n', '\r']) {
+            self.answer.insert(0, ' ');
+        }
+    }
+
+    /// Appends formatted text and a newline to the test result, preceded by a space if nothing has
+    /// been written yet and the text is neither empty nor begins with a newline.
+    ///
+    /// This is normally called via `writeln_case!`.
+    pub fn writeln_answer(&mut self, args: Arguments) {
+        self.write_answer(args);
+        self.answer.push('\n');
+    }
+
+    /// Returns the test result written so far.
+    pub fn as_str(&self) -> &str {
+        &self.answer
+    }
+}
+
+impl Write for CaseWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.answer.push_str(s);
+        Ok(())
+    }
+}
+
+impl Display for CaseWriter {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(&self.answer)?;
+        if self.answer.ends_with('\n') {
+            Ok(())
+        } else {
+            f.write_str("\n")
+        }
+    }
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Consumes the test engine, executing a parser once per test case, and a solver that writes
+    /// the test result to a `CaseWriter` rather than returning it.
+    ///
+    /// This makes solvers for multi-line or incrementally built answers as terse as those that
+    /// return `format!()`, e.g. `|d, w| { writeln_case!(w); for row in &d.grid { ... } }`. Test
+    /// results are written to the output file in order, as with `TestEngine::run()`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics in the event of an I/O error.
+    pub fn run_streaming<
+        D: Sized + Send + Sync,
+        P: Fn(&mut InputReader) -> D,
+        S: Fn(&D, &mut CaseWriter) + Sync
+    >
+        (
+        self,
+        p: P,
+        s: S,
+    ) {
+        let _ = self.execute(
+            |reader| Some(p(reader)),
+            |d| {
+                let mut writer = CaseWriter::new();
+                s(d, &mut writer);
+                writer
+            },
+            |_, _, _| {},
+        );
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Chains of fallback solvers.
+
+use super::{Input, InputReader, Output, TestEngine};
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// An ordered chain of solvers, each tried in turn until one produces an acceptable test result.
+///
+/// A typical chain starts with a fast heuristic and ends with an exact but slow brute-force
+/// solver. Each solver is paired with a checker deciding whether its result is accepted; if not,
+/// the next solver is tried. The result of the last solver is always accepted.
+pub struct SolverChain<'a, D, R> {
+    /// The solvers, in the order they are tried.
+    stages: Vec<Stage<'a, D, R>>,
+}
+
+/// A boxed solver.
+type BoxedSolver<'a, D, R> = Box<dyn Fn(&D) -> R + Sync + 'a>;
+
+/// A boxed checker, deciding whether a solver's result is accepted.
+type BoxedChecker<'a, D, R> = Box<dyn Fn(&D, &R) -> bool + Sync + 'a>;
+
+/// One solver in a chain.
+struct Stage<'a, D, R> {
+    /// The name of the solver, as written in the summary.
+    name: String,
+    /// The solver.
+    solve: BoxedSolver<'a, D, R>,
+    /// Decides whether a result produced by the solver is accepted.
+    accept: BoxedChecker<'a, D, R>,
+}
+
+/// A test result along with the index of the solver that produced it.
+struct Chained<R> {
+    /// The test result.
+    result: R,
+    /// The index of the solver that produced the test result.
+    stage: usize,
+}
+
+impl<'a, D, R> SolverChain<'a, D, R> {
+    /// Creates an empty chain.
+    pub fn new() -> SolverChain<'a, D, R> {
+        SolverChain { stages: Vec::new() }
+    }
+
+    /// Appends a solver to the chain, along with a checker that is called with the data for a test
+    /// case and the solver's result, and returns whether the result is accepted.
+    pub fn then<S: Fn(&D) -> R + Sync + 'a, A: Fn(&D, &R) -> bool + Sync + 'a>(
+        mut self,
+        name: &str,
+        solve: S,
+        accept: A,
+    ) -> SolverChain<'a, D, R> {
+        self.stages.push(Stage {
+            name: name.to_owned(),
+            solve: Box::new(solve),
+            accept: Box::new(accept),
+        });
+        self
+    }
+
+    /// Appends a solver whose results are always accepted, which ends the chain for practical
+    /// purposes.
+    pub fn fallback<S: Fn(&D) -> R + Sync + 'a>(
+        self,
+        name: &str,
+        solve: S,
+    ) -> SolverChain<'a, D, R> {
+        self.then(name, solve, |_, _| true)
+    }
+
+    /// Solves a test case with each solver in turn, returning the first accepted result (or the
+    /// last solver's result) along with the index of the solver that produced it.
+    fn solve(&self, d: &D) -> Chained<R> {
+        let last = self.stages.len() - 1;
+        for (i, stage) in self.stages.iter().enumerate() {
+            let result = (stage.solve)(d);
+            if i == last || (stage.accept)(d, &result) {
+                return Chained {
+                    result: result,
+                    stage: i,
+                };
+            }
+        }
+        unreachable!()
+    }
+}
+
+impl<'a, D, R> Default for SolverChain<'a, D, R> {
+    fn default() -> SolverChain<'a, D, R> {
+        SolverChain::new()
+    }
+}
+
+impl<'a, D, R> Debug for SolverChain<'a, D, R> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.stages.iter().map(|stage| &stage.name))
+            .finish()
+    }
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Consumes the test engine, executing a parser once per test case and the solvers of a
+    /// `SolverChain` until one produces an acceptable test result.
+    ///
+    /// After all test cases have been solved, the number of test cases solved by each solver is
+    /// written to standard error, along with the test cases that fell through to each solver after
+    /// the first.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the chain is empty, or in the event of an I/O error.
+    pub fn run_chain<
+        D: Sized + Send + Sync,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader) -> D
+    >
+        (
+        self,
+        p: P,
+        chain: SolverChain<D, R>,
+    ) {
+        assert!(!chain.stages.is_empty(), "solver chain must contain at least one solver");
+        let mut solved_by = vec![Vec::new(); chain.stages.len()];
+        let _ = self.execute(
+            |reader| Some(p(reader)),
+            |d| chain.solve(d),
+            |case, r, _| solved_by[r.stage].push(case),
+        );
+        eprintln!("solver chain:");
+        for (i, (stage, cases)) in chain.stages.iter().zip(&solved_by).enumerate() {
+            if i == 0 || cases.is_empty() {
+                eprintln!("  {}: {} cases", stage.name, cases.len());
+            } else {
+                let list: Vec<String> = cases.iter().map(|case| format!("#{}", case)).collect();
+                eprintln!(
+                    "  {}: {} cases (Case {})",
+                    stage.name,
+                    cases.len(),
+                    list.join(", ")
+                );
+            }
+        }
+    }
+}
+
+impl<R: Display> Display for Chained<R> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.result.fmt(f)
+    }
+}
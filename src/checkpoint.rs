@@ -0,0 +1,280 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checkpointing solved test cases so that an interrupted run can be resumed.
+
+use super::{history, logging, read_input, samples, Input, InputReader, Output, TestEngine};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsString;
+use std::fmt::{self, Display, Formatter, Write};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The first line of a checkpoint file.
+const HEADER: &str = "gcj-helper checkpoint 1";
+
+/// Where and how often `TestEngine::run_checkpointed()` saves its progress.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Checkpoint {
+    /// The path of the checkpoint file.
+    path: PathBuf,
+    /// The minimum time between saves.
+    interval: Duration,
+}
+
+/// The progress of a run, as saved to a checkpoint file.
+#[derive(Debug)]
+struct State {
+    /// A fingerprint of the input file the progress belongs to.
+    fingerprint: u64,
+    /// The test result of each solved test case, as written to the output file.
+    answers: BTreeMap<usize, String>,
+    /// The test cases being solved when the progress was saved.
+    pending: BTreeSet<usize>,
+    /// When the progress was last saved.
+    saved_at: Instant,
+}
+
+/// A test result that was either restored from a checkpoint or produced by the solver.
+enum Resumed<R> {
+    /// A test result restored from a checkpoint.
+    Saved(String),
+    /// A test result produced by the solver.
+    Solved(R),
+}
+
+impl Checkpoint {
+    /// Creates a checkpoint saved to the given path at most once per minute.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Checkpoint {
+        Checkpoint {
+            path: path.into(),
+            interval: Duration::from_secs(60),
+        }
+    }
+
+    /// Sets the minimum time between saves.
+    ///
+    /// Progress is saved when a test case starts or finishes being solved, once this much time has
+    /// passed since the previous save. Saving rewrites the whole checkpoint file, so a very short
+    /// interval slows down runs with many test cases.
+    pub fn interval(mut self, interval: Duration) -> Checkpoint {
+        self.interval = interval;
+        self
+    }
+
+    /// Returns the path of the temporary file written before replacing the checkpoint file.
+    fn temp_path(&self) -> PathBuf {
+        let mut path = OsString::from(self.path.as_os_str());
+        path.push(".tmp");
+        PathBuf::from(path)
+    }
+}
+
+impl State {
+    /// Creates the state of a run over an input file with the given fingerprint, restoring any
+    /// progress saved to the checkpoint file for the same input file.
+    fn load(checkpoint: &Checkpoint, fingerprint: u64) -> State {
+        let mut state = State {
+            fingerprint: fingerprint,
+            answers: BTreeMap::new(),
+            pending: BTreeSet::new(),
+            saved_at: Instant::now(),
+        };
+        let s = match fs::read_to_string(&checkpoint.path) {
+            Ok(s) => s,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return state,
+            Err(e) => panic!("could not read checkpoint file: {}", e),
+        };
+        let saved = State::parse(&s).expect("could not parse checkpoint file");
+        if saved.fingerprint != fingerprint {
+            eprintln!(
+                "checkpoint file {} belongs to a different input file; starting from scratch",
+                checkpoint.path.display()
+            );
+            return state;
+        }
+        eprintln!(
+            "resuming from checkpoint file {}: {} test cases already solved",
+            checkpoint.path.display(),
+            saved.answers.len()
+        );
+        if !saved.pending.is_empty() {
+            let list: Vec<String> = saved.pending.iter().map(|case| format!("#{}", case)).collect();
+            eprintln!("test cases in progress when last saved: Case {}", list.join(", "));
+        }
+        state.answers = saved.answers;
+        state
+    }
+
+    /// Parses the contents of a checkpoint file.
+    fn parse(s: &str) -> io::Result<State> {
+        let invalid = |what: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid checkpoint file: {}", what),
+            )
+        };
+        let mut state = State {
+            fingerprint: 0,
+            answers: BTreeMap::new(),
+            pending: BTreeSet::new(),
+            saved_at: Instant::now(),
+        };
+        let mut rest = s.strip_prefix(HEADER)
+            .and_then(|rest| rest.strip_prefix('\n'))
+            .ok_or_else(|| invalid("missing header"))?;
+        while !rest.is_empty() {
+            let end = rest.find('\n').ok_or_else(|| invalid("unterminated line"))?;
+            let line = &rest[..end];
+            rest = &rest[end + 1..];
+            let mut fields = line.split(' ');
+            match fields.next() {
+                Some("input") => {
+                    state.fingerprint = fields
+                        .next()
+                        .and_then(|field| u64::from_str_radix(field, 16).ok())
+                        .ok_or_else(|| invalid(line))?;
+                }
+                Some("pending") => for field in fields {
+                    let _ = state
+                        .pending
+                        .insert(field.parse().map_err(|_| invalid(line))?);
+                },
+                Some("case") => {
+                    let numbers: Vec<usize> = fields
+                        .map(|field| field.parse())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| invalid(line))?;
+                    let (case, len) = match numbers[..] {
+                        [case, len] => (case, len),
+                        _ => return Err(invalid(line)),
+                    };
+                    let answer = rest.get(..len)
+                        .filter(|_| rest[len..].starts_with('\n'))
+                        .ok_or_else(|| invalid(line))?;
+                    let _ = state.answers.insert(case, answer.to_owned());
+                    rest = &rest[len + 1..];
+                }
+                _ => return Err(invalid(line)),
+            }
+        }
+        Ok(state)
+    }
+
+    /// Writes the state to the checkpoint file, replacing it only once the new contents have been
+    /// written in full, so that a crash while saving leaves the previous checkpoint intact.
+    fn save(&mut self, checkpoint: &Checkpoint) {
+        let mut s = String::from(HEADER);
+        s.push('\n');
+        let _ = writeln!(s, "input {:016x}", self.fingerprint);
+        s.push_str("pending");
+        for case in &self.pending {
+            let _ = write!(s, " {}", case);
+        }
+        s.push('\n');
+        for (case, answer) in &self.answers {
+            let _ = writeln!(s, "case {} {}", case, answer.len());
+            s.push_str(answer);
+            s.push('\n');
+        }
+        let temp_path = checkpoint.temp_path();
+        fs::write(&temp_path, s).expect("could not write checkpoint file");
+        fs::rename(&temp_path, &checkpoint.path).expect("could not replace checkpoint file");
+        self.saved_at = Instant::now();
+    }
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Consumes the test engine, executing a parser and solver once per test case, periodically
+    /// saving the test results solved so far to a checkpoint file.
+    ///
+    /// If the checkpoint file already exists and was saved by a run over the same input file, the
+    /// test results it contains are written without calling the solver, so that a run interrupted
+    /// by a crash or reboot resumes from its last checkpoint rather than from scratch. The parser
+    /// is still executed for every test case. The checkpoint file also records which test cases
+    /// were being solved when it was saved, which are listed when resuming, as these are likely
+    /// to be the ones that caused a crash. A checkpoint file saved for a different input file is
+    /// ignored. The checkpoint file is deleted once every test case has been solved.
+    ///
+    /// # Panics
+    ///
+    /// This method panics in the event of an I/O error, or if the checkpoint file is invalid.
+    pub fn run_checkpointed<
+        D: Sized + Send + Sync,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader) -> D,
+        S: Fn(&D) -> R + Sync
+    >
+        (
+        self,
+        checkpoint: Checkpoint,
+        p: P,
+        s: S,
+    ) {
+        let text = read_input(self.input, self.settings.input_encoding);
+        // The samples are checked before the solver is wrapped, so that they neither resume from
+        // nor are saved to the checkpoint.
+        let mut settings = self.settings;
+        samples::check_gate(&mut settings, &|reader: &mut InputReader| Some(p(reader)), &s);
+        let state = Mutex::new(State::load(&checkpoint, fingerprint(&text)));
+        let solve = |d: &D| {
+            let case = logging::current_case().expect("test case not recorded by test engine");
+            {
+                let mut state = state.lock().expect("could not lock checkpoint");
+                if let Some(answer) = state.answers.get(&case) {
+                    return Resumed::Saved(answer.clone());
+                }
+                let _ = state.pending.insert(case);
+                if state.saved_at.elapsed() >= checkpoint.interval {
+                    state.save(&checkpoint);
+                }
+            }
+            let r = s(d);
+            let mut state = state.lock().expect("could not lock checkpoint");
+            let _ = state.pending.remove(&case);
+            let _ = state.answers.insert(case, r.to_string());
+            if state.saved_at.elapsed() >= checkpoint.interval {
+                state.save(&checkpoint);
+            }
+            Resumed::Solved(r)
+        };
+        let _ = Self::execute_text(
+            self.output,
+            settings,
+            &text,
+            |reader| Some(p(reader)),
+            solve,
+            |_, _, _| {},
+            || None,
+        );
+        for path in &[checkpoint.temp_path(), checkpoint.path.clone()] {
+            match fs::remove_file(path) {
+                Err(ref e) if e.kind() != io::ErrorKind::NotFound => {
+                    panic!("could not remove checkpoint file: {}", e)
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<R: Display> Display for Resumed<R> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Resumed::Saved(ref answer) => f.write_str(answer),
+            Resumed::Solved(ref result) => result.fmt(f),
+        }
+    }
+}
+
+/// Computes the 64-bit FNV-1a hash of the text of an input file.
+fn fingerprint(text: &str) -> u64 {
+    history::fnv1a(history::FNV_OFFSET_BASIS, text.as_bytes())
+}
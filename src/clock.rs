@@ -0,0 +1,161 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Tracking the time remaining in a contest, and the clock that time-based features read.
+
+use super::{logging, Input, Output, TestEngine};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// A source of the current time, read by the time limit, the run budget, the contest clock, the
+/// progress reports and the run summary.
+///
+/// By default, test engines read a `SystemClock`. A `FakeClock` can be set with
+/// `TestEngine::clock()` instead, so that code built on these features can be tested without
+/// sleeping.
+pub trait Clock: Debug + Send + Sync {
+    /// Returns the time elapsed since a fixed point in the past, such as when the clock was
+    /// created. This should never decrease.
+    fn monotonic(&self) -> Duration;
+
+    /// Returns the current system time, used for deadlines such as the end of a contest.
+    fn system_time(&self) -> SystemTime;
+}
+
+/// The clock of the operating system.
+#[derive(Clone, Copy, Debug)]
+pub struct SystemClock {
+    /// When the clock was created.
+    origin: Instant,
+}
+
+/// A clock that only moves when told to, for testing time-based features deterministically.
+///
+/// Clones of a fake clock share the same time, so a test can keep one clone and advance it, e.g.
+/// from within a solver to simulate a slow test case, while the test engine reads another.
+#[derive(Clone, Debug)]
+pub struct FakeClock {
+    /// The time elapsed since the clock was created, and the system time at its creation.
+    state: Arc<Mutex<(Duration, SystemTime)>>,
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Sets the time at which the contest (or the current submission window) ends.
+    ///
+    /// Once set, the time remaining is written to standard error after all test cases have been
+    /// solved, so that long-running solutions are noticed before the deadline passes.
+    pub fn contest_end(mut self, end: SystemTime) -> TestEngine<I, O> {
+        self.settings.contest_end = Some(end);
+        self
+    }
+
+    /// Sets the length of the submission window, starting from now.
+    ///
+    /// This is equivalent to calling `TestEngine::contest_end()` with the current time plus
+    /// `length`, and is useful for judges that allow a fixed amount of time to submit output after
+    /// downloading an input file.
+    pub fn submission_window(self, length: Duration) -> TestEngine<I, O> {
+        let now = self.settings.clock.system_time();
+        self.contest_end(now + length)
+    }
+
+    /// Sets the clock read by the time limit, the run budget, the contest clock, the progress
+    /// reports, the run summary, the per-case timings, and the summaries of `TestSets`.
+    ///
+    /// The default is a `SystemClock`. Set a `FakeClock` to test code that relies on these
+    /// features without real sleeps; note that watchdogs, timeouts, retries, auto-tuning and
+    /// benchmarks still measure real time. Since `TestEngine::submission_window()` reads the
+    /// clock when it is called, set the clock first.
+    pub fn clock<C: Clock + 'static>(mut self, clock: C) -> TestEngine<I, O> {
+        self.settings.clock = Arc::new(clock);
+        self
+    }
+}
+
+impl SystemClock {
+    /// Creates a clock reading the time of the operating system.
+    pub fn new() -> SystemClock {
+        SystemClock {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn monotonic(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+impl FakeClock {
+    /// Creates a fake clock whose system time starts at `start`.
+    pub fn new(start: SystemTime) -> FakeClock {
+        FakeClock {
+            state: Arc::new(Mutex::new((Duration::new(0, 0), start))),
+        }
+    }
+
+    /// Moves the clock, and every clone of it, forward by `by`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the clock's lock has been poisoned.
+    pub fn advance(&self, by: Duration) {
+        let mut state = self.state.lock().expect("could not lock fake clock");
+        state.0 += by;
+        state.1 += by;
+    }
+}
+
+impl Clock for FakeClock {
+    fn monotonic(&self) -> Duration {
+        self.state.lock().expect("could not lock fake clock").0
+    }
+
+    fn system_time(&self) -> SystemTime {
+        self.state.lock().expect("could not lock fake clock").1
+    }
+}
+
+/// Writes the time remaining until a deadline, as of `now`, to standard error. If the deadline
+/// has passed, a warning log record is also emitted, and the warning is returned.
+pub fn report(deadline: SystemTime, now: SystemTime) -> Option<String> {
+    let remaining = time_remaining(deadline, now);
+    eprintln!("contest clock: {}", remaining);
+    if deadline < now {
+        let warning = format!("contest clock: {}", remaining);
+        logging::warn(&warning);
+        Some(warning)
+    } else {
+        None
+    }
+}
+
+/// Describes the time remaining until a deadline, as of `now`, e.g. `"1h 02m 03s remaining"`.
+pub fn time_remaining(deadline: SystemTime, now: SystemTime) -> String {
+    match deadline.duration_since(now) {
+        Ok(remaining) => format!("{} remaining", format_hms(remaining)),
+        Err(e) => format!("deadline passed {} ago", format_hms(e.duration())),
+    }
+}
+
+/// Formats a duration as hours, minutes and seconds.
+fn format_hms(d: Duration) -> String {
+    let secs = d.as_secs();
+    format!("{}h {:02}m {:02}s", secs / 3600, secs / 60 % 60, secs % 60)
+}
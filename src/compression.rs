@@ -0,0 +1,124 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Transparent decompression of input files.
+//!
+//! Input file paths ending in `.gz` or `.zip` are decompressed as they are opened if the
+//! `compression` feature is enabled; otherwise, opening such a path fails with an explanatory
+//! error rather than feeding compressed data to the parser.
+
+#[cfg(feature = "compression")]
+use flate2::read::MultiGzDecoder;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Cursor, Read};
+use std::path::Path;
+#[cfg(feature = "compression")]
+use zip::ZipArchive;
+
+/// An input file opened from a path, decompressed if necessary.
+#[derive(Debug)]
+pub struct InputFile {
+    /// The input file, if it is read as is.
+    file: Option<File>,
+    /// The decompressed contents of the input file, if it was compressed.
+    data: Cursor<Vec<u8>>,
+}
+
+/// The compression format of an input file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Format {
+    /// The input file is not compressed.
+    Plain,
+    /// The input file is compressed with gzip.
+    Gzip,
+    /// The input file is a zip archive containing the input.
+    Zip,
+}
+
+impl InputFile {
+    /// Opens an input file, decompressing it if its path ends in `.gz` or `.zip`.
+    ///
+    /// A zip archive must contain either exactly one file, or exactly one file whose name ends in
+    /// `.in`, which is used as the input file.
+    pub fn open(path: &Path) -> io::Result<InputFile> {
+        let format = match path.extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("gz") => Format::Gzip,
+            Some(e) if e.eq_ignore_ascii_case("zip") => Format::Zip,
+            _ => Format::Plain,
+        };
+        let file = OpenOptions::new().read(true).open(path)?;
+        if format == Format::Plain {
+            return Ok(InputFile {
+                file: Some(file),
+                data: Cursor::new(Vec::new()),
+            });
+        }
+        Ok(InputFile {
+            file: None,
+            data: Cursor::new(decompress(file, format)?),
+        })
+    }
+}
+
+impl Read for InputFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.file {
+            Some(ref mut file) => file.read(buf),
+            None => self.data.read(buf),
+        }
+    }
+}
+
+/// Decompresses an entire input file.
+#[cfg(feature = "compression")]
+fn decompress(file: File, format: Format) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    if format == Format::Gzip {
+        let _ = MultiGzDecoder::new(file).read_to_end(&mut data)?;
+        return Ok(data);
+    }
+    let mut archive = ZipArchive::new(file)?;
+    let mut files = Vec::new();
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i)?;
+        if entry.is_file() {
+            files.push((i, entry.name()?.ends_with(".in")));
+        }
+    }
+    let index = match files.len() {
+        1 => files[0].0,
+        _ => {
+            let inputs: Vec<usize> = files
+                .iter()
+                .filter(|&&(_, is_input)| is_input)
+                .map(|&(i, _)| i)
+                .collect();
+            if inputs.len() != 1 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "zip archive contains {} files, {} of which end in `.in`; expected one",
+                        files.len(),
+                        inputs.len()
+                    ),
+                ));
+            }
+            inputs[0]
+        }
+    };
+    let _ = archive.by_index(index)?.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Fails to decompress an input file, as the `compression` feature is disabled.
+#[cfg(not(feature = "compression"))]
+fn decompress(_: File, _: Format) -> io::Result<Vec<u8>> {
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "compressed input files require the `compression` feature",
+    ))
+}
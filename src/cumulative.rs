@@ -0,0 +1,56 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Test cases that depend on the results of earlier test cases.
+
+use super::{logging, Input, InputReader, Output, TestEngine};
+use std::fmt::Display;
+use std::sync::Mutex;
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Consumes the test engine, executing a parser once per test case and a solver that is also
+    /// given the test results of every earlier test case.
+    ///
+    /// The solver is passed the data for a test case and a slice holding the test results of
+    /// test cases 1 to N - 1, in order, for problems whose test cases are cumulative, such as
+    /// those where state persists from one test case to the next. Test cases are therefore solved
+    /// one at a time, in order, even if the `parallel` feature is enabled; they are still parsed
+    /// up front in that case.
+    ///
+    /// # Panics
+    ///
+    /// This method panics in the event of an I/O error.
+    pub fn run_cumulative<
+        D: Sized + Send + Sync,
+        R: Clone + Display + Sized + Send,
+        P: Fn(&mut InputReader) -> D,
+        S: Fn(&D, &[R]) -> R + Sync
+    >
+        (
+        mut self,
+        p: P,
+        s: S,
+    ) {
+        self.settings.batch_size = Some(usize::MAX);
+        let previous = Mutex::new(Vec::new());
+        let _ = self.execute(
+            |reader| Some(p(reader)),
+            |d| {
+                let mut previous = previous.lock().expect("could not lock previous test results");
+                // Each sample checked by a sample gate is a run of its own, starting again from
+                // the first test case.
+                if logging::current_case() == Some(1) {
+                    previous.clear();
+                }
+                let r = s(d, &previous);
+                previous.push(r.clone());
+                r
+            },
+            |_, _, _| {},
+        );
+    }
+}
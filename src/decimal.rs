@@ -0,0 +1,162 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Reading decimal numbers exactly, as fixed-point integers.
+
+use super::{FromInput, InputReader};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// An exact decimal number, for probability and money problems where the representation errors
+/// of `f64` could change an answer.
+///
+/// A decimal is stored as an `i128` value and a scale, the number of digits after the decimal
+/// point, such that the number equals `value / 10^scale`. The scale is the number of digits
+/// written after the decimal point, trailing zeros included: `"0.000001"` is read as the value `1`
+/// with scale 6, and `"-2.50"` as the value `-250` with scale 2. `Decimal::to_scale()` converts a
+/// decimal to a fixed-point integer at any other scale, so that numbers read with different
+/// scales can be combined using integer arithmetic.
+///
+/// Decimals are read from a single token consisting of an optional sign, a run of digits, and
+/// optionally a decimal point followed by another run of digits; exponents are not accepted.
+/// Decimals are written with the same number of digits after the decimal point as their scale.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Decimal {
+    /// The number multiplied by `10^scale`.
+    value: i128,
+    /// The number of digits after the decimal point.
+    scale: u32,
+}
+
+impl Decimal {
+    /// Creates the decimal number `value / 10^scale`.
+    pub fn new(value: i128, scale: u32) -> Decimal {
+        Decimal {
+            value: value,
+            scale: scale,
+        }
+    }
+
+    /// Returns the number multiplied by `10^scale`, where `scale` is `Decimal::scale()`.
+    pub fn value(self) -> i128 {
+        self.value
+    }
+
+    /// Returns the number of digits after the decimal point.
+    pub fn scale(self) -> u32 {
+        self.scale
+    }
+
+    /// Returns the number multiplied by `10^scale`, e.g. `250000` for `"0.25"` at scale 6.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the result is not an integer, e.g. `"0.125"` at scale 2, or cannot
+    /// be represented as an `i128`.
+    pub fn to_scale(self, scale: u32) -> i128 {
+        self.checked_to_scale(scale).unwrap_or_else(|| {
+            panic!("could not represent {} exactly with scale {}", self, scale)
+        })
+    }
+
+    /// Returns the number multiplied by `10^scale`, or `None` if the result is not an integer or
+    /// cannot be represented as an `i128`.
+    pub fn checked_to_scale(self, scale: u32) -> Option<i128> {
+        if scale >= self.scale {
+            10i128.checked_pow(scale - self.scale)?.checked_mul(self.value)
+        } else {
+            match 10i128.checked_pow(self.scale - scale) {
+                Some(divisor) if self.value % divisor == 0 => Some(self.value / divisor),
+                // Every nonzero `i128` is smaller than a divisor too large to represent.
+                None if self.value == 0 => Some(0),
+                _ => None,
+            }
+        }
+    }
+
+    /// Returns the nearest `f64` to this number.
+    pub fn to_f64(self) -> f64 {
+        // Parsing the decimal representation rounds correctly, unlike dividing two `f64`s.
+        self.to_string()
+            .parse()
+            .expect("could not convert decimal number to `f64`")
+    }
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let digits = format!(
+            "{:0width$}",
+            self.value.unsigned_abs(),
+            width = self.scale as usize + 1
+        );
+        let (int_part, frac_part) = digits.split_at(digits.len() - self.scale as usize);
+        let sign = if self.value < 0 { "-" } else { "" };
+        if frac_part.is_empty() {
+            write!(f, "{}{}", sign, int_part)
+        } else {
+            write!(f, "{}{}.{}", sign, int_part, frac_part)
+        }
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Decimal, String> {
+        let invalid = || format!("could not parse {:?} as a decimal number", s);
+        let (negative, unsigned) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (int_part, frac_part) = match unsigned.find('.') {
+            Some(i) => (&unsigned[..i], &unsigned[i + 1..]),
+            None => (unsigned, ""),
+        };
+        let digits = format!("{}{}", int_part, frac_part);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let value: i128 = if negative {
+            format!("-{}", digits).parse()
+        } else {
+            digits.parse()
+        }.map_err(|_| invalid())?;
+        Ok(Decimal::new(value, frac_part.len() as u32))
+    }
+}
+
+impl FromInput for Decimal {
+    fn from_input(reader: &mut InputReader) -> Decimal {
+        let token = reader.read_token();
+        token.parse().unwrap_or_else(|e: String| panic!("{}", e))
+    }
+}
+
+impl<'a> InputReader<'a> {
+    /// Reads a decimal number from the input file exactly, as a whitespace-delimited token.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the token is not a valid decimal number, or its value cannot be
+    /// represented as an `i128`.
+    pub fn read_decimal(&mut self) -> Decimal {
+        self.read()
+    }
+
+    /// Reads a decimal number from the input file as a fixed-point integer with `scale` digits
+    /// after the decimal point, e.g. `250000` for `"0.25"` with a scale of 6.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the token is not a valid decimal number, or has more significant
+    /// digits after the decimal point than `scale`, or if the result cannot be represented as an
+    /// `i128`.
+    pub fn read_fixed(&mut self, scale: u32) -> i128 {
+        self.read_decimal().to_scale(scale)
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Formatting floating-point test results by significant digits rather than decimal places.
+
+use std::fmt::{self, Display, Formatter};
+
+/// Writes a floating-point number in positional notation with at least the given number of
+/// significant digits, for problems asking for answers "with at least 9 significant digits".
+///
+/// Unlike a fixed number of digits after the decimal point, this keeps small numbers such as
+/// `0.000001234` precise, and never writes an exponent, which some judges reject. For example,
+/// `Significant(0.000123456, 3)` is written as `"0.000123"`, `Significant(123.456, 3)` as
+/// `"123"` and `Significant(123456.0, 3)` as `"123456"`, since digits before the decimal point
+/// are never dropped. Zero is written with `digits - 1` digits after the decimal point, and
+/// infinities and NaN are written as by `f64`'s own `Display` implementation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Significant(pub f64, pub usize);
+
+/// Writes a floating-point number in scientific notation with the given number of significant
+/// digits, e.g. `Scientific(123456.0, 3)` is written as `"1.23e5"`.
+///
+/// The mantissa has exactly `digits` significant digits, one of which precedes the decimal point;
+/// the exponent is written without a sign if it is positive and without leading zeros, as by
+/// Rust's `{:e}` formatting, which judges that parse floating-point answers accept.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scientific(pub f64, pub usize);
+
+impl Display for Significant {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let Significant(x, digits) = *self;
+        if !x.is_finite() {
+            return write!(f, "{}", x);
+        }
+        // The exponent of the number once rounded to the given number of significant digits.
+        let exponent = match exponent_of(x, digits) {
+            Some(exponent) => exponent,
+            None => return write!(f, "{:.*}", digits.saturating_sub(1), x),
+        };
+        let decimals = (digits as i64 - 1 - i64::from(exponent)).max(0) as usize;
+        write!(f, "{:.*}", decimals, x)
+    }
+}
+
+impl Display for Scientific {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let Scientific(x, digits) = *self;
+        write!(f, "{:.*e}", digits.saturating_sub(1), x)
+    }
+}
+
+/// Returns the decimal exponent of `x` rounded to `digits` significant digits, or `None` if `x`
+/// is zero.
+fn exponent_of(x: f64, digits: usize) -> Option<i32> {
+    if x == 0.0 {
+        return None;
+    }
+    // Formatting rounds correctly, so the exponent accounts for rounding up to a power of ten.
+    let formatted = format!("{:.*e}", digits.saturating_sub(1), x);
+    let exponent = &formatted[formatted.find('e').expect("could not format exponent") + 1..];
+    Some(exponent.parse().expect("could not parse exponent"))
+}
@@ -0,0 +1,74 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Reading typed values from an input file.
+
+use super::InputReader;
+#[cfg(feature = "bigint")]
+use num_bigint::{BigInt, BigUint};
+use std::array;
+
+/// A type that can be read from an input file.
+///
+/// This is implemented for the primitive numeric types, `char` and `String`, which are read as a
+/// single whitespace-delimited token, for tuples of up to six such types, which are read as
+/// consecutive tokens, and for fixed-size arrays, whose elements are read in turn. If the `bigint`
+/// feature is enabled, this is also implemented for `BigInt` and `BigUint`. If the `derive`
+/// feature is enabled, `#[derive(FromInput)]` can be used to implement this trait for structs.
+pub trait FromInput: Sized {
+    /// Reads a value from an input file.
+    ///
+    /// # Panics
+    ///
+    /// Implementations should panic if the input file does not contain a valid value.
+    fn from_input(reader: &mut InputReader) -> Self;
+}
+
+/// Implements `FromInput` for types that are parsed from a single token via `FromStr`.
+macro_rules! impl_from_input_for_token {
+    ($($t:ty),*) => {
+        $(
+            impl FromInput for $t {
+                fn from_input(reader: &mut InputReader) -> $t {
+                    let token = reader.read_token();
+                    token.parse().unwrap_or_else(|_| {
+                        panic!("could not parse {:?} as `{}`", token, stringify!($t))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+/// Implements `FromInput` for tuples whose elements all implement `FromInput`.
+macro_rules! impl_from_input_for_tuple {
+    ($($t:ident),*) => {
+        impl<$($t: FromInput),*> FromInput for ($($t,)*) {
+            fn from_input(reader: &mut InputReader) -> ($($t,)*) {
+                ($($t::from_input(reader),)*)
+            }
+        }
+    };
+}
+
+impl_from_input_for_token!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, char, String
+);
+#[cfg(feature = "bigint")]
+impl_from_input_for_token!(BigInt, BigUint);
+impl_from_input_for_tuple!(A);
+impl_from_input_for_tuple!(A, B);
+impl_from_input_for_tuple!(A, B, C);
+impl_from_input_for_tuple!(A, B, C, D);
+impl_from_input_for_tuple!(A, B, C, D, E);
+impl_from_input_for_tuple!(A, B, C, D, E, F);
+
+impl<T: FromInput, const N: usize> FromInput for [T; N] {
+    fn from_input(reader: &mut InputReader) -> [T; N] {
+        array::from_fn(|_| T::from_input(reader))
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Writing test results that are themselves grids of characters.
+
+use std::fmt::{self, Display, Formatter, Write};
+
+/// Writes a grid of characters as a multi-line test result, for construction problems whose
+/// answer is a grid.
+///
+/// The `"Case #N:"` header line is ended, then each row is written as a line of its own, with no
+/// separator between cells, e.g. the rows `["#.", ".#"]` are written as `"Case #N:\n#.\n.#\n"`.
+/// A grid answer is usually created from the rows of a solver's working grid, such as a
+/// `Vec<Vec<char>>` or `Vec<Vec<u8>>`, with `GridAnswer::new()`; if the `util` feature is
+/// enabled, a `&Grid<char>` or `&Grid<u8>` can be converted with `GridAnswer::from()`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GridAnswer<'a, C: 'a> {
+    /// The cells of each row.
+    rows: Vec<&'a [C]>,
+}
+
+/// A cell of a grid written by `GridAnswer`.
+pub trait GridCell {
+    /// Writes the character of the cell.
+    fn write_cell(&self, f: &mut Formatter) -> fmt::Result;
+}
+
+impl<'a, C: GridCell> GridAnswer<'a, C> {
+    /// Creates a grid answer from a list of rows.
+    pub fn new<R: AsRef<[C]>>(rows: &'a [R]) -> GridAnswer<'a, C> {
+        GridAnswer::from_rows(rows.iter().map(AsRef::as_ref).collect())
+    }
+
+    /// Creates a grid answer from the cells of each row.
+    pub fn from_rows(rows: Vec<&'a [C]>) -> GridAnswer<'a, C> {
+        GridAnswer { rows: rows }
+    }
+}
+
+impl<'a, C: GridCell> Display for GridAnswer<'a, C> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_char('\n')?;
+        for row in &self.rows {
+            for cell in *row {
+                cell.write_cell(f)?;
+            }
+            f.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+impl GridCell for char {
+    fn write_cell(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_char(*self)
+    }
+}
+
+impl GridCell for u8 {
+    fn write_cell(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_char(char::from(*self))
+    }
+}
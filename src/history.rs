@@ -0,0 +1,208 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A persistent history of the runs of a solution.
+
+use super::{format_duration, paths, Input, Output, TestEngine};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The conventional file name for a problem's run history.
+pub const HISTORY_FILE_NAME: &str = ".gcj-history";
+
+/// The header line of a run history file.
+const HEADER: &str = concat!(
+    "finished_at,wall_time,cases,output_bytes,output_hash,",
+    "status,warnings,threads,seed"
+);
+
+/// The initial state of a 64-bit FNV-1a hash.
+pub const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// One run of a solution, as recorded in a run history file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RunRecord {
+    /// When the run finished, in seconds since the Unix epoch.
+    pub finished_at: u64,
+    /// The wall time of the run.
+    pub wall_time: Duration,
+    /// The number of test cases solved.
+    pub cases: usize,
+    /// The number of bytes written to the output file.
+    pub output_bytes: usize,
+    /// The 64-bit FNV-1a hash of the output file.
+    pub output_hash: u64,
+    /// How the run ended: `"ok"`, `"output_error"` if the output file could not be flushed, or
+    /// `"panicked"`.
+    pub status: String,
+    /// The number of warnings raised during the run.
+    pub warnings: usize,
+    /// The number of threads used to solve test cases in parallel mode, if not the default.
+    pub threads: Option<usize>,
+    /// The seed used for per-case random number generators.
+    pub seed: u64,
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Appends a record of each run to a run history file at `path`, conventionally named
+    /// `.gcj-history` and kept alongside the problem's manifest.
+    ///
+    /// Each record holds when the run finished, its wall time, the number of test cases solved,
+    /// the size and hash of the output file, how the run ended, the number of warnings raised,
+    /// and the thread count and seed used. Runs that panic are recorded too. The history can be
+    /// read with `RunRecord::load_history()` or listed with `cargo gcj history`, which shows how
+    /// each run compares with the one before it, for tracking whether optimisation work is
+    /// actually paying off, and whether it changed any answers.
+    pub fn run_history<P: Into<PathBuf>>(mut self, path: P) -> TestEngine<I, O> {
+        self.settings.run_history = Some(path.into());
+        self
+    }
+}
+
+impl RunRecord {
+    /// Loads every run recorded in a run history file, oldest first.
+    ///
+    /// A missing file holds no runs. Lines that cannot be parsed, such as those written by a
+    /// different version of this crate, are skipped.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the file exists but cannot be read.
+    pub fn load_history<P: AsRef<Path>>(path: P) -> Vec<RunRecord> {
+        let path = paths::expand_home(path.as_ref());
+        match fs::read_to_string(&path) {
+            Ok(text) => text.lines().skip(1).filter_map(parse_record).collect(),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => panic!("could not read run history `{}`: {}", path.display(), e),
+        }
+    }
+
+    /// Describes the run on one line, e.g.
+    /// `"2017-04-08 12:34:56 UTC: ok, 100 cases in 1.234s, output 1f2e3d4c5b6a7980 (4096 bytes)"`.
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{}: {}, {} cases in {}, output {:016x} ({} bytes)",
+            format_utc(self.finished_at),
+            self.status,
+            self.cases,
+            format_duration(self.wall_time),
+            self.output_hash,
+            self.output_bytes
+        );
+        if self.warnings > 0 {
+            summary.push_str(&format!(", {} warnings", self.warnings));
+        }
+        if let Some(threads) = self.threads {
+            summary.push_str(&format!(", {} threads", threads));
+        }
+        summary
+    }
+
+    /// Describes how this run differs from an earlier one, e.g.
+    /// `"35.2% faster, same output"` or `"12.0% slower, output changed"`.
+    pub fn compare(&self, earlier: &RunRecord) -> String {
+        let old = earlier.wall_time.as_secs_f64();
+        let new = self.wall_time.as_secs_f64();
+        let speed = if old == 0.0 || (new - old).abs() / old < 0.005 {
+            "same speed".to_owned()
+        } else if new < old {
+            format!("{:.1}% faster", (old - new) / old * 100.0)
+        } else {
+            format!("{:.1}% slower", (new - old) / old * 100.0)
+        };
+        let output = if self.output_hash == earlier.output_hash {
+            "same output"
+        } else {
+            "output changed"
+        };
+        format!("{}, {}", speed, output)
+    }
+}
+
+/// Appends a record to a run history file, creating it if it does not exist.
+pub fn record(path: &Path, record: &RunRecord) -> io::Result<()> {
+    let exists = path.exists();
+    let mut file = OpenOptions::new().append(true).create(true).open(path)?;
+    if !exists {
+        writeln!(file, "{}", HEADER)?;
+    }
+    writeln!(
+        file,
+        "{},{}.{:06},{},{},{:016x},{},{},{},{}",
+        record.finished_at,
+        record.wall_time.as_secs(),
+        record.wall_time.subsec_micros(),
+        record.cases,
+        record.output_bytes,
+        record.output_hash,
+        record.status,
+        record.warnings,
+        record.threads.map_or_else(String::new, |threads| threads.to_string()),
+        record.seed
+    )
+}
+
+/// Converts a system time to seconds since the Unix epoch.
+pub fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Continues a 64-bit FNV-1a hash over some bytes.
+pub fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Parses one line of a run history file.
+fn parse_record(line: &str) -> Option<RunRecord> {
+    let fields: Vec<&str> = line.trim_end_matches('\r').split(',').collect();
+    if fields.len() != 9 {
+        return None;
+    }
+    Some(RunRecord {
+        finished_at: fields[0].parse().ok()?,
+        wall_time: Duration::try_from_secs_f64(fields[1].parse().ok()?).ok()?,
+        cases: fields[2].parse().ok()?,
+        output_bytes: fields[3].parse().ok()?,
+        output_hash: u64::from_str_radix(fields[4], 16).ok()?,
+        status: fields[5].to_owned(),
+        warnings: fields[6].parse().ok()?,
+        threads: match fields[7] {
+            "" => None,
+            threads => Some(threads.parse().ok()?),
+        },
+        seed: fields[8].parse().ok()?,
+    })
+}
+
+/// Formats a time in seconds since the Unix epoch as a UTC date and time.
+fn format_utc(secs: u64) -> String {
+    // Converts days since the epoch to a civil date, as described in Howard Hinnant's
+    // "chrono-Compatible Low-Level Date Algorithms".
+    let days = (secs / 86400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096)
+        / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
@@ -0,0 +1,136 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! An index of the input data consumed by each test case.
+
+use super::{Input, Output, TestEngine};
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// The header row of a case index file.
+const HEADER: &str = "case,start,end";
+
+/// The byte range of the input file consumed by the parser for each test case, as recorded by
+/// `TestEngine::case_index()`.
+///
+/// Offsets are into the input file as decoded by the test engine, which for UTF-8 input files is
+/// the file itself (after decompression, if any). A test case's range starts where the previous
+/// test case's range ends, so it includes any leading whitespace.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CaseIndex {
+    /// The byte range of each test case, in order.
+    spans: Vec<Range<usize>>,
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Sets a path to write an index of the input file to, once all test cases have been solved.
+    ///
+    /// The index is a CSV file with a header row, followed by one row per test case with the
+    /// columns `case`, `start` and `end`: the byte range of the input file consumed while parsing
+    /// that test case. It can be loaded with `CaseIndex::load()` to extract a single test case
+    /// without reparsing the whole input file.
+    pub fn case_index<P: Into<PathBuf>>(mut self, path: P) -> TestEngine<I, O> {
+        self.settings.case_index = Some(path.into());
+        self
+    }
+}
+
+impl CaseIndex {
+    /// Creates an index from the byte range of each test case, in order.
+    pub fn new(spans: Vec<Range<usize>>) -> CaseIndex {
+        CaseIndex { spans: spans }
+    }
+
+    /// Loads an index written by `TestEngine::case_index()`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<CaseIndex> {
+        let s = fs::read_to_string(path)?;
+        let invalid = |line: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid case index row: {:?}", line),
+            )
+        };
+        let mut lines = s.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(invalid(s.lines().next().unwrap_or("")));
+        }
+        let mut spans = Vec::new();
+        for line in lines.filter(|line| !line.is_empty()) {
+            let fields: Vec<usize> = line.split(',')
+                .map(|field| field.parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| invalid(line))?;
+            match fields[..] {
+                [case, start, end] if case == spans.len() + 1 && start <= end => {
+                    spans.push(start..end)
+                }
+                _ => return Err(invalid(line)),
+            }
+        }
+        Ok(CaseIndex::new(spans))
+    }
+
+    /// Writes the index to a file, in the format read by `CaseIndex::load()`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut csv = String::from(HEADER);
+        csv.push('\n');
+        for (i, span) in self.spans.iter().enumerate() {
+            let _ = writeln!(csv, "{},{},{}", i + 1, span.start, span.end);
+        }
+        fs::write(path, csv)
+    }
+
+    /// Returns the number of test cases in the index.
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Returns `true` if the index contains no test cases.
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Returns the byte range of each test case, in order.
+    pub fn spans(&self) -> &[Range<usize>] {
+        &self.spans
+    }
+
+    /// Returns the byte range of a test case, numbered from 1, or `None` if there is no such test
+    /// case.
+    pub fn span(&self, case: usize) -> Option<Range<usize>> {
+        case.checked_sub(1)
+            .and_then(|i| self.spans.get(i))
+            .cloned()
+    }
+
+    /// Returns the text of a test case, numbered from 1, from the input file it was recorded
+    /// from, or `None` if there is no such test case or the input does not contain it.
+    pub fn extract<'a>(&self, input: &'a str, case: usize) -> Option<&'a str> {
+        self.span(case).and_then(|span| input.get(span))
+    }
+
+    /// Returns an input file containing only one test case, numbered from 1, from the input file
+    /// it was recorded from, preceded by a test case count of 1.
+    ///
+    /// The result can be fed back to a test engine to re-run a single test case in isolation.
+    pub fn single_case_input(&self, input: &str, case: usize) -> Option<String> {
+        self.extract(input, case).map(|data| {
+            let data = data.trim_start_matches(&['\r', '\n'][..]);
+            let mut s = String::with_capacity(0);
+            s.reserve_exact(2 + data.len() + 1);
+            s.push_str("1\n");
+            s.push_str(data);
+            if !data.ends_with('\n') {
+                s.push('\n');
+            }
+            s
+        })
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Stopping a run cleanly when it is interrupted with Ctrl-C.
+//!
+//! If the `interrupt` feature is enabled on a Unix platform, the test engine can block `SIGINT`
+//! for the duration of a run and wait for it on a dedicated thread, which asks the test engine to
+//! stop. Otherwise, interruption is never requested and the handler does nothing.
+
+use super::activity;
+#[cfg(feature = "interrupt")]
+use super::{Input, Output, TestEngine};
+#[cfg(all(unix, feature = "interrupt"))]
+use nix::sys::pthread;
+#[cfg(all(unix, feature = "interrupt"))]
+use nix::sys::signal::{SigSet, SigmaskHow, Signal};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(all(unix, feature = "interrupt"))]
+use std::os::unix::thread::JoinHandleExt;
+#[cfg(all(unix, feature = "interrupt"))]
+use std::thread::{self, JoinHandle};
+
+/// Whether the run has been interrupted.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the run has finished, so that the handler thread should exit.
+#[cfg(all(unix, feature = "interrupt"))]
+static FINISHED: AtomicBool = AtomicBool::new(false);
+
+/// The exit status used when a run is interrupted, as for a process killed by `SIGINT`.
+const EXIT_STATUS: i32 = 130;
+
+/// Handles Ctrl-C for the duration of a run, if enabled.
+#[cfg(all(unix, feature = "interrupt"))]
+#[derive(Debug)]
+pub struct Handler {
+    /// If the handler is enabled, the thread waiting for `SIGINT`, and the signal mask of the
+    /// thread that installed the handler before `SIGINT` was blocked.
+    thread: Option<(JoinHandle<()>, SigSet)>,
+}
+
+/// Handles Ctrl-C for the duration of a run, if enabled.
+#[cfg(not(all(unix, feature = "interrupt")))]
+#[derive(Debug)]
+pub struct Handler;
+
+#[cfg(feature = "interrupt")]
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Sets whether interrupting the run with Ctrl-C stops it cleanly.
+    ///
+    /// Without this, Ctrl-C kills the process at once, leaving an output file containing an
+    /// unknown number of test results. With this enabled, the first Ctrl-C writes how far the run
+    /// got to standard error, along with the test cases still being solved and for how long,
+    /// longest first. No further test cases are started: once the test cases in progress have
+    /// been solved, every test result solved up to the first unsolved test case is written, the
+    /// output file is flushed, the run summary (if enabled) notes the interruption, and the
+    /// process exits with status 130. A second Ctrl-C exits immediately, for when a test case is
+    /// stuck.
+    ///
+    /// This method is only available if the `interrupt` feature is enabled, and has no effect
+    /// except on Unix platforms. `SIGINT` is blocked on the calling thread during the run, so it
+    /// is only noticed by threads started during the run, such as the thread pool used in
+    /// parallel mode, if the pool was not already started earlier. This is disabled by default.
+    pub fn handle_interrupt(mut self, enabled: bool) -> TestEngine<I, O> {
+        self.settings.handle_interrupt = enabled;
+        self
+    }
+}
+
+#[cfg(all(unix, feature = "interrupt"))]
+impl Handler {
+    /// Starts handling Ctrl-C, if enabled, until the returned value is dropped.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `SIGINT` cannot be blocked, or the handler thread cannot be
+    /// started.
+    pub fn install(enabled: bool) -> Handler {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        if !enabled {
+            return Handler { thread: None };
+        }
+        let mut mask = SigSet::empty();
+        mask.add(Signal::SIGINT);
+        let previous_mask = mask.thread_swap_mask(SigmaskHow::SIG_BLOCK)
+            .expect("could not block SIGINT");
+        FINISHED.store(false, Ordering::SeqCst);
+        let thread = thread::Builder::new()
+            .name("gcj-helper interrupt handler".to_owned())
+            .spawn(move || {
+                while mask.wait().is_ok() && !FINISHED.load(Ordering::SeqCst) {
+                    interrupted();
+                }
+            })
+            .expect("could not start interrupt handler thread");
+        Handler {
+            thread: Some((thread, previous_mask)),
+        }
+    }
+
+    /// Stops handling Ctrl-C, restoring the signal mask of the calling thread.
+    pub fn uninstall(self) {}
+}
+
+#[cfg(all(unix, feature = "interrupt"))]
+impl Drop for Handler {
+    fn drop(&mut self) {
+        if let Some((thread, previous_mask)) = self.thread.take() {
+            FINISHED.store(true, Ordering::SeqCst);
+            if pthread::pthread_kill(thread.as_pthread_t(), Signal::SIGINT).is_ok() {
+                let _ = thread.join();
+            }
+            let _ = previous_mask.thread_set_mask();
+        }
+    }
+}
+
+#[cfg(not(all(unix, feature = "interrupt")))]
+impl Handler {
+    /// Does nothing, as Ctrl-C cannot be handled on this platform or without the `interrupt`
+    /// feature.
+    pub fn install(enabled: bool) -> Handler {
+        let _ = enabled;
+        Handler
+    }
+
+    /// Does nothing.
+    pub fn uninstall(self) {}
+}
+
+/// Returns `true` if the run has been interrupted, so that no further test cases should be
+/// started.
+pub fn requested() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Exits the process if the run has been interrupted, once the given number of test results have
+/// been written.
+pub fn exit_if_requested(written: usize) {
+    if requested() {
+        eprintln!(
+            "run interrupted: {} test results written, {}",
+            written,
+            activity::describe_progress()
+        );
+        process::exit(EXIT_STATUS);
+    }
+}
+
+/// Responds to Ctrl-C: the first time, asks the test engine to stop and reports how far the run
+/// got; the second time, exits immediately.
+#[cfg(all(unix, feature = "interrupt"))]
+fn interrupted() {
+    if INTERRUPTED.swap(true, Ordering::SeqCst) {
+        eprintln!(
+            "interrupted again: exiting immediately, {}",
+            activity::describe_progress()
+        );
+        process::exit(EXIT_STATUS);
+    }
+    eprintln!(
+        "interrupted: {}; finishing the test cases in progress",
+        activity::describe_progress()
+    );
+    let running = activity::describe_running(5);
+    if !running.is_empty() {
+        eprintln!("still running: {}", running);
+    }
+    eprintln!("press Ctrl-C again to exit immediately");
+}
@@ -0,0 +1,198 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Skipping malformed test cases instead of aborting the run.
+
+use super::{logging, report, Input, InputReader, Output, TestEngine};
+use std::any::Any;
+use std::cell::Cell;
+use std::fmt::{self, Display, Formatter};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+/// The text written in place of the test result of a test case that could not be parsed.
+pub const MALFORMED_CASE_ANSWER: &str = " MALFORMED\n";
+
+thread_local! {
+    /// Whether a parser is being run leniently on this thread, so that its panics are reported as
+    /// warnings rather than by the panic hook.
+    static PARSING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Wraps the panic hook so that it ignores panics raised by parsers being run leniently.
+static QUIET_HOOK: Once = Once::new();
+
+/// Where each test case ends, so that parsing can resume at the next test case after a malformed
+/// one.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CaseBoundary {
+    /// Each test case spans exactly this many lines.
+    Lines(usize),
+    /// Each test case begins with a line whose first token is the number of lines that follow it,
+    /// e.g. `"3"` followed by three more lines. If that token is itself malformed, only the first
+    /// line is skipped.
+    CountedLines,
+    /// Test cases are separated by one or more blank lines.
+    BlankLine,
+}
+
+/// A test result, or the placeholder written for a malformed test case.
+enum Lenient<R> {
+    /// The test case was parsed and solved.
+    Solved(R),
+    /// The test case could not be parsed.
+    Malformed,
+}
+
+impl<R: Display> Display for Lenient<R> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Lenient::Solved(ref result) => result.fmt(f),
+            Lenient::Malformed => f.write_str(MALFORMED_CASE_ANSWER),
+        }
+    }
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Consumes the test engine, executing a parser and solver once per test case, skipping test
+    /// cases that the parser fails to parse.
+    ///
+    /// Whenever the parser panics, the input file is rewound to the start of the test case and
+    /// resynchronised by skipping to the next test case as described by `boundary`, e.g. past a
+    /// fixed number of lines. The malformed test case is not solved; `" MALFORMED\n"` is written
+    /// as its test result instead, a warning naming it and quoting the parser's panic message is
+    /// written to standard error and included in the run summary and JSON report, and parsing
+    /// continues with the next test case. This is useful for validating large hand-edited or
+    /// generated input files in one pass, rather than fixing one malformed test case per run.
+    ///
+    /// # Panics
+    ///
+    /// This method panics in the event of an I/O error.
+    pub fn run_lenient<
+        D: Sized + Send + Sync,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader) -> D,
+        S: Fn(&D) -> R + Sync
+    >
+        (
+        self,
+        boundary: CaseBoundary,
+        p: P,
+        s: S,
+    ) {
+        QUIET_HOOK.call_once(|| {
+            let hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| if !PARSING.with(|p| p.get()) {
+                hook(info)
+            }));
+        });
+        let _ = self.execute(
+            |reader| {
+                let start = reader.position();
+                PARSING.with(|p| p.set(true));
+                let parsed = panic::catch_unwind(AssertUnwindSafe(|| p(reader)));
+                PARSING.with(|p| p.set(false));
+                match parsed {
+                    Ok(d) => Some(Some(d)),
+                    Err(payload) => {
+                        reader.offset = start;
+                        let skipped = skip_case(reader, boundary);
+                        malformed(&*payload, skipped);
+                        Some(None)
+                    }
+                }
+            },
+            |d| match *d {
+                Some(ref d) => Lenient::Solved(s(d)),
+                None => Lenient::Malformed,
+            },
+            |_, _, _| {},
+        );
+    }
+}
+
+/// Skips the test case beginning at the reader's position, returning the number of lines skipped.
+fn skip_case(reader: &mut InputReader, boundary: CaseBoundary) -> usize {
+    let mut skipped = 0;
+    match boundary {
+        CaseBoundary::Lines(n) => while skipped < n && skip_line(reader).is_some() {
+            skipped += 1;
+        },
+        CaseBoundary::CountedLines => {
+            let count: usize = match skip_line(reader) {
+                Some(line) => {
+                    skipped += 1;
+                    line.split_whitespace()
+                        .next()
+                        .and_then(|token| token.parse().ok())
+                        .unwrap_or(0)
+                }
+                None => 0,
+            };
+            while skipped <= count && skip_line(reader).is_some() {
+                skipped += 1;
+            }
+        }
+        CaseBoundary::BlankLine => {
+            // The previous test case's parser may have stopped short of the blank lines that
+            // separate it from this one.
+            skipped += skip_blank_lines(reader);
+            while let Some(line) = skip_line(reader) {
+                skipped += 1;
+                if line.trim().is_empty() {
+                    break;
+                }
+            }
+            skipped += skip_blank_lines(reader);
+        }
+    }
+    skipped
+}
+
+/// Skips any blank lines at the reader's position, returning the number of lines skipped.
+fn skip_blank_lines(reader: &mut InputReader) -> usize {
+    let mut skipped = 0;
+    while reader.input[reader.position()..].starts_with(['\n', '\r']) {
+        let _ = skip_line(reader);
+        skipped += 1;
+    }
+    skipped
+}
+
+/// Skips a line of the input file, including its end-of-line marker, returning the line, or
+/// `None` at the end of the input file.
+fn skip_line<'a>(reader: &mut InputReader<'a>) -> Option<&'a str> {
+    let input = reader.input;
+    let start = reader.position();
+    let rest = &input[start..];
+    if rest.is_empty() {
+        return None;
+    }
+    let len = rest.find('\n').map_or(rest.len(), |i| i + 1);
+    reader.offset = start + len;
+    Some(&rest[..len])
+}
+
+/// Reports that the current test case was malformed, after skipping the given number of lines.
+fn malformed(payload: &(dyn Any + Send), skipped: usize) {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown error".to_owned());
+    let case = logging::current_case()
+        .map_or_else(|| "a test case".to_owned(), |case| format!("Case #{}", case));
+    let warning = format!(
+        "{} could not be parsed ({}); skipped {} lines",
+        case,
+        message,
+        skipped
+    );
+    eprintln!("warning: {}", warning);
+    logging::warn(&warning);
+    report::defer_warning(warning);
+}
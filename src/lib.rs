@@ -39,12 +39,248 @@
 //! * It enables test case parallelisation if the `parallel` feature is enabled, improving
 //!   run-time performance at the cost of increased build times.
 //!
+//! Parallel mode is not always a win, e.g. when test cases are tiny; `TestEngine::auto_tune()`
+//! times a sample of test cases sequentially and with several thread counts before the run, and
+//! uses the fastest configuration, optionally remembering it in the problem's manifest. If the
+//! `affinity` feature is enabled, `TestEngine::pin_threads()` pins the threads of parallel mode
+//! to specific cores on Linux, for steadier timings when benchmarking against the time limit.
+//!
+//! By default, the first line of the input file is expected to specify the number of test cases.
+//! Inputs without a case count can be handled by calling `TestEngine::case_mode()` with
+//! `CaseMode::UntilEof`, in which case the parser is invoked repeatedly until the end of the input
+//! file is reached; `TestEngine::run_until_none()` additionally lets the parser end the run early
+//! by returning `None`. Legacy formats that end with a sentinel test case, such as a line of
+//! `0 0`, can use `TestEngine::run_until_sentinel()`, whose parser returns a `ControlFlow`,
+//! breaking once it reads the sentinel. `CaseMode::Single` treats the entire input file as one
+//! test case, without a case count or a `"Case #N:"` header.
+//!
+//! Test data is usually read from an input file and test results written to an output file, but
+//! any `Input` and `Output` may be used; for example, `TestEngine::stdio()` uses standard input and
+//! standard output instead, and `TestEngine::kattis()` additionally omits `"Case #N:"` headers and
+//! reads test cases until the end of the input, as expected by Kattis-style judges.
+//! `TestEngine::with_dyn_io()` accepts boxed readers and writers chosen at runtime.
+//! `TestEngine::from_files()` accepts files (or descriptors) that have already been opened, and
+//! `Shards` reads an input file split across several files as one continuous input. If the
+//! `compression` feature is enabled, input file paths ending in `.gz` or `.zip` are decompressed
+//! as they are read. `TestEngine::tee()` writes test results to an additional output, such as
+//! standard output or an in-memory buffer, without running the solver twice. File paths beginning
+//! with `~` are expanded to the home directory, missing parent directories of output files (e.g.
+//! `out/large/A.out`) are created, and files written once the run finishes, such as reports, are
+//! checked for writability before any test case is solved.
+//!
+//! A parser and solver can also be described by implementing the `Problem` trait, and executed
+//! with `TestEngine::run_problem()`. If the `derive` feature is enabled, the `#[gcj_helper::main]`
+//! attribute generates a complete `main` function from either a `Problem` implementation, or a
+//! function that reads one test case from an `InputReader` and returns its result; input and
+//! output file paths are obtained from command line arguments, as with `TestEngine::from_args()`.
+//! Without procedural macros, the `gcj_solution!` macro offers a similarly terse setup from a
+//! parse block and a solve block.
+//!
+//! Several problems can share one binary via the `multi_problem!` macro, which generates a `main`
+//! function taking the problem name as its first command line argument, and dispatching to a
+//! module of the same name exposing `parse` and `solve` functions.
+//!
+//! When many test cases share subproblems, `TestEngine::run_memoized()` passes the solver a `Memo`
+//! cache shared by every test case in the run, which is safe to use when test cases are solved in
+//! parallel. For the rare problem whose test cases are cumulative, `TestEngine::run_cumulative()`
+//! solves test cases in order, passing the solver the test results of every earlier test case.
+//! Problems that ship with auxiliary data files, such as a dictionary or a precomputed table, can
+//! register them with `TestEngine::aux_file()`; `TestEngine::run_with_aux()` loads them once and
+//! passes the resulting `AuxData` to the parser and solver of every test case.
+//! Problems whose output ends with a line summarising every test case, such as a total, can use
+//! `TestEngine::run_aggregated()`, which records a value from each test case as it is solved and
+//! writes the aggregate of them after the last test result.
+//!
+//! Randomised solvers can call `case_rng()` for a random number generator seeded from the run's
+//! seed and the test case number, so results are reproducible regardless of the order in which
+//! test cases are solved. The seed is set with `TestEngine::seed()` or the `GCJ_SEED` environment
+//! variable. If the `rand` feature is enabled, the generator implements `rand_core::RngCore`.
+//! `TestEngine::run_with_retry()` re-runs a test case with an escalating attempt number when the
+//! solver reports low confidence in its result, or an attempt exceeds the soft deadline of its
+//! `RetryPolicy`, so that a fast heuristic can fall back to a bigger budget or another algorithm.
+//! `TestEngine::run_chain()` instead tries an ordered `SolverChain` of solvers, such as a fast
+//! heuristic followed by an exact brute force, falling through whenever a solver's checker rejects
+//! its result, and reports which solver produced each test result. For runs lasting hours,
+//! `TestEngine::run_checkpointed()` periodically saves the test results solved so far to a
+//! `Checkpoint` file, from which the run resumes after a crash or reboot. If the `interrupt`
+//! feature is enabled, `TestEngine::handle_interrupt(true)` makes Ctrl-C stop a run cleanly on
+//! Unix platforms, reporting the test cases still running and writing every test result solved
+//! so far before exiting. `TestEngine::stall_watchdog()` warns whenever no test case has finished
+//! for a given interval, listing the test cases still running and for how long, which pinpoints
+//! a test case stuck in an infinite loop. `TestEngine::run_with_timeout()` goes further, abandoning
+//! any test case that runs past a hard time limit, writing `" TLE\n"` as its test result and
+//! moving on to the next test case; solvers can poll `case_cancelled()` to stop early once
+//! abandoned.
+//! Deeply recursive solvers can be given a larger stack with `TestEngine::stack_size()`, which
+//! solves test cases on a dedicated thread instead of the thread calling `TestEngine::run()`.
+//!
+//! # Checking samples
+//!
+//! `TestEngine::check_samples()` runs a parser and solver over sample inputs held in memory and
+//! compares the results against the expected outputs, reporting mismatches case by case. The
+//! `include_samples!` macro embeds sample files into the binary at compile time, so that samples
+//! can be checked regardless of the working directory. `TestEngine::sample_gate()` checks them as
+//! part of every run, before the input file is solved, and aborts the run if any fails. Output
+//! files can also be compared directly using `mismatched_cases()`, which pairs test results by
+//! case number, so that a missing test result does not offset later ones. Before uploading,
+//! `validate_output()` (or `cargo gcj validate`) checks that an output file has exactly one
+//! `"Case #N:"` header per test case of its input file, in order, and ends with a newline. When
+//! validating a large hand-edited or generated input file, `TestEngine::run_lenient()` skips any
+//! test case that the parser fails to parse, using a `CaseBoundary` to find the start of the next
+//! test case, and lists the malformed test cases once the run finishes.
+//!
+//! # The `TestSets` type
+//!
+//! Problems split into several test sets (e.g. a visible and a hidden test set) can be solved in
+//! one invocation using `TestSets`, which runs the same parser and solver over one `TestEngine`
+//! per test set and writes a per-test-set summary to standard error, including whether each test
+//! set was solved within its expected time limit. `TestSets::run_concurrent()` solves different
+//! test sets on separate threads at once, with a combined progress report as they run.
+//!
+//! # Problem manifests
+//!
+//! Configuration can be kept out of code in a `gcj.toml` manifest, which describes the input and
+//! output paths, the input format, the time limit, sample files and the thread count for a
+//! problem. `TestEngine::from_manifest()` creates a test engine configured by a manifest, and
+//! `cargo gcj new` and `cargo gcj watch` read and write the same file.
+//!
+//! # Benchmarking
+//!
+//! `TestEngine::run_benchmark()` solves the full input file several times after a warm-up run,
+//! and reports the spread of total run times and of solver time per test case. For a quicker look
+//! at where time is spent, `TestEngine::case_timing(true)` writes the parse and solve time of each
+//! test case to standard error as the test case is solved. To measure solver optimisations with
+//! an external harness such as criterion inside `cargo bench`, `RecordedInput` holds an input file
+//! in memory and provides a whole-run benchmark function, and the parsed data for each test case
+//! for per-case benchmarks.
+//!
+//! # Logging
+//!
+//! Debug output written with `gcj_debug!`, which takes the same arguments as `eprintln!`, is
+//! prefixed with the test case being parsed or solved, e.g. `"Case #3: "`, and is written one
+//! whole message at a time, so debug output from test cases solved in parallel stays grep-able.
+//! `TestEngine::run_with_dump()` uses this to write the data for each test case as it is parsed,
+//! and `TestEngine::trace_input(true)` uses it to echo every line and token handed to the parser,
+//! which shows exactly where a parser lost sync with the input file.
+//!
+//! When test results go to standard output, `TestEngine::isolate_stdout(true)` redirects the
+//! `print!` and `println!` macros exported by this crate (which shadow the standard ones under
+//! `#[macro_use]`) to standard error for the duration of the run, so that leftover prints cannot
+//! corrupt the answers. `TestEngine::capture_debug(true)` groups debug output by test case,
+//! writing each test case's output in one block once it has been parsed or solved.
+//!
+//! If the `log` feature is enabled, the test engine emits records through the `log` crate as each
+//! test case starts and finishes, including how long it took, plus warnings such as a passed
+//! contest deadline. Wrapping a logger in `CaseLogger` prefixes every record emitted by a parser
+//! or solver with the test case being processed, even when test cases are solved in parallel.
+//!
+//! If the `tracing` feature is enabled, each test case is also given a `tracing` span, with fields
+//! for the test case number, the number of bytes parsed and the time taken, containing spans for
+//! the parse, solve and write phases.
+//!
+//! # Time limits
+//!
+//! If the time limit for an input file is set with `TestEngine::time_limit()` (or in a manifest),
+//! warnings are written to standard error as soon as the run has used 50% and 80% of the limit,
+//! and if the limit is exceeded, so that a run that will not finish in time can be aborted early.
+//!
+//! `TestEngine::run_budget()` instead stops starting test cases once a wall-clock budget has been
+//! spent, writing a placeholder answer (set with `TestEngine::placeholder_answer()`) for each
+//! remaining test case, so that a complete output file is produced before a submission window
+//! closes.
+//!
+//! # Profiling
+//!
+//! `TestEngine::profile_hooks()` registers functions called before and after each test case is
+//! solved, with the test case number, so that external profilers can attribute samples to
+//! specific test cases.
+//!
+//! # Run summaries
+//!
+//! `TestEngine::summary(true)` writes a summary to standard error after the run, covering the wall
+//! time, the split between parsing and solving, the slowest test cases, the output size, whether
+//! the output was synced to disk (see `TestEngine::sync_output()`) and any warnings.
+//! `TestEngine::json_report()` writes the same information, along with the test engine
+//! configuration, to a JSON file for use by other tools. `TestEngine::timings_csv()` writes the
+//! size and parse, solve and write times of each test case to a CSV file, for analysis in a
+//! spreadsheet. `TestEngine::timing_baseline()` stores these timings between runs, and lists the
+//! test cases that became significantly slower or faster since the previous run, which catches
+//! performance regressions introduced while refactoring. `TestEngine::case_index()` records the
+//! byte range of the input file consumed by each test case, which `CaseIndex` uses to extract a
+//! single test case as a standalone input file without reparsing the whole input. Solvers can
+//! attach metadata such as a confidence score or the algorithm used to a test case with
+//! `annotate_case()`, which `TestEngine::annotations_file()` writes to a JSON or CSV sidecar file
+//! rather than the output file.
+//!
+//! `TestEngine::run_history()` appends a record of every run, including its wall time and a hash
+//! of its output, to a `.gcj-history` file kept per problem; `RunRecord::load_history()` and
+//! `cargo gcj history` list past runs and compare them, showing whether optimisation work during
+//! a long contest is converging and whether it changed any answers.
+//!
+//! # Contest clock
+//!
+//! If the contest end time is configured with `TestEngine::contest_end()`, or the length of a
+//! submission window with `TestEngine::submission_window()`, the time remaining is written to
+//! standard error after each run. `TestEngine::progress(true)` additionally writes periodic
+//! progress reports, with an estimate of the time left to solve the remaining test cases.
+//!
+//! These features, along with the time limit, the run budget, the run summary and per-case
+//! timings, read the time from a `Clock`. `TestEngine::clock()` replaces the default
+//! `SystemClock` with a `FakeClock`, which only moves when advanced, so that they can be tested
+//! deterministically without real sleeps.
+//!
 //! # The `InputReader` type
 //!
 //! `gcj-helper` provides parsers with access to an `InputReader`, which obtains data from the
 //! input file in a `io::Read`-like fashion. The `InputReader::read_next_line()` method reads a
 //! line of text from the input file, consuming the end-of-line marker, and returns a `&str`
-//! containing the result.
+//! containing the result. Input files must be valid UTF-8 by default; input files containing
+//! Latin-1 text, stray invalid bytes or a byte order mark can be read by setting an
+//! `InputEncoding` with `TestEngine::input_encoding()`.
+//!
+//! Typed values can be read using `InputReader::read()`, which works with any type implementing
+//! `FromInput`; this includes the primitive numeric types, `char`, `String`, and tuples and arrays
+//! thereof, which are read as whitespace-delimited tokens. If the `derive` feature is enabled,
+//! `#[derive(FromInput)]` can be used to implement `FromInput` for a struct representing the data
+//! for one test case, with field attributes describing the layout of the input:
+//!
+//! * `#[gcj(line)]` reads the field from an entire line of text.
+//! * `#[gcj(count = "n")]` reads a `Vec` of `n` values, where `n` is an expression that may refer
+//!   to earlier fields.
+//! * `#[gcj(grid(rows = "r"))]` reads a `Vec<Vec<u8>>` from `r` lines of text, via
+//!   `InputReader::read_grid()`; `#[gcj(grid(rows = "r", chars))]` reads a `Vec<Vec<char>>`, via
+//!   `InputReader::read_char_grid()`, for grids containing non-ASCII symbols.
+//!
+//! A parser for such a struct is then simply `|input| input.read::<Case>()`.
+//!
+//! Fixed-size groups of values, such as three coordinates, can be read into an array with
+//! `InputReader::read_array()`, e.g. `let [x, y, z] = input.read_array::<i64, 3>();`, which needs
+//! no heap allocation.
+//!
+//! `InputReader::peek_token()` and `InputReader::peek_n_tokens()` return upcoming tokens without
+//! consuming them, for formats where whether a field is present depends on the value that follows.
+//!
+//! Lines holding huge numbers of values can be consumed one token at a time with
+//! `InputReader::line_tokens()`, an iterator that never collects the line's tokens, e.g.
+//! `let sum: u64 = input.line_tokens().map(|t| t.parse::<u64>().unwrap()).sum();`. The input file
+//! itself is still read into memory in full before the first test case is parsed.
+//!
+//! Inputs laid out in fixed-width fields rather than separated by whitespace, such as ASCII art,
+//! can be split with `InputReader::read_columns()`, e.g. `input.read_columns(&[3, 5, 2])`.
+//!
+//! Where reading a number like `0.000001` as an `f64` would introduce representation errors,
+//! `InputReader::read_decimal()` reads it exactly, as a `Decimal` holding an integer value and the
+//! number of digits after the decimal point, and `InputReader::read_fixed()` reads it as a
+//! fixed-point integer with a given number of digits after the decimal point.
+//!
+//! Lines and tokens returned by an `InputReader<'a>` borrow from the text of the input file, which
+//! the test engine keeps alive for the whole run, rather than from the reader. Implementing the
+//! `BorrowedProblem` trait and calling `TestEngine::run_borrowed()` lets the data for each test
+//! case hold these `&'a str`s directly, instead of copying every string into an owned `String`.
+//!
+//! For quick, one-off parsers, the `gcj_input!` macro reads several values into local variables
+//! at once, e.g. `gcj_input!(input, n: usize, a: [i64; n], s: String)`, while `read_line!` parses
+//! exactly one line into a tuple, e.g. `let (n, k) = read_line!(input => u64, u64)`.
 //!
 //! # Formatting test results
 //!
@@ -52,6 +288,83 @@
 //! current test case. This does not prepend or append any whitespace. This means that if the
 //! colon must be followed by a space, your result should begin with one, and that the result must
 //! end with a newline.
+//!
+//! Each test result is flushed to the output file as soon as it has been written, so if the run
+//! is aborted part-way through, whether by a panic, an I/O error or a signal, the output file
+//! still holds every test result written before then. (If the `parallel` feature is enabled,
+//! test results are only written once every test case has been solved.)
+//!
+//! The output file is identical, byte for byte, on every platform: test results are written
+//! exactly as formatted, with no translation of line endings, Rust formats numbers the same way
+//! regardless of the locale, and test results are always written in order, even when solved in
+//! parallel. A stray `"\r\n"` in a test result (e.g. one copied from an input file written on
+//! Windows) is written as is, unless `TestEngine::normalize_newlines(true)` is set, which replaces
+//! it with `"\n"`.
+//!
+//! `TestEngine::lint_output(true)` checks each test result for trailing spaces, consecutive
+//! spaces, tabs and empty results, which are classic causes of presentation errors, and warns about
+//! offending test cases on standard error. `TestEngine::max_case_output()` aborts the run, naming
+//! the offending test case, if a test result exceeds a plausible number of bytes or lines, before
+//! a solver stuck printing in a loop can fill the disk.
+//!
+//! Solvers for problems where some test cases have no solution can return an `Option` and be
+//! executed with `TestEngine::run_optional()`, which writes `" IMPOSSIBLE\n"` (or the text set
+//! with `TestEngine::none_answer()`) whenever the solver returns `None`.
+//!
+//! Rather than returning a test result, a solver executed with `TestEngine::run_streaming()`
+//! writes it piece by piece to a `CaseWriter`, using the `write_case!` and `writeln_case!` macros,
+//! which take the same arguments as `write!` and `writeln!` and insert the space that follows the
+//! `"Case #N:"` header.
+//!
+//! If the `derive` feature is enabled, `#[derive(Answer)]` implements `Display` for a struct or
+//! enum representing a test result, following these rules; field separators, floating-point
+//! precision and multi-line fields are declared with `#[gcj(...)]` attributes.
+//!
+//! Problems asking for a number of significant digits rather than decimal places can wrap
+//! floating-point test results in `Significant`, which writes at least the given number of
+//! significant digits without an exponent, or `Scientific`, which writes exactly that many in
+//! scientific notation; the `significant` and `scientific` field attributes of
+//! `#[derive(Answer)]` do the same. Construction problems whose answer is a grid can return a
+//! `GridAnswer`, which ends the header line and writes each row of characters on a line of its
+//! own.
+//!
+//! Problems that require a separator between case blocks, such as a blank line, can set one with
+//! `TestEngine::case_separator()` rather than writing it from the solver. Contests that score each
+//! answer separately can have every test result written to its own file as well, named after a
+//! template passed to `TestEngine::case_files()`.
+//!
+//! # Utilities
+//!
+//! If the `util` feature is enabled, the `util` module provides data structures and algorithms
+//! that contest solutions commonly need, such as `util::ModInt`, an integer modulo a constant
+//! for problems asking for an answer "modulo 10^9 + 7", and `util::Rational`, an exact rational
+//! number for problems where comparing floating-point numbers is unsafe. `util::Grid` stores a
+//! two-dimensional grid indexed by `(row, col)`, with bounds-checked iteration over neighbouring
+//! cells, and can be read directly from an input file. `util::Dsu` is a disjoint-set forest
+//! (union-find) with path compression and union by size, and `util::binary_search_answer()` and
+//! its variants encapsulate binary search over the answer, for integers and floating-point
+//! numbers. `util::compress()` performs coordinate compression, mapping sparse values to dense
+//! indices. `util::RollingHash` compares substrings in constant time using a double polynomial
+//! hash. `util::PrefixSum` and `util::PrefixSum2d` sum ranges of a sequence or rectangles of a
+//! grid in constant time, and `util::math` provides `gcd()`, `lcm()`, `mod_pow()`,
+//! `mod_inverse()` and a prime sieve. These types implement `FromInput` and `Display` where it
+//! makes sense, so they can be read by parsers and written as test results.
+//!
+//! If the `bigint` feature is enabled, the arbitrary-precision integer types `BigInt` and `BigUint`
+//! from the `num-bigint` crate are re-exported, and can be read with `InputReader::read()` or
+//! `InputReader::read_bigint()`, for problems whose values exceed `u128`.
+//!
+//! # WebAssembly
+//!
+//! The test engine also runs on `wasm32-wasip1`, for browser-based practice tools and sandboxed
+//! runners, both in stdio mode and with `TestEngine::with_io()` over in-memory buffers. Input and
+//! output file paths work within the directories preopened by the runtime (e.g. with
+//! `wasmtime --dir=.`). WASI targets without threads cannot use the `parallel` feature, which is
+//! rejected at compile time; on such targets, `TestEngine::stack_size()` and
+//! `TestEngine::stall_watchdog()` have no effect, and the features that rely on threads, such as
+//! `TestEngine::run_with_timeout()` and `TestSets::run_concurrent()`, panic when used. The stack
+//! size of a WebAssembly module is fixed when it is linked, e.g. with
+//! `-C link-arg=-zstack-size=<bytes>`. `cargo gcj`, which runs other processes, is not supported.
 
 #![cfg_attr(feature = "clippy", feature(plugin))]
 #![cfg_attr(feature = "clippy", plugin(clippy))]
@@ -59,7 +372,6 @@
 #![cfg_attr(feature = "clippy", forbid(clippy_internal))]
 #![cfg_attr(feature = "clippy", forbid(clippy_pedantic))]
 #![forbid(warnings)]
-#![forbid(box_pointers)]
 #![forbid(fat_ptr_transmutes)]
 #![forbid(missing_copy_implementations)]
 #![forbid(missing_debug_implementations)]
@@ -73,51 +385,623 @@
 #![forbid(unused_results)]
 #![forbid(variant_size_differences)]
 
+#[cfg(all(target_os = "wasi", not(target_feature = "atomics"), feature = "parallel"))]
+compile_error!("the `parallel` feature requires threads, which this WASI target does not support");
+
+#[cfg(feature = "compression")]
+extern crate flate2;
+#[cfg(feature = "derive")]
+extern crate gcj_helper_derive;
+#[cfg(feature = "log")]
+extern crate log;
+#[cfg(any(
+    all(target_os = "linux", feature = "affinity"),
+    all(unix, feature = "interrupt")
+))]
+extern crate nix;
+#[cfg(feature = "bigint")]
+extern crate num_bigint;
+#[cfg(feature = "rand")]
+extern crate rand_core;
 #[cfg(feature = "parallel")]
 extern crate rayon;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(feature = "compression")]
+extern crate zip;
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use std::{env, io};
 use std::ffi::OsString;
-use std::fmt::{Arguments, Display};
-use std::fs::{File, OpenOptions};
-use std::io::{LineWriter, Read, Write};
-use std::path::Path;
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{BufRead, LineWriter, Read, Write};
+use std::mem;
+use std::ops::ControlFlow;
+#[cfg(feature = "parallel")]
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+#[cfg(feature = "parallel")]
+use std::sync::Mutex;
+#[cfg(not(feature = "parallel"))]
+use std::thread;
+use std::time::{Duration, SystemTime};
+use budget::Budget;
+use profile::ProfileHooks;
+use progress::Progress;
+use report::RunReport;
+#[cfg(not(feature = "parallel"))]
+use stack::SolverThread;
+use trace::CaseSpan;
+use watchdog::Watchdog;
+
+#[macro_use]
+mod macros;
+
+mod activity;
+#[cfg(feature = "parallel")]
+mod affinity;
+mod aggregate;
+mod annotate;
+mod aux_data;
+mod bench;
+mod budget;
+mod case_writer;
+mod chain;
+mod checkpoint;
+mod clock;
+mod compression;
+mod cumulative;
+mod decimal;
+mod float;
+mod from_input;
+mod grid_answer;
+mod history;
+mod index;
+mod interrupt;
+mod limit;
+mod lenient;
+mod line_tokens;
+mod lint;
+mod logging;
+mod manifest;
+mod memo;
+mod optional;
+mod output_limit;
+mod paths;
+mod problem;
+mod profile;
+mod progress;
+mod regression;
+mod report;
+mod retry;
+mod rng;
+mod samples;
+mod shards;
+mod stack;
+mod score;
+mod submission;
+mod tee;
+mod test_set;
+mod timeout;
+mod trace;
+mod tune;
+mod validate;
+mod watchdog;
+#[cfg(feature = "util")]
+pub mod util;
+
+pub use annotate::annotate_case;
+pub use aux_data::AuxData;
+pub use bench::RecordedInput;
+pub use budget::DEFAULT_PLACEHOLDER_ANSWER;
+pub use case_writer::CaseWriter;
+pub use chain::SolverChain;
+pub use checkpoint::Checkpoint;
+pub use clock::{Clock, FakeClock, SystemClock};
+pub use compression::InputFile;
+pub use decimal::Decimal;
+pub use float::{Scientific, Significant};
+pub use from_input::FromInput;
+pub use grid_answer::{GridAnswer, GridCell};
+pub use history::{RunRecord, HISTORY_FILE_NAME};
+pub use index::CaseIndex;
+pub use lenient::{CaseBoundary, MALFORMED_CASE_ANSWER};
+pub use line_tokens::LineTokens;
+#[cfg(feature = "bigint")]
+pub use num_bigint::{BigInt, BigUint};
+#[cfg(feature = "log")]
+pub use logging::CaseLogger;
+pub use logging::{current_case, write_debug, write_stdout};
+pub use manifest::{Manifest, MANIFEST_FILE_NAME};
+pub use memo::Memo;
+pub use optional::DEFAULT_NONE_ANSWER;
+pub use output_limit::OutputLimit;
+#[cfg(feature = "derive")]
+pub use gcj_helper_derive::{main, Answer, FromInput};
+pub use problem::{BorrowedProblem, Problem};
+pub use retry::{Attempt, RetryPolicy};
+pub use rng::{case_rng, CaseRng, SEED_VAR};
+pub use samples::{mismatched_cases, pair_cases, split_cases, Sample};
+pub use shards::{ShardReader, Shards};
+pub use submission::package_submission;
+pub use tee::{Tee, TeeWriter};
+pub use test_set::TestSets;
+pub use timeout::{case_cancelled, TIME_LIMIT_EXCEEDED_ANSWER};
+pub use tune::AutoTune;
+pub use validate::validate_output;
 
 /// Facilitates the execution of problem solving code.
 #[derive(Debug)]
-pub struct TestEngine<I: AsRef<Path>, O: AsRef<Path>> {
-    /// A path to an input file.
-    input_file_path: I,
-    /// A path to an output file.
-    output_file_path: O,
+pub struct TestEngine<I: Input, O: Output> {
+    /// Where test data is read from.
+    input: I,
+    /// Where test results are written to.
+    output: O,
+    /// How test cases are read and written.
+    settings: Settings,
+}
+
+/// Configuration for a test engine, independent of its input and output.
+#[derive(Clone, Debug)]
+struct Settings {
+    /// The strategy used to determine the number of test cases.
+    case_mode: CaseMode,
+    /// Whether each test result is preceded by a `"Case #N:"` header.
+    case_headers: bool,
+    /// When the contest or submission window ends, if known.
+    contest_end: Option<SystemTime>,
+    /// The clock read by time-based features.
+    clock: Arc<dyn Clock>,
+    /// The number of threads used to solve test cases in parallel mode, if not the default.
+    threads: Option<usize>,
+    /// Whether the time taken by each test case is written to standard error.
+    case_timing: bool,
+    /// Whether progress reports are written to standard error.
+    progress: bool,
+    /// Whether a summary is written to standard error once all test cases have been solved.
+    summary: bool,
+    /// Where a JSON report is written once all test cases have been solved, if anywhere.
+    json_report: Option<PathBuf>,
+    /// Where a CSV file of per-case timings is written once all test cases have been solved, if
+    /// anywhere.
+    timings_csv: Option<PathBuf>,
+    /// Where per-case timings are stored for comparison with the next run, if anywhere.
+    timing_baseline: Option<PathBuf>,
+    /// Functions called before and after each test case is solved, if any.
+    profile_hooks: Option<ProfileHooks>,
+    /// The time limit for solving the input file, if known.
+    time_limit: Option<Duration>,
+    /// The fractions of the time limit at which warnings are raised, if not the default.
+    time_limit_thresholds: Option<Vec<f64>>,
+    /// Whether every line and token read from the input file is echoed to standard error.
+    trace_input: bool,
+    /// How the bytes of the input file are decoded into text.
+    input_encoding: InputEncoding,
+    /// The seed used for per-case random number generators, if not read from the environment.
+    seed: Option<u64>,
+    /// The stack size of a dedicated thread solving test cases, if any.
+    stack_size: Option<usize>,
+    /// The text written in place of a missing test result.
+    none_answer: String,
+    /// Whether each test result is checked for whitespace mistakes.
+    lint_output: bool,
+    /// The text written between consecutive test results.
+    case_separator: String,
+    /// Where an index of the input data consumed by each test case is written, if anywhere.
+    case_index: Option<PathBuf>,
+    /// Where the annotations attached to test cases are written, if anywhere.
+    annotations_file: Option<PathBuf>,
+    /// The name and path of each auxiliary data file.
+    aux_files: Vec<(String, PathBuf)>,
+    /// The samples checked before the input file is solved, if gated.
+    sample_gate: Option<&'static [Sample]>,
+    /// The maximum plausible size of one test result, if limited.
+    max_case_output: Option<OutputLimit>,
+    /// Where a record of the run is appended once it finishes, if anywhere.
+    run_history: Option<PathBuf>,
+    /// Whether the output file is synced to durable storage at the end of the run.
+    sync_output: bool,
+    /// The path template each test result is also written to, if any.
+    case_files: Option<String>,
+    /// The number of test cases solved per parallel task, if not chosen automatically.
+    batch_size: Option<usize>,
+    /// How the number of threads used in parallel mode is chosen by timing a sample of test
+    /// cases, if it is.
+    #[cfg_attr(not(feature = "parallel"), allow(dead_code))]
+    auto_tune: Option<AutoTune>,
+    /// The logical CPUs the threads used in parallel mode are pinned to, if any.
+    #[cfg_attr(not(feature = "parallel"), allow(dead_code))]
+    pin_threads: Option<Vec<usize>>,
+    /// Whether `"\r\n"` line endings in test results are replaced with `"\n"`.
+    normalize_newlines: bool,
+    /// Whether standard output is reserved for test results.
+    isolate_stdout: bool,
+    /// Whether debug output is captured per test case.
+    capture_debug: bool,
+    /// Whether interrupting the run with Ctrl-C stops it cleanly.
+    handle_interrupt: bool,
+    /// How long the run may go without a test case finishing before a warning is raised, if
+    /// watched.
+    stall_watchdog: Option<Duration>,
+    /// How long the run may take before no further test cases are started, if limited.
+    run_budget: Option<Duration>,
+    /// The text written in place of the test result of each test case not started within the run
+    /// budget.
+    placeholder_answer: String,
+}
+
+/// A test case that has been parsed, but not yet solved.
+#[cfg(feature = "parallel")]
+struct PendingCase<D> {
+    /// The data for the test case.
+    data: D,
+    /// The time taken by the parser.
+    parse_time: Duration,
+    /// The byte range of the input file consumed by the parser.
+    input_span: Range<usize>,
+    /// The span covering the test case.
+    span: CaseSpan,
+}
+
+/// The time taken to parse, solve and write one test case.
+#[derive(Clone, Copy, Debug)]
+struct CaseTime {
+    /// The time taken by the parser.
+    parse: Duration,
+    /// The time taken by the solver.
+    solve: Duration,
+    /// The time taken to write the test result, or zero if it has not yet been written.
+    write: Duration,
+}
+
+/// A source of test data, such as a path to an input file.
+pub trait Input {
+    /// The type used to read test data.
+    type Reader: Read;
+
+    /// Opens the source for reading.
+    fn open(self) -> io::Result<Self::Reader>;
+}
+
+/// A destination for test results, such as a path to an output file.
+pub trait Output {
+    /// The type used to write test results.
+    type Writer: Write;
+
+    /// Opens the destination for writing.
+    fn open(self) -> io::Result<Self::Writer>;
+
+    /// Ensures that everything written to a writer, which has already been flushed, has reached
+    /// durable storage, returning `false` if this is not supported by the destination.
+    ///
+    /// This is called at the end of a run if `TestEngine::sync_output(true)` was set. By default,
+    /// nothing is done and `false` is returned; output file paths sync the file to disk.
+    fn sync(writer: &mut Self::Writer) -> io::Result<bool> {
+        let _ = writer;
+        Ok(false)
+    }
+}
+
+/// A test engine reading from and writing to trait objects, as created by
+/// `TestEngine::with_dyn_io()`.
+pub type DynTestEngine = TestEngine<FromReader<Box<dyn BufRead>>, ToWriter<Box<dyn Write>>>;
+
+/// Standard input and output, for use as an `Input` or an `Output`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stdio;
+
+/// Any type implementing `io::Read`, for use as an `Input`.
+#[derive(Debug)]
+pub struct FromReader<R: Read>(pub R);
+
+/// Any type implementing `io::Write`, for use as an `Output`.
+#[derive(Debug)]
+pub struct ToWriter<W: Write>(pub W);
+
+/// Specifies how the number of test cases in an input file is determined.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CaseMode {
+    /// The first line of the input file specifies the number of test cases.
+    #[default]
+    Counted,
+    /// The input file does not specify the number of test cases; the parser is invoked repeatedly
+    /// until the end of the input file is reached.
+    UntilEof,
+    /// The entire input file is a single test case. The parser is invoked exactly once, and the
+    /// test result is written without a `"Case #N:"` header.
+    Single,
+}
+
+/// Specifies how the bytes of an input file are decoded into text.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum InputEncoding {
+    /// The input file must be valid UTF-8; reading it fails otherwise.
+    #[default]
+    Utf8,
+    /// The input file is decoded as UTF-8, replacing invalid bytes with U+FFFD REPLACEMENT
+    /// CHARACTER and removing a leading byte order mark, if any.
+    Lossy,
+    /// The input file is decoded as ISO-8859-1 (Latin-1), mapping each byte to the character with
+    /// the same code point, after removing a leading UTF-8 byte order mark, if any. As this never
+    /// fails and preserves every other byte, it also suits input files with binary sections, whose
+    /// bytes are recovered with `c as u8` for each character `c`.
+    Latin1,
 }
 
 /// Supports reading from an input file.
+///
+/// The text of the input file is owned by the test engine for the duration of the run, and the
+/// lines and tokens returned by an input reader borrow from it for the lifetime `'a`, rather than
+/// from the reader itself.
 #[derive(Debug)]
-pub struct InputReader {
+pub struct InputReader<'a> {
     /// A string representing the input file.
-    input: String,
+    input: &'a str,
     /// The current position within the input file.
     offset: usize,
+    /// Whether every line and token read is echoed to standard error.
+    trace: bool,
+}
+
+/// Yields test cases from an input file according to a `CaseMode`.
+enum CaseIter {
+    /// A fixed number of test cases remain.
+    Counted(usize),
+    /// Test cases remain until the end of the input file is reached.
+    UntilEof,
 }
 
 /// Supports writing to an output file.
-struct OutputWriter(LineWriter<File>);
+struct OutputWriter<W: Write> {
+    /// The output file.
+    file: LineWriter<W>,
+    /// Whether each test result is preceded by a `"Case #N:"` header.
+    case_headers: bool,
+    /// The number of bytes written to the output file.
+    written: usize,
+    /// The 64-bit FNV-1a hash of the bytes written to the output file.
+    hash: u64,
+    /// Whether each test result is checked for whitespace mistakes.
+    lint: bool,
+    /// The warnings raised by checking test results.
+    lint_warnings: Vec<String>,
+    /// The text written between consecutive test results.
+    separator: String,
+    /// Whether the last test result written ended with a newline, or `None` if no test result has
+    /// been written yet.
+    ended_line: Option<bool>,
+    /// Syncs the output file to durable storage, returning `false` if this is not supported.
+    sync: fn(&mut W) -> io::Result<bool>,
+    /// The path template each test result is also written to, if any.
+    case_files: Option<String>,
+    /// Whether `"\r\n"` line endings in test results are replaced with `"\n"`.
+    normalize_newlines: bool,
+    /// The maximum plausible size of one test result, if limited.
+    max_case_output: Option<OutputLimit>,
+}
 
-impl<I: AsRef<Path>, O: AsRef<Path>> TestEngine<I, O> {
-    /// Creates a new test engine using the specified input and output file paths.
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Creates a new test engine using the specified input and output, which are typically file
+    /// paths.
     ///
     /// Calling this method is cheap; no files are opened until `TestEngine::run()` is called.
-    pub fn new(input_file_path: I, output_file_path: O) -> TestEngine<I, O> {
+    pub fn new(input: I, output: O) -> TestEngine<I, O> {
         TestEngine {
-            input_file_path: input_file_path,
-            output_file_path: output_file_path,
+            input: input,
+            output: output,
+            settings: Settings {
+                case_mode: CaseMode::default(),
+                case_headers: true,
+                contest_end: None,
+                clock: Arc::new(SystemClock::new()),
+                threads: None,
+                case_timing: false,
+                progress: false,
+                summary: false,
+                json_report: None,
+                timings_csv: None,
+                timing_baseline: None,
+                profile_hooks: None,
+                time_limit: None,
+                time_limit_thresholds: None,
+                trace_input: false,
+                input_encoding: InputEncoding::Utf8,
+                seed: None,
+                stack_size: None,
+                none_answer: DEFAULT_NONE_ANSWER.to_owned(),
+                lint_output: false,
+                case_separator: String::new(),
+                case_index: None,
+                annotations_file: None,
+                aux_files: Vec::new(),
+                sample_gate: None,
+                max_case_output: None,
+                run_history: None,
+                sync_output: false,
+                case_files: None,
+                batch_size: None,
+                auto_tune: None,
+                pin_threads: None,
+                normalize_newlines: false,
+                isolate_stdout: false,
+                capture_debug: false,
+                handle_interrupt: false,
+                stall_watchdog: None,
+                run_budget: None,
+                placeholder_answer: DEFAULT_PLACEHOLDER_ANSWER.to_owned(),
+            },
         }
     }
 
-    #[cfg(not(feature = "parallel"))]
+    /// Sets the strategy used to determine how many test cases the input file contains.
+    ///
+    /// By default, the first line of the input file is expected to specify the number of test
+    /// cases (`CaseMode::Counted`).
+    pub fn case_mode(mut self, mode: CaseMode) -> TestEngine<I, O> {
+        self.settings.case_mode = mode;
+        self
+    }
+
+    /// Sets whether each test result is preceded by a `"Case #N:"` header.
+    ///
+    /// Headers are written by default, except in `CaseMode::Single`, where they are never written.
+    pub fn case_headers(mut self, enabled: bool) -> TestEngine<I, O> {
+        self.settings.case_headers = enabled;
+        self
+    }
+
+    /// Sets the text written between consecutive test results, such as `"\n"` for a blank line
+    /// between case blocks.
+    ///
+    /// The separator is written only between test results, never before the first or after the
+    /// last, and always starts on a new line: if a test result does not end with a newline, one is
+    /// written before the separator. This means multi-line test results (such as those produced
+    /// by `#[gcj(multiline)]` fields) need no extra spacing of their own. By default, no
+    /// separator is written.
+    pub fn case_separator<S: Into<String>>(mut self, separator: S) -> TestEngine<I, O> {
+        self.settings.case_separator = separator.into();
+        self
+    }
+
+    /// Sets a path template for writing each test result to its own file, in addition to the
+    /// output file, where `{case}` is replaced with the test case number (e.g.
+    /// `"answers/case-{case}.txt"`).
+    ///
+    /// Each file contains only the test result, without the `"Case #N:"` header or the single
+    /// space that conventionally follows it. This suits optimisation contests that score the
+    /// answer to each test case separately, and tools that post-process answers individually.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `template` does not contain `{case}`.
+    pub fn case_files<S: Into<String>>(mut self, template: S) -> TestEngine<I, O> {
+        let template = template.into();
+        assert!(
+            template.contains("{case}"),
+            "case file template must contain `{}`",
+            "{case}"
+        );
+        self.settings.case_files = Some(template);
+        self
+    }
+
+    /// Sets whether `"\r\n"` line endings in test results are replaced with `"\n"` before being
+    /// written.
+    ///
+    /// Test results are otherwise written exactly as formatted, so a solver that accidentally
+    /// emits `"\r\n"`, e.g. by echoing a line read from an input file with Windows line endings,
+    /// produces an output file that differs from the expected one. This is disabled by default.
+    pub fn normalize_newlines(mut self, enabled: bool) -> TestEngine<I, O> {
+        self.settings.normalize_newlines = enabled;
+        self
+    }
+
+    /// Sets whether the output file is synced to durable storage once all test results have been
+    /// written.
+    ///
+    /// The output file is always flushed explicitly at the end of a run, and the run panics if
+    /// this fails (e.g. because the disk is full), rather than losing the error when the file is
+    /// closed. Enabling this additionally waits for the data to reach the disk, so that a
+    /// completed run survives a crash or power loss. Outputs other than file paths cannot be
+    /// synced; the run summary states whether the output was. This is disabled by default.
+    pub fn sync_output(mut self, enabled: bool) -> TestEngine<I, O> {
+        self.settings.sync_output = enabled;
+        self
+    }
+
+    /// Sets the number of threads used to solve test cases in parallel mode.
+    ///
+    /// By default, one thread is used per logical CPU. This setting has no effect unless the
+    /// `parallel` feature is enabled.
+    pub fn threads(mut self, count: usize) -> TestEngine<I, O> {
+        self.settings.threads = Some(count);
+        self
+    }
+
+    /// Sets the number of consecutive test cases solved together as one task in parallel mode.
+    ///
+    /// When an input file contains a huge number of tiny test cases, the overhead of scheduling
+    /// each one as its own task can exceed the time taken to solve it. Batching amortises this
+    /// overhead; test results are still written in order. By default, test cases are batched so
+    /// that each thread receives about 64 tasks, which leaves room to balance test cases of
+    /// uneven difficulty. This setting has no effect unless the `parallel` feature is enabled.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `size` is zero.
+    pub fn batch_size(mut self, size: usize) -> TestEngine<I, O> {
+        assert!(size > 0, "batch size must be at least 1");
+        self.settings.batch_size = Some(size);
+        self
+    }
+
+    /// Sets whether the time taken to parse and solve each test case is written to standard error
+    /// as each test case is solved.
+    ///
+    /// This is disabled by default.
+    pub fn case_timing(mut self, enabled: bool) -> TestEngine<I, O> {
+        self.settings.case_timing = enabled;
+        self
+    }
+
+    /// Sets whether progress reports are written to standard error while test cases are solved.
+    ///
+    /// Each report states the number of test cases solved, the elapsed time, an estimate of the
+    /// time remaining (if the number of test cases is known in advance) and, if configured, the
+    /// time remaining in the contest. Reports are written at most once per second, plus once after
+    /// the last test case. This is disabled by default.
+    pub fn progress(mut self, enabled: bool) -> TestEngine<I, O> {
+        self.settings.progress = enabled;
+        self
+    }
+
+    /// Sets how the bytes of the input file are decoded into text.
+    ///
+    /// By default, the input file must be valid UTF-8 (`InputEncoding::Utf8`). Input files
+    /// containing Latin-1 text or stray bytes can instead be read with `InputEncoding::Latin1` or
+    /// `InputEncoding::Lossy`.
+    pub fn input_encoding(mut self, encoding: InputEncoding) -> TestEngine<I, O> {
+        self.settings.input_encoding = encoding;
+        self
+    }
+
+    /// Sets whether a summary of the run is written to standard error once all test cases have
+    /// been solved.
+    ///
+    /// The summary includes the wall time, the total time spent parsing and solving, the slowest
+    /// test cases, the size of the output file and any warnings raised during the run. This is
+    /// disabled by default.
+    pub fn summary(mut self, enabled: bool) -> TestEngine<I, O> {
+        self.settings.summary = enabled;
+        self
+    }
+
+    /// Sets a path to write a machine-readable JSON report of the run to.
+    ///
+    /// The report is a JSON object containing the run's `status` (`"ok"`, `"panicked"` if a
+    /// parser or solver panicked, or `"output_error"` if the output file could not be flushed),
+    /// the test engine `config`, the `wall_time` in seconds, the `output_bytes` written, whether
+    /// the output was `output_synced` to disk, the `parse_time` and `solve_time` of each of the
+    /// `cases` solved, and any `warnings` raised.
+    pub fn json_report<P: Into<PathBuf>>(mut self, path: P) -> TestEngine<I, O> {
+        self.settings.json_report = Some(path.into());
+        self
+    }
+
+    /// Sets a path to write the timings of each test case to, as a CSV file.
+    ///
+    /// The file has a header row, followed by one row per test case with the columns `case`,
+    /// `input_bytes` (the size of the test case's data in the input file), `parse_time`,
+    /// `solve_time` and `write_time`, with times in seconds. This makes it easy to plot solver
+    /// time against the size of each test case in a spreadsheet or other external tool.
+    pub fn timings_csv<P: Into<PathBuf>>(mut self, path: P) -> TestEngine<I, O> {
+        self.settings.timings_csv = Some(path.into());
+        self
+    }
+
     /// Consumes the test engine, executing a parser and solver once per test case.
     ///
     /// # Panics
@@ -134,26 +1018,22 @@ impl<I: AsRef<Path>, O: AsRef<Path>> TestEngine<I, O> {
         p: P,
         s: S,
     ) {
-        let mut reader = InputReader::new(self.input_file_path);
-        let mut writer = OutputWriter::new(self.output_file_path);
-        let mut current_case: usize = 1;
-        let case_count = reader.get_case_count();
-        while current_case <= case_count {
-            writer.write_test_result(current_case, (s)(&(p)(&mut reader)));
-            current_case += 1;
-        }
+        let _ = self.execute(|reader| Some(p(reader)), s, |_, _, _| {});
     }
 
-    /// Consumes the test engine, executing a parser and solver once per test case.
+    /// Consumes the test engine, executing a parser and solver once per test case, until either
+    /// the parser returns `None` or there are no more test cases.
+    ///
+    /// This is mainly useful alongside `CaseMode::UntilEof`, for input files that end with a
+    /// terminating line rather than at the last test case.
     ///
     /// # Panics
     ///
     /// This method panics in the event of an I/O error.
-    #[cfg(feature = "parallel")]
-    pub fn run<
+    pub fn run_until_none<
         D: Sized + Send + Sync,
         R: Display + Sized + Send,
-        P: Fn(&mut InputReader) -> D,
+        P: Fn(&mut InputReader) -> Option<D>,
         S: Fn(&D) -> R + Sync
     >
         (
@@ -161,24 +1041,441 @@ impl<I: AsRef<Path>, O: AsRef<Path>> TestEngine<I, O> {
         p: P,
         s: S,
     ) {
-        let mut reader = InputReader::new(self.input_file_path);
-        let mut writer = OutputWriter::new(self.output_file_path);
-        let case_count = reader.get_case_count();
+        let _ = self.execute(p, s, |_, _, _| {});
+    }
+
+    /// Consumes the test engine, executing a parser and solver once per test case, until the
+    /// parser reads the sentinel that ends the input file.
+    ///
+    /// This is for legacy formats with no case count, whose input ends with a sentinel test case
+    /// such as a line of `0 0`. The parser returns `ControlFlow::Continue` with the data for each
+    /// test case, or `ControlFlow::Break(())` once it has read the sentinel, which is not counted
+    /// as a test case; test cases are numbered as they are read. Whatever the `CaseMode`, no case
+    /// count is read, as with `CaseMode::UntilEof`, and the run also ends if the end of the input
+    /// file is reached before the sentinel.
+    ///
+    /// # Panics
+    ///
+    /// This method panics in the event of an I/O error.
+    pub fn run_until_sentinel<
+        D: Sized + Send + Sync,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader) -> ControlFlow<(), D>,
+        S: Fn(&D) -> R + Sync
+    >
+        (
+        mut self,
+        p: P,
+        s: S,
+    ) {
+        self.settings.case_mode = CaseMode::UntilEof;
+        let _ = self.execute(
+            |reader| match p(reader) {
+                ControlFlow::Continue(d) => Some(d),
+                ControlFlow::Break(()) => None,
+            },
+            s,
+            |_, _, _| {},
+        );
+    }
+
+    /// Executes a parser and solver once per test case, returning the number of test cases
+    /// solved.
+    ///
+    /// `observe` is called with each test result and the time taken to produce it, after the
+    /// test result has been written.
+    fn execute<
+        D: Sized + Send + Sync,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader) -> Option<D>,
+        S: Fn(&D) -> R + Sync,
+        F: FnMut(usize, &R, &CaseTime)
+    >
+        (
+        self,
+        p: P,
+        s: S,
+        observe: F,
+    ) -> usize {
+        let text = read_input(self.input, self.settings.input_encoding);
+        Self::execute_text(self.output, self.settings, &text, p, s, observe, || None)
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    /// Executes a parser and solver once per test case over the text of an input file, one test
+    /// case at a time, returning the number of test cases solved.
+    ///
+    /// Unlike `TestEngine::execute()`, the data for each test case may borrow from the text. Once
+    /// every test case has been written, `trailer` is called, and any text it returns is written
+    /// to the end of the output file.
+    fn execute_text<
+        'a,
+        D: Sized + Send + Sync + 'a,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader<'a>) -> Option<D>,
+        S: Fn(&D) -> R + Sync,
+        F: FnMut(usize, &R, &CaseTime),
+        E: FnOnce() -> Option<String>
+    >
+        (
+        output: O,
+        mut settings: Settings,
+        text: &'a str,
+        p: P,
+        s: S,
+        mut observe: F,
+        trailer: E,
+    ) -> usize {
+        samples::check_gate(&mut settings, &p, &s);
+        settings.seed = Some(rng::start_run(settings.seed));
+        let _isolation = logging::Isolation::begin(settings.isolate_stdout, settings.capture_debug);
+        let _tracking = activity::Tracking::begin(
+            settings.handle_interrupt || settings.stall_watchdog.is_some(),
+        );
+        let interrupt_handler = interrupt::Handler::install(settings.handle_interrupt);
+        let watchdog = Watchdog::start(settings.stall_watchdog);
+        let budget = Budget::start(settings.run_budget, &settings.clock);
+        let clock = Arc::clone(&settings.clock);
+        let mut report = RunReport::new(&settings);
+        let mut limit_watch = settings.limit_watch();
+        let mut reader = InputReader::new(text);
+        reader.trace = settings.trace_input;
+        let mut writer = OutputWriter::new(
+            output,
+            settings.case_headers && settings.case_mode != CaseMode::Single,
+        );
+        writer.lint = settings.lint_output;
+        writer.separator = settings.case_separator.clone();
+        writer.case_files = settings.case_files.clone();
+        writer.normalize_newlines = settings.normalize_newlines;
+        writer.max_case_output = settings.max_case_output;
+        let mut cases = CaseIter::new(settings.case_mode, &mut reader);
+        activity::set_total(cases.size_hint());
+        let mut progress = if settings.progress {
+            let total = match cases.size_hint() {
+                0 => None,
+                n => Some(n),
+            };
+            Some(Progress::new(total, settings.contest_end, &clock))
+        } else {
+            None
+        };
+        let profile_hooks = settings.profile_hooks;
+        let case_timing = settings.case_timing;
+        // WASI targets without threads solve every test case on the calling thread.
+        let stack_size = settings
+            .stack_size
+            .filter(|_| !cfg!(all(target_os = "wasi", not(target_feature = "atomics"))));
+        let solve = |case: usize, d: &D| {
+            ProfileHooks::wrap(profile_hooks, case, || logging::with_case(case, || s(d)))
+        };
+        let mut placeholders = 0;
+        let solved = thread::scope(|scope| {
+            let mut solver_thread = stack_size.map(|size| SolverThread::spawn(scope, size, &solve));
+            let mut current_case: usize = 1;
+            loop {
+                if interrupt::requested() {
+                    break;
+                }
+                if budget.exhausted() {
+                    let mut remaining = cases.size_hint();
+                    if remaining == 0 {
+                        while cases.next_case(&mut reader, &p).is_some() {
+                            remaining += 1;
+                        }
+                    }
+                    for case in current_case..current_case + remaining {
+                        writer.write_test_result(case, &settings.placeholder_answer);
+                    }
+                    placeholders = remaining;
+                    break;
+                }
+                let span = CaseSpan::new(current_case);
+                let parse_offset = reader.position();
+                let parse_start = clock.monotonic();
+                let d = match span.parse(|| {
+                    logging::with_case(current_case, || cases.next_case(&mut reader, &p))
+                }) {
+                    Some(d) => d,
+                    None => break,
+                };
+                let parse_time = clock.monotonic().saturating_sub(parse_start);
+                logging::case_started(current_case);
+                activity::case_started(current_case);
+                let solve_start = clock.monotonic();
+                let r = span.solve(|| match solver_thread {
+                    Some(ref mut solver_thread) => solver_thread.solve(current_case, d),
+                    None => solve(current_case, &d),
+                });
+                let mut time = CaseTime {
+                    parse: parse_time,
+                    solve: clock.monotonic().saturating_sub(solve_start),
+                    write: Duration::new(0, 0),
+                };
+                logging::case_finished(current_case, &time);
+                activity::case_finished(current_case);
+                let write_start = clock.monotonic();
+                span.write(|| writer.write_test_result(current_case, &r));
+                time.write = clock.monotonic().saturating_sub(write_start);
+                let input_span = parse_offset..reader.position();
+                span.record(input_span.len(), &time);
+                if case_timing {
+                    time.report(current_case);
+                }
+                if let Some(ref mut progress) = progress {
+                    progress.case_done();
+                }
+                if let Some(ref mut limit_watch) = limit_watch {
+                    limit_watch.check(current_case);
+                }
+                observe(current_case, &r, &time);
+                report.add_case(time, input_span);
+                current_case += 1;
+            }
+            current_case - 1
+        });
+        if let Some(limit_watch) = limit_watch {
+            report.add_warnings(limit_watch.into_warnings());
+        }
+        if interrupt::requested() {
+            report.add_warnings(vec![format!("run interrupted after {} test cases", solved)]);
+        } else if budget.exhausted() && placeholders > 0 {
+            report.add_warnings(vec![budget.report(solved, placeholders)]);
+        }
+        if !interrupt::requested() {
+            if let Some(trailer) = trailer() {
+                writer
+                    .write_all(trailer.as_bytes())
+                    .expect("could not write trailing output to output file");
+            }
+        }
+        let flushed = writer.finish(settings.sync_output);
+        report.add_warnings(writer.lint_warnings);
+        report.output_flushed(&flushed);
+        report.finish(&settings, writer.written, writer.hash);
+        if let Err(e) = flushed {
+            panic!("could not flush output file: {}", e);
+        }
+        watchdog.stop();
+        interrupt_handler.uninstall();
+        interrupt::exit_if_requested(solved);
+        solved
+    }
+
+    /// Executes a parser and solver once per test case over the text of an input file, solving
+    /// test cases in parallel, and returns the number of test cases solved.
+    ///
+    /// Unlike `TestEngine::execute()`, the data for each test case may borrow from the text. Once
+    /// every test case has been written, `trailer` is called, and any text it returns is written
+    /// to the end of the output file.
+    #[cfg(feature = "parallel")]
+    fn execute_text<
+        'a,
+        D: Sized + Send + Sync + 'a,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader<'a>) -> Option<D>,
+        S: Fn(&D) -> R + Sync,
+        F: FnMut(usize, &R, &CaseTime),
+        E: FnOnce() -> Option<String>
+    >
+        (
+        output: O,
+        mut settings: Settings,
+        text: &'a str,
+        p: P,
+        s: S,
+        mut observe: F,
+        trailer: E,
+    ) -> usize {
+        samples::check_gate(&mut settings, &p, &s);
+        settings.seed = Some(rng::start_run(settings.seed));
+        let _isolation = logging::Isolation::begin(settings.isolate_stdout, settings.capture_debug);
+        let _tracking = activity::Tracking::begin(
+            settings.handle_interrupt || settings.stall_watchdog.is_some(),
+        );
+        let interrupt_handler = interrupt::Handler::install(settings.handle_interrupt);
+        let watchdog = Watchdog::start(settings.stall_watchdog);
+        let budget = Budget::start(settings.run_budget, &settings.clock);
+        let clock = Arc::clone(&settings.clock);
+        let mut report = RunReport::new(&settings);
+        let limit_watch = settings
+            .limit_watch()
+            .map(|watch| (Mutex::new(watch), Mutex::new(0)));
+        let mut reader = InputReader::new(text);
+        reader.trace = settings.trace_input;
+        let mut writer = OutputWriter::new(
+            output,
+            settings.case_headers && settings.case_mode != CaseMode::Single,
+        );
+        writer.lint = settings.lint_output;
+        writer.separator = settings.case_separator.clone();
+        writer.case_files = settings.case_files.clone();
+        writer.normalize_newlines = settings.normalize_newlines;
+        writer.max_case_output = settings.max_case_output;
+        let mut cases = CaseIter::new(settings.case_mode, &mut reader);
         let mut data = Vec::with_capacity(0);
-        data.reserve_exact(case_count);
-        for _ in 0..case_count {
-            data.push((p(&mut reader), None));
-        }
-        data.par_iter_mut().for_each(|d| d.1 = Some(s(&d.0)));
-        for (i, &(_, ref r)) in data.iter().enumerate() {
-            writer.write_test_result(
-                i + 1,
-                match *r {
-                    Some(ref x) => x,
-                    None => unreachable!(),
-                },
-            );
+        data.reserve_exact(cases.size_hint());
+        loop {
+            let case = data.len() + 1;
+            let span = CaseSpan::new(case);
+            let parse_offset = reader.position();
+            let parse_start = clock.monotonic();
+            let d = match span.parse(|| {
+                logging::with_case(case, || cases.next_case(&mut reader, &p))
+            }) {
+                Some(d) => d,
+                None => break,
+            };
+            data.push(PendingCase {
+                data: d,
+                parse_time: clock.monotonic().saturating_sub(parse_start),
+                input_span: parse_offset..reader.position(),
+                span: span,
+            });
+        }
+        activity::set_total(data.len());
+        if let Some(tune) = settings.auto_tune.take() {
+            let threads = tune::choose_threads(&tune, &data, |i, d: &PendingCase<D>| {
+                let _ = logging::with_case(i + 1, || s(&d.data));
+            });
+            settings.threads = Some(threads);
+        }
+        let pinning = settings.pin_threads.take().map(|cores| {
+            settings.threads = Some(settings.threads.unwrap_or(cores.len()));
+            affinity::Pinning::new(cores)
+        });
+        let case_timing = settings.case_timing;
+        let profile_hooks = settings.profile_hooks;
+        let progress = if settings.progress {
+            Some(Mutex::new(Progress::new(
+                Some(data.len()),
+                settings.contest_end,
+                &clock,
+            )))
+        } else {
+            None
+        };
+        let solve_case = |i: usize, d: &PendingCase<D>| {
+            logging::case_started(i + 1);
+            activity::case_started(i + 1);
+            let solve_start = clock.monotonic();
+            let r = d.span.solve(|| {
+                ProfileHooks::wrap(profile_hooks, i + 1, || {
+                    logging::with_case(i + 1, || s(&d.data))
+                })
+            });
+            let time = CaseTime {
+                parse: d.parse_time,
+                solve: clock.monotonic().saturating_sub(solve_start),
+                write: Duration::new(0, 0),
+            };
+            logging::case_finished(i + 1, &time);
+            activity::case_finished(i + 1);
+            if case_timing {
+                time.report(i + 1);
+            }
+            if let Some(ref progress) = progress {
+                progress.lock().expect("could not lock progress").case_done();
+            }
+            if let Some((ref watch, ref solved)) = limit_watch {
+                let mut solved = solved.lock().expect("could not lock case count");
+                *solved += 1;
+                watch.lock().expect("could not lock time limit watch").check(*solved);
+            }
+            (r, time)
+        };
+        let batch_size = settings.batch_size.unwrap_or_else(|| {
+            let threads = settings.threads.unwrap_or_else(|| {
+                std::thread::available_parallelism().map_or(1, |n| n.get())
+            });
+            data.len() / (threads * 64)
+        }).clamp(1, data.len().max(1));
+        let mut batches = Vec::with_capacity(0);
+        let mut solve_all = || {
+            data.par_chunks(batch_size)
+                .enumerate()
+                .map(|(batch, cases)| {
+                    if let Some(ref pinning) = pinning {
+                        pinning.pin_current_thread();
+                    }
+                    let mut results = Vec::with_capacity(0);
+                    results.reserve_exact(cases.len());
+                    for (j, d) in cases.iter().enumerate() {
+                        if interrupt::requested() || budget.exhausted() {
+                            break;
+                        }
+                        results.push(solve_case(batch * batch_size + j, d));
+                    }
+                    results
+                })
+                .collect_into(&mut batches)
+        };
+        match settings.threads {
+            Some(threads) => {
+                let config = rayon::Configuration::new().set_num_threads(threads);
+                rayon::ThreadPool::new(config)
+                    .expect("could not create thread pool")
+                    .install(solve_all)
+            }
+            None => solve_all(),
+        }
+        let mut solved = 0;
+        let mut placeholders = 0;
+        for (batch, results) in batches.iter().enumerate() {
+            for (j, &(ref r, time)) in results.iter().enumerate() {
+                let i = batch * batch_size + j;
+                let d = &data[i];
+                let mut time = time;
+                let write_start = clock.monotonic();
+                d.span.write(|| writer.write_test_result(i + 1, r));
+                time.write = clock.monotonic().saturating_sub(write_start);
+                d.span.record(d.input_span.len(), &time);
+                observe(i + 1, r, &time);
+                report.add_case(time, d.input_span.clone());
+                solved += 1;
+            }
+            // A batch is only cut short if the run was interrupted, in which case test results
+            // after the first unsolved test case cannot be written in order, or if the run budget
+            // was spent, in which case the unsolved test cases receive placeholders.
+            let batch_end = data.len().min((batch + 1) * batch_size);
+            let unsolved = batch * batch_size + results.len()..batch_end;
+            if !unsolved.is_empty() {
+                if interrupt::requested() {
+                    break;
+                }
+                for i in unsolved {
+                    writer.write_test_result(i + 1, &settings.placeholder_answer);
+                    placeholders += 1;
+                }
+            }
+        }
+        if let Some((watch, _)) = limit_watch {
+            let watch = watch.into_inner().expect("could not lock time limit watch");
+            report.add_warnings(watch.into_warnings());
+        }
+        if interrupt::requested() {
+            report.add_warnings(vec![format!("run interrupted after {} test cases", solved)]);
+        } else if budget.exhausted() && placeholders > 0 {
+            report.add_warnings(vec![budget.report(solved, placeholders)]);
+        }
+        if !interrupt::requested() {
+            if let Some(trailer) = trailer() {
+                writer
+                    .write_all(trailer.as_bytes())
+                    .expect("could not write trailing output to output file");
+            }
         }
+        let flushed = writer.finish(settings.sync_output);
+        report.add_warnings(writer.lint_warnings);
+        report.output_flushed(&flushed);
+        report.finish(&settings, writer.written, writer.hash);
+        if let Err(e) = flushed {
+            panic!("could not flush output file: {}", e);
+        }
+        watchdog.stop();
+        interrupt_handler.uninstall();
+        interrupt::exit_if_requested(solved);
+        solved
     }
 }
 
@@ -197,6 +1494,156 @@ impl TestEngine<OsString, OsString> {
         let output_file_path = args.next().expect("output file path not specified");
         Self::new(input_file_path, output_file_path)
     }
+
+    /// Creates a new test engine for one of several problems handled by the same binary, using
+    /// command line arguments of the form `<problem> <input> <output>`.
+    ///
+    /// Returns the problem name along with the test engine; the `multi_problem!` macro uses this
+    /// to dispatch to the selected problem.
+    ///
+    /// Calling this method is cheap; no files are opened until `TestEngine::run()` is called.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the problem name, input file path or output file path is missing,
+    /// or if the problem name is not valid Unicode.
+    pub fn from_problem_args() -> (String, TestEngine<OsString, OsString>) {
+        let mut args = env::args_os();
+        let problem = args.nth(1)
+            .expect("problem name not specified")
+            .into_string()
+            .expect("problem name is not valid Unicode");
+        let input_file_path = args.next().expect("input file path not specified");
+        let output_file_path = args.next().expect("output file path not specified");
+        (problem, Self::new(input_file_path, output_file_path))
+    }
+}
+
+impl<R: Read, W: Write> TestEngine<FromReader<R>, ToWriter<W>> {
+    /// Creates a new test engine that reads test data from `reader` and writes test results to
+    /// `writer`.
+    ///
+    /// This is useful for running a solution on in-memory data, e.g. `&[u8]` and `Vec<u8>`.
+    pub fn with_io(reader: R, writer: W) -> TestEngine<FromReader<R>, ToWriter<W>> {
+        TestEngine::new(FromReader(reader), ToWriter(writer))
+    }
+}
+
+impl DynTestEngine {
+    /// Creates a new test engine that reads test data from, and writes test results to,
+    /// trait objects.
+    ///
+    /// Unlike `TestEngine::with_io()`, the returned test engine has the same type regardless of
+    /// where test data comes from or goes to, which suits applications that embed solutions (such
+    /// as GUIs, or servers that run solutions) and choose sources at runtime, without the engine
+    /// being compiled once per combination of reader and writer.
+    pub fn with_dyn_io(reader: Box<dyn BufRead>, writer: Box<dyn Write>) -> DynTestEngine {
+        TestEngine::new(FromReader(reader), ToWriter(writer))
+    }
+}
+
+impl TestEngine<FromReader<File>, ToWriter<File>> {
+    /// Creates a new test engine that reads test data from, and writes test results to, files that
+    /// have already been opened.
+    ///
+    /// Besides `File`, this accepts any type that converts into one, such as `OwnedFd` on Unix and
+    /// `OwnedHandle` on Windows, which suits harnesses and judges that pre-open descriptors and
+    /// pass them down. The output file is written from its current position; it is not truncated.
+    pub fn from_files<A: Into<File>, B: Into<File>>(
+        input: A,
+        output: B,
+    ) -> TestEngine<FromReader<File>, ToWriter<File>> {
+        TestEngine::new(FromReader(input.into()), ToWriter(output.into()))
+    }
+}
+
+impl TestEngine<Stdio, Stdio> {
+    /// Creates a new test engine that reads test data from standard input and writes test results
+    /// to standard output.
+    pub fn stdio() -> TestEngine<Stdio, Stdio> {
+        Self::new(Stdio, Stdio)
+    }
+
+    /// Creates a new test engine suited to Kattis-style judges: test data is read from standard
+    /// input until the end of the input is reached, and test results are written to standard
+    /// output without `"Case #N:"` headers.
+    ///
+    /// If the entire input is a single test case, call `TestEngine::case_mode()` with
+    /// `CaseMode::Single`.
+    pub fn kattis() -> TestEngine<Stdio, Stdio> {
+        Self::stdio()
+            .case_mode(CaseMode::UntilEof)
+            .case_headers(false)
+    }
+}
+
+impl TestEngine<PathBuf, PathBuf> {
+    /// Creates a new test engine for an old-style Code Jam input file, given the directory it was
+    /// downloaded to, the problem letter (e.g. `"A"`) and the input size (e.g. `"small"`).
+    ///
+    /// If the directory contains files named in the form `A-small-attemptN.in`, the one with the
+    /// highest attempt number is used; otherwise `A-small-practice.in` or `A-small.in` is used, in
+    /// that order. The output file is written alongside the input file, with the same name but an
+    /// `.out` extension.
+    ///
+    /// Calling this method is cheap; no files are opened until `TestEngine::run()` is called.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the directory cannot be read, or if no matching input file exists.
+    pub fn from_attempt<P: AsRef<Path>>(
+        dir: P,
+        problem: &str,
+        size: &str,
+    ) -> TestEngine<PathBuf, PathBuf> {
+        let dir = dir.as_ref();
+        let attempt_prefix = format!("{}-{}-attempt", problem, size);
+        let mut newest: Option<(u32, PathBuf)> = None;
+        for entry in fs::read_dir(dir).expect("could not read input file directory") {
+            let path = entry.expect("could not read input file directory").path();
+            let attempt = path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(attempt_prefix.as_str()))
+                .and_then(|name| name.strip_suffix(".in"))
+                .and_then(|n| n.parse::<u32>().ok());
+            if let Some(n) = attempt {
+                let is_newer = match newest {
+                    Some((m, _)) => n > m,
+                    None => true,
+                };
+                if is_newer {
+                    newest = Some((n, path));
+                }
+            }
+        }
+        let input_file_path = match newest {
+            Some((_, path)) => path,
+            None => [
+                format!("{}-{}-practice.in", problem, size),
+                format!("{}-{}.in", problem, size),
+            ].iter()
+                .map(|name| dir.join(name))
+                .find(|path| path.is_file())
+                .expect("could not find a matching input file"),
+        };
+        let output_file_path = input_file_path.with_extension("out");
+        Self::new(input_file_path, output_file_path)
+    }
+
+    /// Creates a new test engine following the USACO convention, where the input and output file
+    /// paths are derived from the problem name: `problem.in` and `problem.out` respectively, in the
+    /// current directory.
+    ///
+    /// As USACO problems consist of a single test case, the returned test engine uses
+    /// `CaseMode::Single`; this can be overridden by calling `TestEngine::case_mode()`.
+    ///
+    /// Calling this method is cheap; no files are opened until `TestEngine::run()` is called.
+    pub fn from_problem_name(name: &str) -> TestEngine<PathBuf, PathBuf> {
+        Self::new(
+            PathBuf::from(format!("{}.in", name)),
+            PathBuf::from(format!("{}.out", name)),
+        ).case_mode(CaseMode::Single)
+    }
 }
 
 impl Default for TestEngine<OsString, OsString> {
@@ -205,96 +1652,461 @@ impl Default for TestEngine<OsString, OsString> {
     }
 }
 
-impl InputReader {
+impl<P: AsRef<Path>> Input for P {
+    type Reader = InputFile;
+
+    fn open(self) -> io::Result<InputFile> {
+        InputFile::open(&paths::expand_home(self.as_ref()))
+    }
+}
+
+impl<P: AsRef<Path>> Output for P {
+    type Writer = File;
+
+    fn open(self) -> io::Result<File> {
+        paths::create_output(self.as_ref())
+    }
+
+    fn sync(writer: &mut File) -> io::Result<bool> {
+        writer.sync_all()?;
+        Ok(true)
+    }
+}
+
+impl<R: Read> Input for FromReader<R> {
+    type Reader = R;
+
+    fn open(self) -> io::Result<R> {
+        Ok(self.0)
+    }
+}
+
+impl<W: Write> Output for ToWriter<W> {
+    type Writer = W;
+
+    fn open(self) -> io::Result<W> {
+        Ok(self.0)
+    }
+}
+
+impl Input for Stdio {
+    type Reader = io::Stdin;
+
+    fn open(self) -> io::Result<io::Stdin> {
+        Ok(io::stdin())
+    }
+}
+
+impl Output for Stdio {
+    type Writer = io::Stdout;
+
+    fn open(self) -> io::Result<io::Stdout> {
+        Ok(io::stdout())
+    }
+}
+
+impl<'a> InputReader<'a> {
+    /// Returns `true` if the input file contains nothing but whitespace past the current position.
+    pub fn is_eof(&self) -> bool {
+        match self.input.get(self.offset..) {
+            Some(s) => s.trim().is_empty(),
+            None => true,
+        }
+    }
+
     /// Reads a line of text from the input file, consuming the end-of-line marker if one is
     /// present.
-    pub fn read_next_line(&mut self) -> &str {
+    pub fn read_next_line(&mut self) -> &'a str {
         if self.offset >= self.input.len() {
             panic!("could not read line from input file: reached end of file");
         }
         let start = self.offset;
-        let end = self.input
-            .char_indices()
-            .skip(start)
-            .take_while(|&(_, c)| c != '\n')
-            .last()
-            .map_or(start, |(i, _)| i + 1);
+        let end = self.input[start..]
+            .find('\n')
+            .map_or(self.input.len(), |i| start + i);
         let s = &self.input[start..end];
-        if s.is_empty() {
-            self.offset = end + 2;
-        } else {
-            self.offset = end + 1;
+        self.offset = end + 1;
+        if self.trace {
+            write_debug(format_args!("input line: {:?}", s));
         }
         s
     }
 
-    /// Creates a new input reader over the given input file.
-    fn new<P: AsRef<Path>>(path: P) -> InputReader {
-        let mut file = OpenOptions::new()
-            .read(true)
-            .open(path)
-            .expect("could not open input file for reading");
-        let mut s = String::with_capacity(0);
-        let _ = file.read_to_string(&mut s)
-            .expect("could not read input file into string");
+    /// Reads a whitespace-delimited token from the input file.
+    ///
+    /// Tokens may span several lines. If nothing but whitespace remains on the current line after
+    /// the token, the end-of-line marker is also consumed, so that a subsequent call to
+    /// `InputReader::read_next_line()` reads the following line.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the end of the input file has been reached.
+    pub fn read_token(&mut self) -> &'a str {
+        let (token, offset) = match self.token_at(self.offset) {
+            Some(next) => next,
+            None => panic!("could not read token from input file: reached end of file"),
+        };
+        self.offset = offset;
+        if self.trace {
+            write_debug(format_args!("input token: {:?}", token));
+        }
+        token
+    }
+
+    /// Returns the next whitespace-delimited token in the input file without consuming it, or
+    /// `None` if nothing but whitespace remains.
+    ///
+    /// This lets a parser branch on the next value, e.g. when a field is only present if the
+    /// value that follows has a certain form.
+    pub fn peek_token(&self) -> Option<&'a str> {
+        self.token_at(self.offset).map(|(token, _)| token)
+    }
+
+    /// Returns up to the next `n` whitespace-delimited tokens in the input file without consuming
+    /// them; fewer are returned if the end of the input file is reached first.
+    pub fn peek_n_tokens(&self, n: usize) -> Vec<&'a str> {
+        let mut tokens = Vec::with_capacity(0);
+        tokens.reserve_exact(n);
+        let mut offset = self.offset;
+        while tokens.len() < n {
+            match self.token_at(offset) {
+                Some((token, next)) => {
+                    tokens.push(token);
+                    offset = next;
+                }
+                None => break,
+            }
+        }
+        tokens
+    }
+
+    /// Reads a value of type `T` from the input file.
+    pub fn read<T: FromInput>(&mut self) -> T {
+        T::from_input(self)
+    }
+
+    /// Reads an arbitrary-precision integer from the input file, as a whitespace-delimited token.
+    ///
+    /// This method is only available if the `bigint` feature is enabled.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the token is not a valid integer.
+    #[cfg(feature = "bigint")]
+    pub fn read_bigint(&mut self) -> BigInt {
+        self.read()
+    }
+
+    /// Reads `n` values of type `T` from the input file.
+    pub fn read_vec<T: FromInput>(&mut self, n: usize) -> Vec<T> {
+        let mut v = Vec::with_capacity(0);
+        v.reserve_exact(n);
+        for _ in 0..n {
+            v.push(T::from_input(self));
+        }
+        v
+    }
+
+    /// Reads `N` values of type `T` from the input file into an array, e.g.
+    /// `let [x, y, z] = input.read_array::<i64, 3>();`.
+    ///
+    /// Unlike `InputReader::read_vec()`, this does not allocate, and the result can be
+    /// destructured.
+    pub fn read_array<T: FromInput, const N: usize>(&mut self) -> [T; N] {
+        self.read()
+    }
+
+    /// Reads `rows` lines of text from the input file as a grid of bytes, one row per line.
+    ///
+    /// This is the fastest way to read a grid, but a non-ASCII symbol occupies several cells, one
+    /// per byte of its UTF-8 encoding; use `InputReader::read_char_grid()` for such grids.
+    pub fn read_grid(&mut self, rows: usize) -> Vec<Vec<u8>> {
+        let mut grid = Vec::with_capacity(0);
+        grid.reserve_exact(rows);
+        for _ in 0..rows {
+            grid.push(self.read_next_line().trim_end_matches('\r').as_bytes().to_vec());
+        }
+        grid
+    }
+
+    /// Reads `rows` lines of text from the input file as a grid of characters, one row per line.
+    ///
+    /// Unlike `InputReader::read_grid()`, each `char` occupies one cell, so grids containing
+    /// non-ASCII symbols (e.g. `'█'` or `'·'`) have the expected width. Symbols built from
+    /// several `char`s, such as letters with combining accents, still occupy one cell per `char`.
+    pub fn read_char_grid(&mut self, rows: usize) -> Vec<Vec<char>> {
+        let mut grid = Vec::with_capacity(0);
+        grid.reserve_exact(rows);
+        for _ in 0..rows {
+            grid.push(self.read_next_line().trim_end_matches('\r').chars().collect());
+        }
+        grid
+    }
+
+    /// Reads a line of text from the input file and splits it into fixed-width fields, the `i`th
+    /// of which is `widths[i]` characters wide.
+    ///
+    /// Fields are not trimmed, so whitespace within them is preserved. As trailing spaces are
+    /// often stripped from input files, fields extending past the end of the line are cut short,
+    /// and fields starting past the end are empty. Any characters after the last field are
+    /// ignored.
+    pub fn read_columns(&mut self, widths: &[usize]) -> Vec<&'a str> {
+        let line = self.read_next_line().trim_end_matches('\r');
+        let mut columns = Vec::with_capacity(0);
+        columns.reserve_exact(widths.len());
+        let mut rest = line;
+        for &width in widths {
+            let end = rest.char_indices().nth(width).map_or(rest.len(), |(i, _)| i);
+            columns.push(&rest[..end]);
+            rest = &rest[end..];
+        }
+        columns
+    }
+
+    /// Returns the whitespace-delimited token beginning at or after the given byte offset, along
+    /// with the offset at which reading continues after it, or `None` if nothing but whitespace
+    /// remains.
+    ///
+    /// If nothing but whitespace remains on the line after the token, reading continues on the
+    /// following line.
+    fn token_at(&self, offset: usize) -> Option<(&'a str, usize)> {
+        // The offset may lie past the end of the input file, after a final line without an
+        // end-of-line marker has been read.
+        let rest = self.input.get(offset..).unwrap_or("");
+        let start = self.input.len() - rest.trim_start().len();
+        let token_len = self.input[start..]
+            .find(char::is_whitespace)
+            .unwrap_or(self.input.len() - start);
+        if token_len == 0 {
+            return None;
+        }
+        let end = start + token_len;
+        // Only the whitespace directly after the token is scanned, so that reading every token
+        // of a long line takes time linear in its length.
+        let after = self.input[end..].trim_start_matches(|c: char| c.is_whitespace() && c != '\n');
+        let next = if after.starts_with('\n') {
+            self.input.len() - after.len() + 1
+        } else {
+            end
+        };
+        Some((&self.input[start..end], next))
+    }
+
+    /// Returns the current position within the input file, as a byte offset.
+    fn position(&self) -> usize {
+        self.offset.min(self.input.len())
+    }
+
+    /// Creates a new input reader over the text of an input file.
+    fn new(input: &'a str) -> InputReader<'a> {
         InputReader {
-            input: s,
+            input: input,
             offset: 0,
+            trace: false,
+        }
+    }
+}
+
+impl CaseIter {
+    /// Creates a new case iterator, reading the number of test cases from the input file if
+    /// required.
+    fn new(mode: CaseMode, reader: &mut InputReader) -> CaseIter {
+        match mode {
+            CaseMode::Counted => CaseIter::Counted(
+                usize::from_str_radix(reader.read_next_line(), 10)
+                    .expect("could not parse test case count"),
+            ),
+            CaseMode::UntilEof => CaseIter::UntilEof,
+            CaseMode::Single => CaseIter::Counted(1),
         }
     }
 
-    /// Reads the number of test cases from the input file.
-    fn get_case_count(&mut self) -> usize {
-        usize::from_str_radix(self.read_next_line(), 10).expect("could not parse test case count")
+    /// Returns the number of test cases expected to remain, or zero if this is not known.
+    fn size_hint(&self) -> usize {
+        match *self {
+            CaseIter::Counted(n) => n,
+            CaseIter::UntilEof => 0,
+        }
+    }
+
+    /// Parses the next test case, or returns `None` if no test cases remain.
+    fn next_case<'a, D, P: Fn(&mut InputReader<'a>) -> Option<D>>(
+        &mut self,
+        reader: &mut InputReader<'a>,
+        p: &P,
+    ) -> Option<D> {
+        match *self {
+            CaseIter::Counted(0) => None,
+            CaseIter::Counted(ref mut n) => {
+                *n -= 1;
+                p(reader)
+            }
+            CaseIter::UntilEof => if reader.is_eof() {
+                None
+            } else {
+                p(reader)
+            },
+        }
+    }
+}
+
+impl CaseTime {
+    /// Writes the time taken by a test case to standard error.
+    fn report(&self, case: usize) {
+        eprintln!(
+            "Case #{}: parsed in {}, solved in {}",
+            case,
+            format_duration(self.parse),
+            format_duration(self.solve)
+        );
     }
 }
 
-impl OutputWriter {
+impl<W: Write> OutputWriter<W> {
     /// Creates a new output writer over the given output file.
-    fn new<P: AsRef<Path>>(path: P) -> OutputWriter {
-        OutputWriter(
-            LineWriter::new(
-                OpenOptions::new()
-                    .write(true)
-                    .truncate(true)
-                    .create(true)
-                    .open(path)
-                    .expect("could not open output file for writing"),
-            ),
-        )
+    fn new<O: Output<Writer = W>>(output: O, case_headers: bool) -> OutputWriter<W> {
+        OutputWriter {
+            file: LineWriter::new(output.open().expect("could not open output file for writing")),
+            case_headers: case_headers,
+            written: 0,
+            hash: history::FNV_OFFSET_BASIS,
+            lint: false,
+            lint_warnings: Vec::new(),
+            separator: String::new(),
+            ended_line: None,
+            sync: O::sync,
+            case_files: None,
+            normalize_newlines: false,
+            max_case_output: None,
+        }
     }
 
-    /// Writes a test result to the output file.
+    /// Writes a test result to the output file, then flushes the output file, so that the test
+    /// result is not lost if the run is aborted before the output file is closed. The test result
+    /// is also written to its own case file, if configured.
     fn write_test_result<R: Display>(&mut self, case: usize, result: R) {
-        let case_prefix = "Case #";
-        let case_number = case.to_string();
-        let case_colon = ":";
-        let r = result.to_string();
-        let mut output = String::with_capacity(0);
-        output.reserve_exact(case_prefix.len() + case_number.len() + case_colon.len() + r.len(),);
-        output.push_str(case_prefix);
-        output.push_str(&case_number);
-        output.push_str(case_colon);
-        output.push_str(&r);
-        self.write_all(output.as_bytes())
+        let mut r = result.to_string();
+        if self.normalize_newlines && r.contains("\r\n") {
+            r = r.replace("\r\n", "\n");
+        }
+        if let Some(limit) = self.max_case_output {
+            output_limit::check(limit, case, &r);
+        }
+        if self.lint {
+            let warnings = lint::check_answer(case, &r);
+            self.lint_warnings.extend(warnings);
+        }
+        self.write_separator();
+        self.ended_line = Some(r.ends_with('\n'));
+        if self.case_headers {
+            let case_prefix = "Case #";
+            let case_number = case.to_string();
+            let case_colon = ":";
+            let mut output = String::with_capacity(0);
+            output.reserve_exact(
+                case_prefix.len() + case_number.len() + case_colon.len() + r.len(),
+            );
+            output.push_str(case_prefix);
+            output.push_str(&case_number);
+            output.push_str(case_colon);
+            output.push_str(&r);
+            self.write_all(output.as_bytes())
+                .expect("could not write test result to output file");
+        } else {
+            self.write_all(r.as_bytes())
+                .expect("could not write test result to output file");
+        }
+        self.flush().expect("could not flush output file");
+        if let Some(ref template) = self.case_files {
+            let path = template.replace("{case}", &case.to_string());
+            let path = paths::expand_home(Path::new(&path));
+            paths::write_file(&path, r.strip_prefix(' ').unwrap_or(&r))
+                .expect("could not write test result to case file");
+        }
+    }
+
+    /// Flushes everything written to the output file and, if `sync` is `true`, syncs it to durable
+    /// storage, returning whether it was synced.
+    fn finish(&mut self, sync: bool) -> io::Result<bool> {
+        self.file.flush()?;
+        if sync {
+            (self.sync)(self.file.get_mut())
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Writes the separator between the previous test result and the next one, if this is not the
+    /// first test result, starting a new line first if the previous test result did not end one.
+    fn write_separator(&mut self) {
+        let ended_line = match self.ended_line {
+            Some(ended_line) => ended_line,
+            None => return,
+        };
+        if self.separator.is_empty() {
+            return;
+        }
+        if !ended_line {
+            self.write_all(b"\n")
+                .expect("could not write test result to output file");
+        }
+        let separator = mem::take(&mut self.separator);
+        self.write_all(separator.as_bytes())
             .expect("could not write test result to output file");
+        self.separator = separator;
     }
 }
 
-impl Write for OutputWriter {
+impl<W: Write> Write for OutputWriter<W> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.0.write(buf)
+        let n = self.file.write(buf)?;
+        self.written += n;
+        self.hash = history::fnv1a(self.hash, &buf[..n]);
+        Ok(n)
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        self.0.flush()
+        self.file.flush()
     }
 
     fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
-        self.0.write_all(buf)
+        self.file.write_all(buf)?;
+        self.written += buf.len();
+        self.hash = history::fnv1a(self.hash, buf);
+        Ok(())
     }
+}
 
-    fn write_fmt(&mut self, fmt: Arguments) -> io::Result<()> {
-        self.0.write_fmt(fmt)
+/// Reads the given input file into a string, decoded with the given encoding.
+///
+/// # Panics
+///
+/// This function panics if the input file cannot be opened or read, or is not valid UTF-8 when
+/// `InputEncoding::Utf8` is used.
+fn read_input<I: Input>(input: I, encoding: InputEncoding) -> String {
+    let mut file = input.open().expect("could not open input file for reading");
+    let mut s = String::with_capacity(0);
+    match encoding {
+        InputEncoding::Utf8 => {
+            let _ = file.read_to_string(&mut s)
+                .expect("could not read input file into string");
+        }
+        InputEncoding::Lossy | InputEncoding::Latin1 => {
+            let mut bytes = Vec::with_capacity(0);
+            let _ = file.read_to_end(&mut bytes)
+                .expect("could not read input file");
+            let bytes = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(&bytes);
+            if encoding == InputEncoding::Lossy {
+                s.push_str(&String::from_utf8_lossy(bytes));
+            } else {
+                s.extend(bytes.iter().map(|&b| char::from(b)));
+            }
+        }
     }
+    s
+}
+
+/// Formats a duration as a number of seconds with millisecond precision.
+fn format_duration(d: Duration) -> String {
+    format!("{}.{:03}s", d.as_secs(), d.subsec_millis())
 }
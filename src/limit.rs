@@ -0,0 +1,119 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Warnings for runs approaching their time limit.
+
+use super::{format_duration, logging, Clock, Input, Output, Settings, TestEngine};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The fractions of the time limit at which warnings are raised by default.
+const DEFAULT_THRESHOLDS: [f64; 2] = [0.5, 0.8];
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Sets the time limit for solving the input file.
+    ///
+    /// Once set, a warning is written to standard error as soon as the time elapsed since the run
+    /// began crosses 50% and 80% of the limit (or the thresholds set with
+    /// `TestEngine::time_limit_warnings()`), and again if the limit is exceeded, so that a run
+    /// that will not finish in time can be aborted early. Warnings are checked each time a test
+    /// case is solved.
+    pub fn time_limit(mut self, limit: Duration) -> TestEngine<I, O> {
+        self.settings.time_limit = Some(limit);
+        self
+    }
+
+    /// Sets the fractions of the time limit at which warnings are raised, e.g. `&[0.5, 0.8]`.
+    pub fn time_limit_warnings(mut self, thresholds: &[f64]) -> TestEngine<I, O> {
+        self.settings.time_limit_thresholds = Some(thresholds.to_vec());
+        self
+    }
+}
+
+impl Settings {
+    /// Starts watching the time elapsed during a run, if a time limit is set.
+    pub fn limit_watch(&self) -> Option<LimitWatch> {
+        let thresholds = self.time_limit_thresholds.as_ref().map(|t| &t[..]);
+        self.time_limit
+            .map(|limit| LimitWatch::new(limit, thresholds, &self.clock))
+    }
+}
+
+/// Watches the time elapsed during a run, raising warnings as it approaches the time limit.
+#[derive(Debug)]
+pub struct LimitWatch {
+    /// When the run began.
+    start: Duration,
+    /// The clock the run is measured by.
+    clock: Arc<dyn Clock>,
+    /// The time limit.
+    limit: Duration,
+    /// The fractions of the time limit at which warnings are raised, in ascending order, followed
+    /// by `1.0` for the limit itself.
+    thresholds: Vec<f64>,
+    /// The index of the next threshold to be crossed.
+    next: usize,
+    /// The warnings raised so far.
+    warnings: Vec<String>,
+}
+
+impl LimitWatch {
+    /// Starts watching a run with the given time limit and warning thresholds, as measured by
+    /// `clock`.
+    pub fn new(limit: Duration, thresholds: Option<&[f64]>, clock: &Arc<dyn Clock>) -> LimitWatch {
+        let mut thresholds = thresholds.unwrap_or(&DEFAULT_THRESHOLDS).to_vec();
+        thresholds.retain(|&t| t > 0.0 && t < 1.0);
+        thresholds.sort_by(|a, b| a.partial_cmp(b).expect("threshold is not a number"));
+        thresholds.push(1.0);
+        LimitWatch {
+            start: clock.monotonic(),
+            clock: Arc::clone(clock),
+            limit: limit,
+            thresholds: thresholds,
+            next: 0,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Checks the time elapsed after `solved` test cases have been solved, raising a warning if a
+    /// threshold has been crossed since the last check.
+    pub fn check(&mut self, solved: usize) {
+        let elapsed = self.clock.monotonic().saturating_sub(self.start);
+        let used = elapsed.as_secs_f64() / self.limit.as_secs_f64();
+        let mut crossed = None;
+        while self.next < self.thresholds.len() && used >= self.thresholds[self.next] {
+            crossed = Some(self.thresholds[self.next]);
+            self.next += 1;
+        }
+        let threshold = match crossed {
+            Some(threshold) => threshold,
+            None => return,
+        };
+        let warning = if threshold >= 1.0 {
+            format!(
+                "time limit of {} exceeded after {} cases",
+                format_duration(self.limit),
+                solved
+            )
+        } else {
+            format!(
+                "{:.0}% of the time limit of {} used after {} cases",
+                threshold * 100.0,
+                format_duration(self.limit),
+                solved
+            )
+        };
+        eprintln!("warning: {}", warning);
+        logging::warn(&warning);
+        self.warnings.push(warning);
+    }
+
+    /// Returns the warnings raised during the run.
+    pub fn into_warnings(self) -> Vec<String> {
+        self.warnings
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Iterating over the tokens of a line one at a time, for lines holding millions of values.
+
+use super::{write_debug, InputReader};
+
+/// An iterator over the whitespace-delimited tokens remaining on the current line of an input
+/// file, returned by `InputReader::line_tokens()`.
+#[derive(Debug)]
+pub struct LineTokens<'a: 'r, 'r> {
+    /// The input reader, positioned after the last token yielded.
+    reader: &'r mut InputReader<'a>,
+    /// Whether the end of the line has been reached.
+    finished: bool,
+}
+
+impl<'a> InputReader<'a> {
+    /// Returns an iterator over the whitespace-delimited tokens remaining on the current line,
+    /// such as `input.line_tokens().map(|t| t.parse::<u64>().unwrap())`.
+    ///
+    /// Each token is found and consumed only when the iterator is advanced, so a line holding
+    /// millions of values can be folded into a sum, or fed into a data structure, without first
+    /// collecting its tokens into a `Vec`, and the work done per token does not depend on the
+    /// length of the line. Tokens are borrowed from the input file, which, as for every other
+    /// method of `InputReader`, is held in memory in full; memory use is not bounded by a buffer
+    /// size. The end-of-line marker is consumed along with the last token on the
+    /// line, so that subsequent reads start on the following line even if the iterator is never
+    /// advanced past it, e.g. by `take(n)`; if the iterator is dropped earlier, the reader is left
+    /// just after the last token yielded.
+    pub fn line_tokens<'r>(&'r mut self) -> LineTokens<'a, 'r> {
+        LineTokens {
+            reader: self,
+            finished: false,
+        }
+    }
+}
+
+impl<'a: 'r, 'r> Iterator for LineTokens<'a, 'r> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.finished {
+            return None;
+        }
+        let input = self.reader.input;
+        let rest = input.get(self.reader.offset..).unwrap_or("");
+        let trimmed = rest.trim_start_matches(|c: char| c.is_whitespace() && c != '\n');
+        let start = input.len() - trimmed.len();
+        if trimmed.is_empty() || trimmed.starts_with('\n') {
+            self.finished = true;
+            self.reader.offset = (start + 1).min(input.len());
+            return None;
+        }
+        let end = trimmed
+            .find(char::is_whitespace)
+            .map_or(input.len(), |i| start + i);
+        let after = input[end..].trim_start_matches(|c: char| c.is_whitespace() && c != '\n');
+        self.reader.offset = if after.starts_with('\n') {
+            self.finished = true;
+            input.len() - after.len() + 1
+        } else {
+            end
+        };
+        let token = &input[start..end];
+        if self.reader.trace {
+            write_debug(format_args!("input token: {:?}", token));
+        }
+        Some(token)
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checks for whitespace mistakes in test results.
+
+use super::{logging, Input, Output, TestEngine};
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Sets whether each test result is checked for whitespace that commonly causes a correct
+    /// answer to be rejected.
+    ///
+    /// Once enabled, a warning naming the test case is written to standard error for any test
+    /// result that contains trailing spaces at the end of a line, two consecutive spaces or a tab,
+    /// or that consists of nothing but whitespace. The single space expected after the
+    /// `"Case #N:"` header is not a mistake. Warnings are also included in the run summary. This
+    /// is disabled by default.
+    pub fn lint_output(mut self, enabled: bool) -> TestEngine<I, O> {
+        self.settings.lint_output = enabled;
+        self
+    }
+}
+
+/// Checks the test result for one test case, writing a warning to standard error for each
+/// mistake found, and returns the warnings.
+pub fn check_answer(case: usize, answer: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    if answer.trim().is_empty() {
+        problems.push("is empty");
+    } else {
+        if answer.split('\n').any(|line| line.ends_with(' ')) {
+            problems.push("has trailing spaces");
+        }
+        if answer.contains("  ") {
+            problems.push("contains two consecutive spaces");
+        }
+        if answer.contains('\t') {
+            problems.push("contains a tab");
+        }
+    }
+    problems
+        .into_iter()
+        .map(|problem| {
+            let warning = format!("Case #{}: test result {}", case, problem);
+            eprintln!("warning: {}", warning);
+            logging::warn(&warning);
+            warning
+        })
+        .collect()
+}
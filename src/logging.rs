@@ -0,0 +1,362 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Debug output and integration with the `log` crate.
+//!
+//! The test engine records which test case is being parsed or solved on each thread, which
+//! `gcj_debug!` uses to tag debug output. When the `log` feature is enabled, the test engine also
+//! emits log records as each test case is parsed and solved, and `CaseLogger` tags records emitted
+//! by parsers and solvers with the current test case. Otherwise, the logging hooks in this module
+//! do nothing.
+
+use super::{CaseTime, Input, InputReader, Output, TestEngine};
+use std::cell::{Cell, RefCell};
+use std::fmt::{Arguments, Debug, Display};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "log")]
+use super::format_duration;
+#[cfg(feature = "log")]
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+thread_local! {
+    /// The test case being parsed or solved on this thread, if any.
+    static CURRENT_CASE: Cell<Option<usize>> = const { Cell::new(None) };
+    /// The debug output captured from the test case on this thread, if it is being captured.
+    static CAPTURED: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Whether standard output is reserved for test results, so that `print!` and `println!` write to
+/// standard error instead.
+static STDOUT_ISOLATED: AtomicBool = AtomicBool::new(false);
+
+/// Whether debug output is captured per test case.
+static CAPTURE_DEBUG: AtomicBool = AtomicBool::new(false);
+
+/// Isolates standard output and captures debug output for the duration of a run, as configured.
+#[derive(Debug)]
+pub struct Isolation {
+    /// Whether either setting was enabled for the run.
+    enabled: bool,
+}
+
+/// Captures the debug output written on this thread while a test case is being parsed or solved.
+#[derive(Debug)]
+struct Capture {
+    /// Whether this capture owns the buffer, rather than being nested within another capture.
+    active: bool,
+}
+
+/// Records a test case as the current test case on this thread, restoring the previous one when
+/// dropped, even if the test case panics.
+#[derive(Debug)]
+struct CaseScope {
+    /// The test case recorded before this one, if any.
+    previous: Option<usize>,
+}
+
+/// A logger adapter that prefixes every record emitted while a test case is being parsed or
+/// solved with `"Case #N: "`.
+///
+/// As loggers must live for the rest of the program, a `CaseLogger` is usually stored in a
+/// `static`, e.g. `static LOGGER: CaseLogger<MyLogger> = CaseLogger::new(MyLogger);`, and then
+/// installed with `LOGGER.init(LevelFilter::Debug)`.
+#[cfg(feature = "log")]
+#[derive(Debug)]
+pub struct CaseLogger<L: Log> {
+    /// The logger that tagged records are forwarded to.
+    inner: L,
+}
+
+#[cfg(feature = "log")]
+impl<L: Log> CaseLogger<L> {
+    /// Creates a new logger adapter forwarding tagged records to `inner`.
+    pub const fn new(inner: L) -> CaseLogger<L> {
+        CaseLogger { inner: inner }
+    }
+
+    /// Installs this logger as the global logger, with the given maximum level.
+    ///
+    /// This fails if a global logger has already been installed.
+    pub fn init(&'static self, level: LevelFilter) -> Result<(), SetLoggerError> {
+        ::log::set_logger(self)?;
+        ::log::set_max_level(level);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "log")]
+impl<L: Log> Log for CaseLogger<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        match current_case() {
+            Some(case) => self.inner.log(
+                &Record::builder()
+                    .args(format_args!("Case #{}: {}", case, record.args()))
+                    .metadata(record.metadata().clone())
+                    .module_path(record.module_path())
+                    .file(record.file())
+                    .line(record.line())
+                    .build(),
+            ),
+            None => self.inner.log(record),
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Sets whether every line and token read from the input file is echoed to standard error.
+    ///
+    /// Each line or token is written in quotes as it is handed to the parser, prefixed with the
+    /// test case being parsed, as `gcj_debug!` does. Lines read before the first test case (such as
+    /// the number of test cases) have no prefix. This is disabled by default.
+    pub fn trace_input(mut self, enabled: bool) -> TestEngine<I, O> {
+        self.settings.trace_input = enabled;
+        self
+    }
+
+    /// Sets whether standard output is reserved for test results while the run is in progress.
+    ///
+    /// When test results are written to standard output, a stray `println!` left in a solver
+    /// corrupts the submission. With this enabled, the `print!` and `println!` macros exported by
+    /// this crate, which shadow the standard ones wherever `#[macro_use] extern crate gcj_helper`
+    /// is declared, write to standard error instead, tagged with the current test case as
+    /// `gcj_debug!` does, with each call written as a separate line. Writes made directly to
+    /// `io::stdout()`, or through `std::println!`, are not redirected. This is disabled by
+    /// default.
+    pub fn isolate_stdout(mut self, enabled: bool) -> TestEngine<I, O> {
+        self.settings.isolate_stdout = enabled;
+        self
+    }
+
+    /// Sets whether debug output is captured per test case, and written to standard error in one
+    /// block once the parser or solver has finished.
+    ///
+    /// This covers everything written by `gcj_debug!`, and by `print!` and `println!` when
+    /// standard output is isolated, so that the debug output of test cases solved in parallel is
+    /// grouped by test case instead of interleaved. Captured output is still written if the
+    /// parser or solver panics. Output written with `eprintln!` or directly to `io::stderr()` is
+    /// not captured. This is disabled by default.
+    pub fn capture_debug(mut self, enabled: bool) -> TestEngine<I, O> {
+        self.settings.capture_debug = enabled;
+        self
+    }
+
+    /// Consumes the test engine, executing a parser and solver once per test case, and writing
+    /// the data for each test case to standard error (pretty-printed with `{:#?}`) after it has
+    /// been parsed and before it is solved.
+    ///
+    /// This makes parser bugs, such as reading the wrong number of lines or swapping fields, easy
+    /// to spot by inspection. Like `gcj_debug!`, each line of output is prefixed with the test
+    /// case number.
+    ///
+    /// # Panics
+    ///
+    /// This method panics in the event of an I/O error.
+    pub fn run_with_dump<
+        D: Debug + Sized + Send + Sync,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader) -> D,
+        S: Fn(&D) -> R + Sync
+    >
+        (
+        self,
+        p: P,
+        s: S,
+    ) {
+        let _ = self.execute(
+            |reader| {
+                let d = p(reader);
+                write_debug(format_args!("{:#?}", d));
+                Some(d)
+            },
+            s,
+            |_, _, _| {},
+        );
+    }
+}
+
+/// Returns the test case being parsed or solved on the current thread, or `None` if this is
+/// called outside of a parser or solver.
+pub fn current_case() -> Option<usize> {
+    CURRENT_CASE.with(|c| c.get())
+}
+
+/// Writes a line of debug output to standard error, prefixing each line with the current test
+/// case (if any).
+///
+/// The entire message is written with standard error locked, so that debug output from test cases
+/// solved in parallel never interleaves mid-line. This is normally called via `gcj_debug!`.
+///
+/// # Panics
+///
+/// This function panics if standard error cannot be written to.
+pub fn write_debug(args: Arguments) {
+    let message = args.to_string();
+    let prefix = match current_case() {
+        Some(case) => format!("Case #{}: ", case),
+        None => String::new(),
+    };
+    let mut output = String::with_capacity(0);
+    output.reserve_exact(message.len() + prefix.len() + 1);
+    for line in message.split('\n') {
+        output.push_str(&prefix);
+        output.push_str(line);
+        output.push('\n');
+    }
+    let captured = CAPTURED.with(|c| match *c.borrow_mut() {
+        Some(ref mut buffer) => {
+            buffer.push_str(&output);
+            true
+        }
+        None => false,
+    });
+    if !captured {
+        write_stderr(&output);
+    }
+}
+
+/// Writes to standard output, or to standard error as debug output if standard output is isolated
+/// (see `TestEngine::isolate_stdout()`), ending the output with a newline if `newline` is `true`.
+///
+/// This is normally called via the `print!` and `println!` macros exported by this crate.
+///
+/// # Panics
+///
+/// This function panics if standard output or standard error cannot be written to.
+pub fn write_stdout(args: Arguments, newline: bool) {
+    if STDOUT_ISOLATED.load(Ordering::SeqCst) {
+        write_debug(args);
+        return;
+    }
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let result = if newline {
+        writeln!(stdout, "{}", args)
+    } else {
+        stdout.write_fmt(args)
+    };
+    result.expect("could not write to standard output");
+}
+
+/// Writes a string to standard error at once.
+fn write_stderr(output: &str) {
+    let stderr = io::stderr();
+    let mut stderr = stderr.lock();
+    stderr
+        .write_all(output.as_bytes())
+        .expect("could not write debug output to standard error");
+}
+
+/// Calls `f` with the given test case recorded as the current test case on this thread.
+pub fn with_case<T, F: FnOnce() -> T>(case: usize, f: F) -> T {
+    let _scope = CaseScope::enter(case);
+    let _capture = Capture::begin();
+    f()
+}
+
+/// Emits a log record stating that a test case is about to be solved.
+#[cfg(feature = "log")]
+pub fn case_started(case: usize) {
+    ::log::trace!(target: "gcj_helper", "Case #{} started", case);
+}
+
+/// Emits a log record stating that a test case is about to be solved.
+#[cfg(not(feature = "log"))]
+pub fn case_started(_: usize) {}
+
+/// Emits a log record stating that a test case has been solved.
+#[cfg(feature = "log")]
+pub fn case_finished(case: usize, time: &CaseTime) {
+    ::log::debug!(
+        target: "gcj_helper",
+        "Case #{} finished (parsed in {}, solved in {})",
+        case,
+        format_duration(time.parse),
+        format_duration(time.solve)
+    );
+}
+
+/// Emits a log record stating that a test case has been solved.
+#[cfg(not(feature = "log"))]
+pub fn case_finished(_: usize, _: &CaseTime) {}
+
+/// Emits a warning log record.
+#[cfg(feature = "log")]
+pub fn warn(message: &str) {
+    ::log::warn!(target: "gcj_helper", "{}", message);
+}
+
+/// Emits a warning log record.
+#[cfg(not(feature = "log"))]
+pub fn warn(_: &str) {}
+
+impl Isolation {
+    /// Isolates standard output and captures debug output, if enabled, until the returned value
+    /// is dropped.
+    pub fn begin(isolate_stdout: bool, capture_debug: bool) -> Isolation {
+        STDOUT_ISOLATED.store(isolate_stdout, Ordering::SeqCst);
+        CAPTURE_DEBUG.store(capture_debug, Ordering::SeqCst);
+        Isolation {
+            enabled: isolate_stdout || capture_debug,
+        }
+    }
+}
+
+impl Drop for Isolation {
+    fn drop(&mut self) {
+        if self.enabled {
+            STDOUT_ISOLATED.store(false, Ordering::SeqCst);
+            CAPTURE_DEBUG.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+impl CaseScope {
+    /// Records the given test case as the current test case on this thread.
+    fn enter(case: usize) -> CaseScope {
+        CaseScope {
+            previous: CURRENT_CASE.with(|c| c.replace(Some(case))),
+        }
+    }
+}
+
+impl Drop for CaseScope {
+    fn drop(&mut self) {
+        CURRENT_CASE.with(|c| c.set(self.previous));
+    }
+}
+
+impl Capture {
+    /// Starts capturing debug output on this thread, if enabled and not already capturing.
+    fn begin() -> Capture {
+        let active = CAPTURE_DEBUG.load(Ordering::SeqCst) && CAPTURED.with(|c| {
+            let mut c = c.borrow_mut();
+            c.is_none() && {
+                *c = Some(String::new());
+                true
+            }
+        });
+        Capture { active: active }
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        if self.active {
+            if let Some(output) = CAPTURED.with(|c| c.borrow_mut().take()) {
+                write_stderr(&output);
+            }
+        }
+    }
+}
@@ -0,0 +1,227 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Macros for terse parsing and solution setup.
+
+/// Reads a sequence of typed values from an `InputReader`, binding each to a local variable.
+///
+/// Each binding is written as `name: Type`, which reads a value via `InputReader::read()`, or
+/// `name: [Type; len]`, which reads a `Vec` of `len` values via `InputReader::read_vec()`; `len`
+/// may refer to earlier bindings. Bindings may be declared `mut`. The reader expression is
+/// evaluated once per binding.
+///
+/// For example, `gcj_input!(input, n: usize, a: [i64; n], s: String)` reads an integer `n`,
+/// followed by `n` integers `a`, followed by a string `s`.
+#[macro_export]
+macro_rules! gcj_input {
+    (@munch $reader:expr;) => {};
+    (@munch $reader:expr; , $($rest:tt)*) => {
+        $crate::gcj_input!(@munch $reader; $($rest)*);
+    };
+    (@munch $reader:expr; mut $name:ident : $($rest:tt)*) => {
+        $crate::gcj_input!(@bind $reader; [mut $name] $($rest)*);
+    };
+    (@munch $reader:expr; $name:ident : $($rest:tt)*) => {
+        $crate::gcj_input!(@bind $reader; [$name] $($rest)*);
+    };
+    (@bind $reader:expr; [$($name:tt)*] [$t:ty; $len:expr] , $($rest:tt)*) => {
+        let $($name)*: Vec<$t> = $reader.read_vec(($len) as usize);
+        $crate::gcj_input!(@munch $reader; $($rest)*);
+    };
+    (@bind $reader:expr; [$($name:tt)*] $t:ty , $($rest:tt)*) => {
+        let $($name)*: $t = $reader.read();
+        $crate::gcj_input!(@munch $reader; $($rest)*);
+    };
+    ($reader:expr, $($rest:tt)*) => {
+        $crate::gcj_input!(@munch $reader; $($rest)* ,);
+    };
+}
+
+/// Executes a solution built from a parse block and a solve block, using input and output file
+/// paths obtained from command line arguments.
+///
+/// This expands to a call to `TestEngine::from_args().run()`, with the parse block reading one
+/// test case via the named `&mut InputReader`, and the solve block computing its result from a
+/// reference to its data. For example:
+///
+/// ```text
+/// fn main() {
+///     gcj_solution! {
+///         parse(input) { input.read::<u64>() }
+///         solve(n) { format!(" {}\n", n * 2) }
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! gcj_solution {
+    (parse($input:ident) $parse:block solve($data:ident) $solve:block) => {
+        $crate::TestEngine::from_args().run(
+            |$input: &mut $crate::InputReader| $parse,
+            |$data| $solve,
+        )
+    };
+}
+
+/// Embeds sample input and output files into the binary at compile time, producing a
+/// `&'static [Sample]` for use with `TestEngine::check_samples()`.
+///
+/// Paths are relative to the file containing the macro invocation, as with `include_str!`.
+/// Samples may be listed either as pairs of paths, e.g.
+/// `include_samples!("a.in" => "a.out", "b.in" => "b.out")`, or as paths without extensions,
+/// e.g. `include_samples!("a", "b")`, which embeds `a.in` and `a.out`, then `b.in` and `b.out`.
+#[macro_export]
+macro_rules! include_samples {
+    ($($input:expr => $expected:expr),+ $(,)*) => {
+        &[$(
+            $crate::Sample {
+                name: $input,
+                input: include_str!($input),
+                expected: include_str!($expected),
+            }
+        ),+]
+    };
+    ($($stem:expr),+ $(,)*) => {
+        &[$(
+            $crate::Sample {
+                name: $stem,
+                input: include_str!(concat!($stem, ".in")),
+                expected: include_str!(concat!($stem, ".out")),
+            }
+        ),+]
+    };
+}
+
+/// Generates a `main` function that solves one of several problems, selected by name.
+///
+/// Each problem is a module exposing a `parse` function, which reads one test case from a
+/// `&mut InputReader`, and a `solve` function, which computes the result for one test case. The
+/// generated `main` expects command line arguments of the form `<problem> <input> <output>`, where
+/// `<problem>` is the name of one of the listed modules, e.g. `multi_problem!(a, b, c)`.
+#[macro_export]
+macro_rules! multi_problem {
+    ($($problem:ident),+ $(,)*) => {
+        fn main() {
+            let (problem, engine) = $crate::TestEngine::from_problem_args();
+            match problem.as_str() {
+                $(stringify!($problem) => engine.run($problem::parse, $problem::solve),)+
+                _ => panic!(
+                    "unknown problem `{}`; expected one of: {}",
+                    problem,
+                    [$(stringify!($problem)),+].join(", ")
+                ),
+            }
+        }
+    };
+}
+
+/// Reads one line from an `InputReader` and parses its whitespace-separated values as a tuple of
+/// the given types.
+///
+/// For example, `let (n, k, s) = read_line!(input => u64, u64, String);` reads a line containing
+/// two integers and a string. Unlike `InputReader::read()`, which reads tokens regardless of line
+/// boundaries, this requires the line to contain exactly as many values as there are types.
+///
+/// # Panics
+///
+/// The expansion panics with a message including the offending line if it contains too few or too
+/// many values, or if a value cannot be parsed as the corresponding type.
+#[macro_export]
+macro_rules! read_line {
+    ($reader:expr => $($t:ty),+ $(,)*) => {{
+        let line = $reader.read_next_line();
+        let expected = stringify!($($t),+);
+        let mut tokens = line.split_whitespace();
+        let mut index = 0usize;
+        let values = ($(
+            {
+                index += 1;
+                let token = tokens.next().unwrap_or_else(|| {
+                    panic!("line {:?} contains too few values; expected ({})", line, expected)
+                });
+                token.parse::<$t>().unwrap_or_else(|_| {
+                    panic!(
+                        "could not parse value {} of line {:?} ({:?}) as `{}`",
+                        index,
+                        line,
+                        token,
+                        stringify!($t)
+                    )
+                })
+            },
+        )+);
+        if tokens.next().is_some() {
+            panic!("line {:?} contains too many values; expected ({})", line, expected);
+        }
+        values
+    }};
+}
+
+/// Writes debug output to standard error, prefixed with the current test case.
+///
+/// This takes the same arguments as `eprintln!`, e.g. `gcj_debug!("dp = {:?}", dp)`. Each line of
+/// output is prefixed with `"Case #N: "` while a parser or solver is running, and the whole
+/// message is written at once, so debug output from test cases solved in parallel never
+/// interleaves mid-line.
+#[macro_export]
+macro_rules! gcj_debug {
+    ($($arg:tt)*) => {
+        $crate::write_debug(format_args!($($arg)*))
+    };
+}
+
+/// Writes to standard output, like the standard `print!`, unless standard output is reserved for
+/// test results by `TestEngine::isolate_stdout()`, in which case this writes to standard error as
+/// `gcj_debug!` does.
+///
+/// Declaring `#[macro_use] extern crate gcj_helper` shadows the standard macro with this one.
+#[macro_export]
+macro_rules! print {
+    ($($arg:tt)*) => {
+        $crate::write_stdout(format_args!($($arg)*), false)
+    };
+}
+
+/// Writes a line to standard output, like the standard `println!`, unless standard output is
+/// reserved for test results by `TestEngine::isolate_stdout()`, in which case this writes to
+/// standard error as `gcj_debug!` does.
+///
+/// Declaring `#[macro_use] extern crate gcj_helper` shadows the standard macro with this one.
+#[macro_export]
+macro_rules! println {
+    () => {
+        $crate::write_stdout(format_args!(""), true)
+    };
+    ($($arg:tt)*) => {
+        $crate::write_stdout(format_args!($($arg)*), true)
+    };
+}
+
+/// Writes formatted text to a `CaseWriter`, preceded by a space if it is the first text written
+/// after the `"Case #N:"` header.
+///
+/// This takes the same arguments as `write!`, e.g. `write_case!(w, "{} {}", a, b)`.
+#[macro_export]
+macro_rules! write_case {
+    ($writer:expr, $($arg:tt)*) => {
+        $writer.write_answer(format_args!($($arg)*))
+    };
+}
+
+/// Writes formatted text and a newline to a `CaseWriter`, preceded by a space if it is the first
+/// text written after the `"Case #N:"` header.
+///
+/// This takes the same arguments as `writeln!`; `writeln_case!(w)` ends the header line, as
+/// multi-line test results require.
+#[macro_export]
+macro_rules! writeln_case {
+    ($writer:expr) => {
+        $writer.writeln_answer(format_args!(""))
+    };
+    ($writer:expr, $($arg:tt)*) => {
+        $writer.writeln_answer(format_args!($($arg)*))
+    };
+}
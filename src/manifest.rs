@@ -0,0 +1,298 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Per-problem configuration files (`gcj.toml`).
+
+#[cfg(feature = "parallel")]
+use super::paths;
+use super::{CaseMode, TestEngine};
+use std::fs;
+#[cfg(feature = "parallel")]
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The conventional file name for a problem manifest.
+pub const MANIFEST_FILE_NAME: &str = "gcj.toml";
+
+/// A problem manifest, describing how a solution is run.
+///
+/// Manifests are usually stored in a `gcj.toml` file alongside the solution, and are written in a
+/// subset of TOML consisting of top-level `key = value` pairs, where values are strings, integers,
+/// booleans or single-line arrays of strings:
+///
+/// ```text
+/// input = "inputs/A-large.in"   # required by TestEngine::from_manifest()
+/// output = "inputs/A-large.out" # defaults to the input path with an .out extension
+/// format = "gcj"                # "gcj", "kattis" or "single"
+/// case_headers = true           # overrides the format's default
+/// time_limit = 20               # in seconds
+/// samples = ["samples/sample"]  # sample file paths, without the .in/.out extension
+/// threads = 4                   # the number of threads used in parallel mode
+/// ```
+///
+/// The `threads` key may also be written by auto-tuning; see `AutoTune::remember()`.
+///
+/// When a manifest is loaded from a file, relative paths are resolved relative to the directory
+/// containing the manifest.
+#[derive(Clone, Debug)]
+pub struct Manifest {
+    /// The path to the input file.
+    pub input: Option<PathBuf>,
+    /// The path to the output file.
+    pub output: Option<PathBuf>,
+    /// How the number of test cases is determined.
+    pub case_mode: CaseMode,
+    /// Whether each test result is preceded by a `"Case #N:"` header.
+    pub case_headers: bool,
+    /// The time limit for solving the input file.
+    pub time_limit: Option<Duration>,
+    /// Paths to sample files, without the `.in` or `.out` extension.
+    pub samples: Vec<PathBuf>,
+    /// The number of threads used to solve test cases in parallel mode.
+    pub threads: Option<usize>,
+}
+
+impl Manifest {
+    /// Loads a manifest from a file, resolving relative paths against the directory containing
+    /// it.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the file cannot be read, or if it is not a valid manifest.
+    pub fn load<P: AsRef<Path>>(path: P) -> Manifest {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).expect("could not read manifest file");
+        let mut manifest = match source.parse::<Manifest>() {
+            Ok(manifest) => manifest,
+            Err(e) => panic!("could not parse `{}`: {}", path.display(), e),
+        };
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        manifest.input = manifest.input.map(|p| dir.join(p));
+        manifest.output = manifest.output.map(|p| dir.join(p));
+        manifest.samples = manifest.samples.iter().map(|p| dir.join(p)).collect();
+        manifest
+    }
+}
+
+impl Default for Manifest {
+    fn default() -> Manifest {
+        Manifest {
+            input: None,
+            output: None,
+            case_mode: CaseMode::default(),
+            case_headers: true,
+            time_limit: None,
+            samples: Vec::new(),
+            threads: None,
+        }
+    }
+}
+
+impl FromStr for Manifest {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Manifest, String> {
+        let mut manifest = Manifest::default();
+        let mut case_headers = None;
+        for (i, line) in source.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let at_line = |e: String| format!("line {}: {}", i + 1, e);
+            let (key, value) = match line.find('=') {
+                Some(eq) => (line[..eq].trim(), line[eq + 1..].trim()),
+                None => return Err(at_line("expected `key = value`".to_owned())),
+            };
+            match key {
+                "input" => manifest.input = Some(parse_string(value).map_err(at_line)?.into()),
+                "output" => manifest.output = Some(parse_string(value).map_err(at_line)?.into()),
+                "format" => {
+                    let (mode, headers) = match parse_string(value).map_err(at_line)?.as_str() {
+                        "gcj" => (CaseMode::Counted, true),
+                        "kattis" => (CaseMode::UntilEof, false),
+                        "single" => (CaseMode::Single, false),
+                        other => return Err(at_line(format!("unknown format `{}`", other))),
+                    };
+                    manifest.case_mode = mode;
+                    manifest.case_headers = headers;
+                }
+                "case_headers" => case_headers = Some(parse_bool(value).map_err(at_line)?),
+                "time_limit" => {
+                    let secs = parse_int(value).map_err(at_line)?;
+                    manifest.time_limit = Some(Duration::from_secs(secs));
+                }
+                "samples" => {
+                    manifest.samples = parse_string_array(value)
+                        .map_err(at_line)?
+                        .into_iter()
+                        .map(PathBuf::from)
+                        .collect()
+                }
+                "threads" => manifest.threads = Some(parse_int(value).map_err(at_line)? as usize),
+                _ => return Err(at_line(format!("unknown key `{}`", key))),
+            }
+        }
+        if let Some(enabled) = case_headers {
+            manifest.case_headers = enabled;
+        }
+        Ok(manifest)
+    }
+}
+
+impl TestEngine<PathBuf, PathBuf> {
+    /// Creates a new test engine configured by a problem manifest, such as `gcj.toml`.
+    ///
+    /// The input and output paths, case mode, case headers, time limit and thread count are taken
+    /// from the manifest. See `Manifest` for the file format.
+    ///
+    /// Calling this method is cheap; no files other than the manifest are opened until
+    /// `TestEngine::run()` is called.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the manifest cannot be loaded, or if it does not specify an input
+    /// file.
+    pub fn from_manifest<P: AsRef<Path>>(path: P) -> TestEngine<PathBuf, PathBuf> {
+        let manifest = Manifest::load(path);
+        let input = manifest.input.expect("manifest does not specify an input file");
+        let output = manifest
+            .output
+            .unwrap_or_else(|| input.with_extension("out"));
+        let mut engine = Self::new(input, output)
+            .case_mode(manifest.case_mode)
+            .case_headers(manifest.case_headers);
+        if let Some(threads) = manifest.threads {
+            engine = engine.threads(threads);
+        }
+        if let Some(limit) = manifest.time_limit {
+            engine = engine.time_limit(limit);
+        }
+        engine
+    }
+}
+
+/// Sets the `threads` key of a manifest file, replacing its existing value if any and keeping the
+/// rest of the file intact, creating the file if it does not exist.
+#[cfg(feature = "parallel")]
+pub fn remember_threads(path: &Path, threads: usize) -> io::Result<()> {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let entry = format!("threads = {}", threads);
+    let mut replaced = false;
+    let mut updated = String::with_capacity(0);
+    updated.reserve_exact(source.len() + entry.len() + 1);
+    for line in source.lines() {
+        let key = strip_comment(line).split('=').next().unwrap_or("").trim();
+        if key == "threads" && !replaced {
+            updated.push_str(&entry);
+            replaced = true;
+        } else {
+            updated.push_str(line);
+        }
+        updated.push('\n');
+    }
+    if !replaced {
+        updated.push_str(&entry);
+        updated.push('\n');
+    }
+    paths::write_file(path, updated)
+}
+
+/// Removes a trailing `#` comment from a line, ignoring `#` characters inside strings.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Parses a double-quoted string, returning the unparsed remainder.
+fn parse_string_prefix(value: &str) -> Result<(String, &str), String> {
+    if !value.starts_with('"') {
+        return Err(format!("expected a string, found `{}`", value));
+    }
+    let mut s = String::new();
+    let mut chars = value.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((s, &value[i + 1..])),
+            '\\' => match chars.next() {
+                Some((_, 'n')) => s.push('\n'),
+                Some((_, 't')) => s.push('\t'),
+                Some((_, c @ '"')) | Some((_, c @ '\\')) => s.push(c),
+                _ => return Err("invalid escape sequence in string".to_owned()),
+            },
+            c => s.push(c),
+        }
+    }
+    Err("unterminated string".to_owned())
+}
+
+/// Parses a value consisting of exactly one double-quoted string.
+fn parse_string(value: &str) -> Result<String, String> {
+    match parse_string_prefix(value)? {
+        (s, "") => Ok(s),
+        (_, rest) => Err(format!("unexpected `{}` after string", rest.trim())),
+    }
+}
+
+/// Parses a single-line array of double-quoted strings.
+fn parse_string_array(value: &str) -> Result<Vec<String>, String> {
+    let mut rest = match value.strip_prefix('[') {
+        Some(rest) => rest.trim_start(),
+        None => return Err(format!("expected an array, found `{}`", value)),
+    };
+    let mut strings = Vec::new();
+    loop {
+        if let Some(after) = rest.strip_prefix(']') {
+            return if after.trim().is_empty() {
+                Ok(strings)
+            } else {
+                Err(format!("unexpected `{}` after array", after.trim()))
+            };
+        }
+        let (s, after) = parse_string_prefix(rest)?;
+        strings.push(s);
+        let after = after.trim_start();
+        rest = match after.strip_prefix(',') {
+            Some(after) => after.trim_start(),
+            None if after.starts_with(']') => after,
+            None => return Err("expected `,` or `]` in array".to_owned()),
+        };
+    }
+}
+
+/// Parses a non-negative integer.
+fn parse_int(value: &str) -> Result<u64, String> {
+    value
+        .replace('_', "")
+        .parse()
+        .map_err(|_| format!("expected a non-negative integer, found `{}`", value))
+}
+
+/// Parses a boolean.
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("expected `true` or `false`, found `{}`", value)),
+    }
+}
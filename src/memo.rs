@@ -0,0 +1,102 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Memoization shared across test cases.
+
+use super::{Input, InputReader, Output, TestEngine};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+/// A memoization cache shared by every test case in a run, safe to use from test cases solved in
+/// parallel.
+///
+/// The cache is never locked while a value is being computed, so values may be computed from
+/// other cached values (e.g. recursively). As a consequence, two test cases solved at the same
+/// time may both compute the same missing value; the first value stored is kept.
+#[derive(Debug, Default)]
+pub struct Memo<K: Eq + Hash, V: Clone> {
+    /// The cached values.
+    values: Mutex<HashMap<K, V>>,
+}
+
+impl<K: Eq + Hash, V: Clone> Memo<K, V> {
+    /// Creates a new, empty memoization cache.
+    pub fn new() -> Memo<K, V> {
+        Memo {
+            values: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the value cached for `key`, if any.
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.values.lock().expect("could not lock memoization cache").get(key).cloned()
+    }
+
+    /// Caches `value` for `key`, unless a value is already cached for `key`.
+    pub fn insert(&self, key: K, value: V) {
+        let _ = self.values
+            .lock()
+            .expect("could not lock memoization cache")
+            .entry(key)
+            .or_insert(value);
+    }
+
+    /// Returns the value cached for `key`, computing and caching it with `f` if it is missing.
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&self, key: K, f: F) -> V {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+        let value = f();
+        self.values
+            .lock()
+            .expect("could not lock memoization cache")
+            .entry(key)
+            .or_insert(value)
+            .clone()
+    }
+
+    /// Returns the number of values cached.
+    pub fn len(&self) -> usize {
+        self.values.lock().expect("could not lock memoization cache").len()
+    }
+
+    /// Returns `true` if no values are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Consumes the test engine, executing a parser and solver once per test case, with a
+    /// memoization cache shared by every test case.
+    ///
+    /// This suits problems where many test cases share subproblems (e.g. the same value of N
+    /// appears in several test cases), so that each subproblem is solved once per run rather than
+    /// once per test case. The solver receives the cache alongside the data for each test case.
+    ///
+    /// # Panics
+    ///
+    /// This method panics in the event of an I/O error.
+    pub fn run_memoized<
+        D: Sized + Send + Sync,
+        R: Display + Sized + Send,
+        K: Eq + Hash + Send,
+        V: Clone + Send,
+        P: Fn(&mut InputReader) -> D,
+        S: Fn(&D, &Memo<K, V>) -> R + Sync
+    >
+        (
+        self,
+        p: P,
+        s: S,
+    ) {
+        let memo = Memo::new();
+        let _ = self.execute(|reader| Some(p(reader)), |d| s(d, &memo), |_, _, _| {});
+    }
+}
@@ -0,0 +1,73 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Test results that may be absent.
+
+use super::{Input, InputReader, Output, TestEngine};
+use std::fmt::{self, Display, Formatter};
+
+/// The text written in place of a missing test result by default.
+pub const DEFAULT_NONE_ANSWER: &str = " IMPOSSIBLE\n";
+
+/// A test result that is either a value, or the sentinel text written in its place.
+struct OptionalAnswer<'a, R: Display> {
+    /// The test result, if the solver produced one.
+    result: Option<R>,
+    /// The text written instead of a missing test result.
+    sentinel: &'a str,
+}
+
+impl<'a, R: Display> Display for OptionalAnswer<'a, R> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.result {
+            Some(ref result) => result.fmt(f),
+            None => f.write_str(self.sentinel),
+        }
+    }
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Sets the text written in place of a test result when a solver passed to
+    /// `TestEngine::run_optional()` returns `None`.
+    ///
+    /// As with any other test result, the text is written immediately after the `"Case #N:"`
+    /// header, so it should usually begin with a space and end with a newline. The default is
+    /// `" IMPOSSIBLE\n"`.
+    pub fn none_answer<S: Into<String>>(mut self, text: S) -> TestEngine<I, O> {
+        self.settings.none_answer = text.into();
+        self
+    }
+
+    /// Consumes the test engine, executing a parser and solver once per test case, where the
+    /// solver may find that a test case has no solution.
+    ///
+    /// Whenever the solver returns `None`, the text set with `TestEngine::none_answer()` is
+    /// written as the test result, so the common "print the answer or `IMPOSSIBLE`" pattern needs
+    /// no special handling in the solver.
+    ///
+    /// # Panics
+    ///
+    /// This method panics in the event of an I/O error.
+    pub fn run_optional<
+        D: Sized + Send + Sync,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader) -> D,
+        S: Fn(&D) -> Option<R> + Sync
+    >
+        (
+        self,
+        p: P,
+        s: S,
+    ) {
+        let sentinel = self.settings.none_answer.clone();
+        let solve = |d: &D| OptionalAnswer {
+            result: s(d),
+            sentinel: &sentinel,
+        };
+        let _ = self.execute(|reader| Some(p(reader)), solve, |_, _, _| {});
+    }
+}
@@ -0,0 +1,62 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Aborting runs whose test results are implausibly large.
+
+use super::{Input, Output, TestEngine};
+
+/// The maximum plausible size of one test result, set with `TestEngine::max_case_output()`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OutputLimit {
+    /// The maximum number of bytes in a test result, excluding its `"Case #N:"` header.
+    Bytes(usize),
+    /// The maximum number of lines in a test result, counting the line holding its `"Case #N:"`
+    /// header.
+    Lines(usize),
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Sets the maximum plausible size of one test result, aborting the run if any test result
+    /// exceeds it.
+    ///
+    /// Each test result is checked before it is written to the output file, so a solver stuck
+    /// printing in a loop, or a pathologically large answer, is caught before it fills the disk,
+    /// and the error names the offending test case. By default, test results may be of any size.
+    ///
+    /// # Panics
+    ///
+    /// The run panics when a test result exceeds the limit.
+    pub fn max_case_output(mut self, limit: OutputLimit) -> TestEngine<I, O> {
+        self.settings.max_case_output = Some(limit);
+        self
+    }
+}
+
+/// Checks the size of the test result of the given test case against a limit.
+///
+/// # Panics
+///
+/// This function panics if the test result exceeds the limit.
+pub fn check(limit: OutputLimit, case: usize, result: &str) {
+    let (size, max, unit) = match limit {
+        OutputLimit::Bytes(max) => (result.len(), max, "bytes"),
+        OutputLimit::Lines(max) => {
+            let lines = result.matches('\n').count() + usize::from(!result.ends_with('\n'));
+            (lines, max, "lines")
+        }
+    };
+    if size > max {
+        panic!(
+            "Case #{} has a test result of {} {}, exceeding the output limit of {} {}",
+            case,
+            size,
+            unit,
+            max,
+            unit
+        );
+    }
+}
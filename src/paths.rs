@@ -0,0 +1,83 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Resolving the paths of files read and written by the test engine.
+//!
+//! Paths given to the test engine may begin with `~`, which is expanded to the home directory as
+//! a shell would, since paths read from a manifest or passed in quotes are not expanded by the
+//! shell. Missing parent directories of files written by the test engine are created, and such
+//! files are checked for writability before the run starts, rather than failing once every test
+//! case has been solved.
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Expands a leading `~` path component to the home directory, if it can be determined.
+pub fn expand_home(path: &Path) -> PathBuf {
+    let rest = match path.strip_prefix("~") {
+        Ok(rest) => rest,
+        Err(_) => return path.to_owned(),
+    };
+    match env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) {
+        Some(home) => Path::new(&home).join(rest),
+        None => path.to_owned(),
+    }
+}
+
+/// Opens an output file for writing, truncating it, after expanding a leading `~` and creating
+/// any missing parent directories.
+pub fn create_output(path: &Path) -> io::Result<File> {
+    let path = expand_home(path);
+    create_parent(&path)?;
+    OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .create(true)
+        .open(&path)
+        .map_err(|e| annotate(&path, e))
+}
+
+/// Prepares to write a file once a run has finished: expands a leading `~`, creates any missing
+/// parent directories, and checks that the file can be written without modifying it, returning
+/// the expanded path.
+pub fn prepare_output(path: &Path) -> io::Result<PathBuf> {
+    let path = expand_home(path);
+    create_parent(&path)?;
+    let existed = path.exists();
+    let _ = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(&path)
+        .map_err(|e| annotate(&path, e))?;
+    if !existed {
+        fs::remove_file(&path).map_err(|e| annotate(&path, e))?;
+    }
+    Ok(path)
+}
+
+/// Writes a file, creating any missing parent directories.
+pub fn write_file<C: AsRef<[u8]>>(path: &Path, contents: C) -> io::Result<()> {
+    create_parent(path)?;
+    fs::write(path, contents).map_err(|e| annotate(path, e))
+}
+
+/// Creates any missing parent directories of a path.
+fn create_parent(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() && !parent.is_dir() => {
+            fs::create_dir_all(parent).map_err(|e| annotate(parent, e))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Adds a path to an I/O error, as the errors returned by the operating system omit it.
+fn annotate(path: &Path, e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("`{}`: {}", path.display(), e))
+}
@@ -0,0 +1,80 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Problems described as a parser and solver pair.
+
+use super::{read_input, Input, InputReader, Output, TestEngine};
+use std::fmt::Display;
+
+/// A problem, described by a parser and a solver.
+///
+/// This is an alternative to passing closures to `TestEngine::run()`, which can be useful when a
+/// solution is split across several modules, or used with `#[gcj_helper::main]`.
+pub trait Problem {
+    /// The data for one test case.
+    type Data: Sized + Send + Sync;
+    /// The result for one test case.
+    type Result: Display + Sized + Send;
+
+    /// Reads the data for one test case from the input file.
+    fn parse(input: &mut InputReader) -> Self::Data;
+
+    /// Solves one test case.
+    fn solve(data: &Self::Data) -> Self::Result;
+}
+
+/// A problem whose test case data borrows from the input file, described by a parser and a
+/// solver.
+///
+/// Where `Problem::Data` must own its contents, so that every string read from the input file is
+/// copied into it, `BorrowedProblem::Data` may hold the `&'a str` lines and tokens returned by an
+/// `InputReader<'a>` directly. The text of the input file is owned by the test engine, which
+/// keeps it alive until every test case has been solved and written. This avoids allocating for
+/// each string field, which matters for string-heavy input files.
+pub trait BorrowedProblem {
+    /// The data for one test case, which may borrow from the input file for the lifetime `'a`.
+    type Data<'a>: Sized + Send + Sync;
+    /// The result for one test case.
+    type Result: Display + Sized + Send;
+
+    /// Reads the data for one test case from the input file.
+    fn parse<'a>(input: &mut InputReader<'a>) -> Self::Data<'a>;
+
+    /// Solves one test case.
+    fn solve(data: &Self::Data<'_>) -> Self::Result;
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Consumes the test engine, executing the parser and solver of a `Problem` once per test
+    /// case.
+    ///
+    /// # Panics
+    ///
+    /// This method panics in the event of an I/O error.
+    pub fn run_problem<T: Problem>(self) {
+        self.run(T::parse, T::solve)
+    }
+
+    /// Consumes the test engine, executing the parser and solver of a `BorrowedProblem` once per
+    /// test case, with test case data borrowing from the input file.
+    ///
+    /// # Panics
+    ///
+    /// This method panics in the event of an I/O error.
+    pub fn run_borrowed<T: BorrowedProblem>(self) {
+        let text = read_input(self.input, self.settings.input_encoding);
+        let _ = Self::execute_text(
+            self.output,
+            self.settings,
+            &text,
+            |reader| Some(T::parse(reader)),
+            T::solve,
+            |_, _, _| {},
+            || None,
+        );
+    }
+}
@@ -0,0 +1,52 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Hooks for driving external profilers.
+
+use super::{Input, Output, TestEngine};
+
+/// Functions called immediately before and after each test case is solved.
+#[derive(Clone, Copy, Debug)]
+pub struct ProfileHooks {
+    /// Called with the test case number before the solver runs.
+    start: fn(usize),
+    /// Called with the test case number after the solver returns.
+    end: fn(usize),
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Sets functions to be called immediately before and after each test case is solved, such as
+    /// functions emitting markers for an external profiler, or pausing and resuming a sampling
+    /// profiler.
+    ///
+    /// Each function is called with the test case number, which is stable between runs of the same
+    /// input file regardless of how test cases are scheduled. Both functions are called on the
+    /// thread solving the test case, which in parallel mode may differ between test cases; the
+    /// time taken by the parser is not included.
+    pub fn profile_hooks(mut self, start: fn(usize), end: fn(usize)) -> TestEngine<I, O> {
+        self.settings.profile_hooks = Some(ProfileHooks {
+            start: start,
+            end: end,
+        });
+        self
+    }
+}
+
+impl ProfileHooks {
+    /// Calls `f` between the start and end hooks for a test case, if any hooks are set.
+    pub fn wrap<T, F: FnOnce() -> T>(hooks: Option<ProfileHooks>, case: usize, f: F) -> T {
+        match hooks {
+            Some(hooks) => {
+                (hooks.start)(case);
+                let result = f();
+                (hooks.end)(case);
+                result
+            }
+            None => f(),
+        }
+    }
+}
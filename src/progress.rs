@@ -0,0 +1,98 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Progress reporting for long-running solutions.
+
+use super::{clock, format_duration, Clock};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// The minimum interval between progress reports.
+pub const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Tracks how many test cases have been solved, periodically reporting progress to standard
+/// error.
+///
+/// Each report is written as a complete line with a single call to `eprintln!()`, so reports never
+/// interleave mid-line with debug output written by solvers, even when test cases are solved in
+/// parallel.
+#[derive(Debug)]
+pub struct Progress {
+    /// The total number of test cases, if known.
+    total: Option<usize>,
+    /// The number of test cases solved so far.
+    done: usize,
+    /// When solving began.
+    start: Duration,
+    /// When progress was last reported.
+    last_report: Duration,
+    /// The clock progress is measured by.
+    clock: Arc<dyn Clock>,
+    /// When the contest or submission window ends, if known.
+    contest_end: Option<SystemTime>,
+}
+
+impl Progress {
+    /// Starts tracking progress towards solving `total` test cases, if known, as measured by
+    /// `clock`.
+    pub fn new(
+        total: Option<usize>,
+        contest_end: Option<SystemTime>,
+        clock: &Arc<dyn Clock>,
+    ) -> Progress {
+        let now = clock.monotonic();
+        Progress {
+            total: total,
+            done: 0,
+            start: now,
+            last_report: now,
+            clock: Arc::clone(clock),
+            contest_end: contest_end,
+        }
+    }
+
+    /// Records that a test case has been solved, reporting progress if enough time has passed
+    /// since the last report, or if this was the last test case.
+    pub fn case_done(&mut self) {
+        self.done += 1;
+        let now = self.clock.monotonic();
+        if now.saturating_sub(self.last_report) >= REPORT_INTERVAL
+            || Some(self.done) == self.total
+        {
+            self.last_report = now;
+            self.report(now.saturating_sub(self.start));
+        }
+    }
+
+    /// Writes a progress report to standard error.
+    fn report(&self, elapsed: Duration) {
+        let mut line = match self.total {
+            Some(total) => {
+                let remaining = (total - self.done) as u32;
+                let eta = elapsed / self.done as u32 * remaining;
+                format!(
+                    "progress: {}/{} cases ({}%), {} elapsed, ETA {}",
+                    self.done,
+                    total,
+                    self.done * 100 / total,
+                    format_duration(elapsed),
+                    format_duration(eta),
+                )
+            }
+            None => format!(
+                "progress: {} cases, {} elapsed",
+                self.done,
+                format_duration(elapsed)
+            ),
+        };
+        if let Some(end) = self.contest_end {
+            line.push_str(", contest clock: ");
+            line.push_str(&clock::time_remaining(end, self.clock.system_time()));
+        }
+        eprintln!("{}", line);
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Comparison of per-case timings against a previous run.
+
+use super::{format_duration, CaseTime, Input, Output, TestEngine};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The factor by which a solve time must change to be reported.
+const SIGNIFICANT_RATIO: f64 = 1.25;
+
+/// The amount by which a solve time must change to be reported, in seconds, so that noise in
+/// very fast test cases is ignored.
+const SIGNIFICANT_DIFFERENCE: f64 = 0.001;
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Sets a path to store per-case timings at, for comparison with the next run.
+    ///
+    /// If the file exists when the run finishes, the solve time of each test case is compared
+    /// with the time recorded in the file, and any test case that became significantly slower or
+    /// faster (by at least 25%, and at least a millisecond) is listed on standard error. Test
+    /// cases that became slower are also raised as warnings. The file is then overwritten with the
+    /// timings of the current run, in the same CSV format as `TestEngine::timings_csv()`.
+    pub fn timing_baseline<P: Into<PathBuf>>(mut self, path: P) -> TestEngine<I, O> {
+        self.settings.timing_baseline = Some(path.into());
+        self
+    }
+}
+
+/// Compares the solve time of each test case with the timings stored at `path` by a previous run,
+/// writing any significant changes to standard error and returning warnings for any test cases
+/// that became slower.
+///
+/// Nothing is compared if `path` does not exist or cannot be parsed.
+pub fn compare(path: &Path, cases: &[CaseTime]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let previous = match fs::read_to_string(path).ok().and_then(|s| parse_solve_times(&s)) {
+        Some(previous) => previous,
+        None => return warnings,
+    };
+    eprintln!("timing comparison with previous run:");
+    if previous.len() != cases.len() {
+        eprintln!(
+            "  previous run solved {} test cases, this run solved {}",
+            previous.len(),
+            cases.len()
+        );
+    }
+    let mut changed = false;
+    for (i, (old, time)) in previous.iter().zip(cases).enumerate() {
+        let old = old.as_secs_f64();
+        let new = time.solve.as_secs_f64();
+        if (new - old).abs() < SIGNIFICANT_DIFFERENCE {
+            continue;
+        }
+        let description = if new >= old * SIGNIFICANT_RATIO {
+            "slower"
+        } else if old >= new * SIGNIFICANT_RATIO {
+            "faster"
+        } else {
+            continue;
+        };
+        changed = true;
+        let message = format!(
+            "Case #{} solved {}: {} -> {}",
+            i + 1,
+            description,
+            format_duration(Duration::from_secs_f64(old)),
+            format_duration(time.solve)
+        );
+        eprintln!("  {}", message);
+        if description == "slower" {
+            warnings.push(message);
+        }
+    }
+    if !changed {
+        eprintln!("  no significant changes");
+    }
+    warnings
+}
+
+/// Parses the solve times from a CSV file written by `TestEngine::timings_csv()`.
+fn parse_solve_times(csv: &str) -> Option<Vec<Duration>> {
+    let mut lines = csv.lines();
+    let column = lines.next()?.split(',').position(|c| c == "solve_time")?;
+    lines
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.split(',')
+                .nth(column)
+                .and_then(|t| t.parse().ok())
+                .filter(|&t: &f64| t.is_finite() && t >= 0.0)
+                .map(Duration::from_secs_f64)
+        })
+        .collect()
+}
@@ -0,0 +1,372 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Reporting on completed runs.
+
+use super::{annotate, clock, format_duration, history, paths, regression, CaseIndex, CaseTime,
+            Clock, RunRecord, Settings};
+use std::cmp::Reverse;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+/// The number of slowest test cases listed in a summary.
+const SLOWEST_CASE_COUNT: usize = 5;
+
+/// Warnings raised during the current run by code outside of the test engine itself, such as the
+/// wrappers around parsers and solvers used by some run methods.
+static DEFERRED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Statistics gathered over one run of a test engine.
+#[derive(Debug)]
+pub struct RunReport {
+    /// When the run began.
+    start: Duration,
+    /// The clock the run is measured by.
+    clock: Arc<dyn Clock>,
+    /// The time taken by each test case, in order.
+    cases: Vec<CaseTime>,
+    /// The byte range of the input file consumed by each test case, in order.
+    spans: Vec<Range<usize>>,
+    /// The number of bytes written to the output file.
+    output_bytes: usize,
+    /// The 64-bit FNV-1a hash of the output file.
+    output_hash: u64,
+    /// Whether the output file was synced to durable storage.
+    output_synced: bool,
+    /// The error raised while flushing or syncing the output file, if any.
+    output_error: Option<String>,
+    /// Warnings raised during the run.
+    warnings: Vec<String>,
+    /// Where to write a JSON report, if anywhere.
+    json_report: Option<PathBuf>,
+    /// Where to write a CSV file of per-case timings, if anywhere.
+    timings_csv: Option<PathBuf>,
+    /// Where to store per-case timings for comparison with the next run, if anywhere.
+    timing_baseline: Option<PathBuf>,
+    /// Where to write an index of the input data consumed by each test case, if anywhere.
+    case_index: Option<PathBuf>,
+    /// Where to write the annotations attached to test cases, if anywhere.
+    annotations_file: Option<PathBuf>,
+    /// Where to append a record of the run, if anywhere.
+    run_history: Option<PathBuf>,
+    /// The number of threads used in parallel mode, if not the default.
+    threads: Option<usize>,
+    /// The seed used for per-case random number generators.
+    seed: u64,
+    /// The test engine configuration, as a JSON object.
+    config: String,
+    /// Whether the run finished normally.
+    finished: bool,
+}
+
+impl RunReport {
+    /// Starts gathering statistics for a new run of a test engine with the given settings.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if any of the files to be written when the run finishes cannot be
+    /// written.
+    pub fn new(settings: &Settings) -> RunReport {
+        annotate::clear();
+        let threads = match settings.threads {
+            Some(n) => n.to_string(),
+            None => "null".to_owned(),
+        };
+        RunReport {
+            start: settings.clock.monotonic(),
+            clock: Arc::clone(&settings.clock),
+            cases: Vec::new(),
+            spans: Vec::new(),
+            output_bytes: 0,
+            output_hash: history::FNV_OFFSET_BASIS,
+            output_synced: false,
+            output_error: None,
+            warnings: Vec::new(),
+            json_report: prepare(&settings.json_report, "JSON report"),
+            timings_csv: prepare(&settings.timings_csv, "timings CSV file"),
+            timing_baseline: prepare(&settings.timing_baseline, "timing baseline file"),
+            case_index: prepare(&settings.case_index, "case index file"),
+            annotations_file: prepare(&settings.annotations_file, "annotations file"),
+            run_history: prepare(&settings.run_history, "run history"),
+            threads: settings.threads,
+            seed: settings.seed.unwrap_or(0),
+            config: format!(
+                concat!(
+                    "{{\"case_mode\": \"{:?}\", \"case_headers\": {}, ",
+                    "\"parallel\": {}, \"threads\": {}, \"seed\": {}}}"
+                ),
+                settings.case_mode,
+                settings.case_headers,
+                cfg!(feature = "parallel"),
+                threads,
+                settings.seed.unwrap_or(0)
+            ),
+            finished: false,
+        }
+    }
+
+    /// Records the time taken by the next test case, and the byte range of its input data.
+    pub fn add_case(&mut self, time: CaseTime, span: Range<usize>) {
+        self.cases.push(time);
+        self.spans.push(span);
+    }
+
+    /// Records the outcome of flushing the output file at the end of the run, and of syncing it
+    /// to durable storage if requested.
+    pub fn output_flushed(&mut self, result: &io::Result<bool>) {
+        match *result {
+            Ok(synced) => self.output_synced = synced,
+            Err(ref e) => {
+                let warning = format!("could not flush output file: {}", e);
+                self.warnings.push(warning);
+                self.output_error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Records warnings raised during the run.
+    pub fn add_warnings(&mut self, warnings: Vec<String>) {
+        self.warnings.extend(warnings);
+    }
+
+    /// Returns the wall time elapsed since the run began.
+    fn wall_time(&self) -> Duration {
+        self.clock.monotonic().saturating_sub(self.start)
+    }
+
+    /// Finishes the run, given the size and hash of the output file, writing any reports
+    /// requested by the test engine's settings to standard error.
+    pub fn finish(mut self, settings: &Settings, output_bytes: usize, output_hash: u64) {
+        let wall_time = self.wall_time();
+        self.warnings.extend(lock_deferred().drain(..));
+        self.output_bytes = output_bytes;
+        self.output_hash = output_hash;
+        if let Some(end) = settings.contest_end {
+            if let Some(warning) = clock::report(end, self.clock.system_time()) {
+                self.warnings.push(warning);
+            }
+        }
+        if let Some(ref path) = self.timing_baseline {
+            let warnings = regression::compare(path, &self.cases);
+            self.warnings.extend(warnings);
+        }
+        if settings.summary {
+            self.write_summary(wall_time);
+        }
+        self.write_csv();
+        if let Some(ref path) = self.case_index {
+            CaseIndex::new(self.spans.clone())
+                .save(path)
+                .expect("could not write case index file");
+        }
+        if let Some(ref path) = self.annotations_file {
+            annotate::save(path).expect("could not write annotations file");
+        }
+        self.finished = true;
+        let status = if self.output_error.is_some() { "output_error" } else { "ok" };
+        self.write_json(status);
+        self.record_history(status);
+    }
+
+    /// Writes the timings of each test case to the configured CSV file and timing baseline, if
+    /// any.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if either file cannot be written.
+    fn write_csv(&self) {
+        if self.timings_csv.is_none() && self.timing_baseline.is_none() {
+            return;
+        }
+        let mut csv = String::from("case,input_bytes,parse_time,solve_time,write_time\n");
+        for (i, (time, span)) in self.cases.iter().zip(&self.spans).enumerate() {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{}",
+                i + 1,
+                span.len(),
+                seconds(time.parse),
+                seconds(time.solve),
+                seconds(time.write)
+            );
+        }
+        if let Some(ref path) = self.timings_csv {
+            fs::write(path, &csv).expect("could not write timings CSV file");
+        }
+        if let Some(ref path) = self.timing_baseline {
+            fs::write(path, &csv).expect("could not write timing baseline file");
+        }
+    }
+
+    /// Writes a JSON report to the configured path, if any.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the report cannot be written, unless the thread is already panicking.
+    fn write_json(&self, status: &str) {
+        let path = match self.json_report {
+            Some(ref path) => path,
+            None => return,
+        };
+        let mut json = String::new();
+        let _ = write!(json, "{{\n  \"status\": \"{}\",\n", status);
+        let _ = writeln!(json, "  \"config\": {},", self.config);
+        let _ = writeln!(json, "  \"wall_time\": {},", seconds(self.wall_time()));
+        let _ = writeln!(json, "  \"output_bytes\": {},", self.output_bytes);
+        let _ = writeln!(json, "  \"output_synced\": {},", self.output_synced);
+        json.push_str("  \"cases\": [");
+        for (i, time) in self.cases.iter().enumerate() {
+            let _ = write!(
+                json,
+                "{}\n    {{\"case\": {}, \"parse_time\": {}, \"solve_time\": {}}}",
+                if i == 0 { "" } else { "," },
+                i + 1,
+                seconds(time.parse),
+                seconds(time.solve)
+            );
+        }
+        json.push_str(if self.cases.is_empty() { "],\n" } else { "\n  ],\n" });
+        json.push_str("  \"warnings\": [");
+        for (i, warning) in self.warnings.iter().enumerate() {
+            if i > 0 {
+                json.push_str(", ");
+            }
+            push_json_string(&mut json, warning);
+        }
+        json.push_str("]\n}\n");
+        if let Err(e) = fs::write(path, json) {
+            if !thread::panicking() {
+                panic!("could not write JSON report: {}", e);
+            }
+        }
+    }
+
+    /// Appends a record of the run to the configured run history, if any.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the record cannot be written, unless the thread is already
+    /// panicking.
+    fn record_history(&self, status: &str) {
+        let path = match self.run_history {
+            Some(ref path) => path,
+            None => return,
+        };
+        let record = RunRecord {
+            finished_at: history::unix_seconds(self.clock.system_time()),
+            wall_time: self.wall_time(),
+            cases: self.cases.len(),
+            output_bytes: self.output_bytes,
+            output_hash: self.output_hash,
+            status: status.to_owned(),
+            warnings: self.warnings.len(),
+            threads: self.threads,
+            seed: self.seed,
+        };
+        if let Err(e) = history::record(path, &record) {
+            if !thread::panicking() {
+                panic!("could not write run history: {}", e);
+            }
+        }
+    }
+
+    /// Writes a human-readable summary of the run to standard error.
+    fn write_summary(&self, wall_time: Duration) {
+        let parse_time: Duration = self.cases.iter().map(|t| t.parse).sum();
+        let solve_time: Duration = self.cases.iter().map(|t| t.solve).sum();
+        eprintln!("summary:");
+        eprintln!("  test cases: {}", self.cases.len());
+        eprintln!("  wall time: {}", format_duration(wall_time));
+        eprintln!(
+            "  parse time: {} (total), solve time: {} (total across threads)",
+            format_duration(parse_time),
+            format_duration(solve_time)
+        );
+        let mut slowest: Vec<(usize, &CaseTime)> = self.cases.iter().enumerate().collect();
+        slowest.sort_by_key(|&(_, time)| Reverse(time.solve));
+        for &(i, time) in slowest.iter().take(SLOWEST_CASE_COUNT) {
+            eprintln!(
+                "  slow case: Case #{} (solved in {})",
+                i + 1,
+                format_duration(time.solve)
+            );
+        }
+        let durability = if self.output_error.is_some() {
+            "not written: flushing failed"
+        } else if self.output_synced {
+            "flushed and synced to disk"
+        } else {
+            "flushed, not synced to disk"
+        };
+        eprintln!("  output size: {} bytes ({})", self.output_bytes, durability);
+        if self.warnings.is_empty() {
+            eprintln!("  warnings: none");
+        }
+        for warning in &self.warnings {
+            eprintln!("  warning: {}", warning);
+        }
+    }
+}
+
+impl Drop for RunReport {
+    fn drop(&mut self) {
+        if !self.finished && thread::panicking() {
+            self.write_json("panicked");
+            self.record_history("panicked");
+        }
+    }
+}
+
+/// Records a warning raised during the current run, to be included in its report.
+pub fn defer_warning(warning: String) {
+    lock_deferred().push(warning);
+}
+
+/// Locks the warnings raised during the current run by code outside of the test engine.
+fn lock_deferred() -> MutexGuard<'static, Vec<String>> {
+    DEFERRED.lock().expect("could not lock deferred warnings")
+}
+
+/// Prepares to write a file when the run finishes, if configured, returning its expanded path.
+///
+/// # Panics
+///
+/// This function panics if the file cannot be written.
+fn prepare(path: &Option<PathBuf>, description: &str) -> Option<PathBuf> {
+    path.as_ref().map(|path| {
+        paths::prepare_output(path)
+            .unwrap_or_else(|e| panic!("could not write {}: {}", description, e))
+    })
+}
+
+/// Formats a duration as a JSON or CSV number of seconds.
+fn seconds(d: Duration) -> String {
+    format!("{}.{:06}", d.as_secs(), d.subsec_micros())
+}
+
+/// Appends a string to a JSON document as a quoted, escaped JSON string.
+pub fn push_json_string(json: &mut String, s: &str) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
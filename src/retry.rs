@@ -0,0 +1,164 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Retrying test cases with escalated resources.
+
+use super::{format_duration, rng, write_debug, Input, InputReader, Output, TestEngine};
+use std::fmt::{self, Display, Formatter};
+use std::time::{Duration, Instant};
+
+/// When a test case is re-run by `TestEngine::run_with_retry()`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// The maximum number of times the solver is executed per test case.
+    max_attempts: usize,
+    /// How long an attempt may take before the test case is retried, if limited.
+    soft_deadline: Option<Duration>,
+}
+
+/// A test result produced by one attempt at solving a test case, along with whether the solver is
+/// confident in it.
+#[derive(Clone, Debug)]
+pub struct Attempt<R> {
+    /// The test result.
+    result: R,
+    /// Whether the solver is confident in the test result.
+    confident: bool,
+}
+
+/// A test result along with the number of attempts taken to produce it.
+struct Retried<R> {
+    /// The test result.
+    result: R,
+    /// The number of times the solver was executed.
+    attempts: usize,
+}
+
+impl RetryPolicy {
+    /// Creates a policy executing the solver at most `max_attempts` times per test case.
+    ///
+    /// By default, a test case is only retried if the solver reports low confidence.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `max_attempts` is zero.
+    pub fn new(max_attempts: usize) -> RetryPolicy {
+        assert!(max_attempts > 0, "solver must be executed at least once per test case");
+        RetryPolicy {
+            max_attempts: max_attempts,
+            soft_deadline: None,
+        }
+    }
+
+    /// Also retries a test case if an attempt takes longer than `deadline`.
+    ///
+    /// The attempt is not interrupted; its result is discarded once it finishes, unless it was the
+    /// last attempt allowed.
+    pub fn soft_deadline(mut self, deadline: Duration) -> RetryPolicy {
+        self.soft_deadline = Some(deadline);
+        self
+    }
+}
+
+impl<R> Attempt<R> {
+    /// Creates an attempt whose result the solver is confident in, which is accepted.
+    pub fn confident(result: R) -> Attempt<R> {
+        Attempt {
+            result: result,
+            confident: true,
+        }
+    }
+
+    /// Creates an attempt whose result the solver is unsure of, which causes the test case to be
+    /// retried if the retry policy allows another attempt.
+    pub fn unsure(result: R) -> Attempt<R> {
+        Attempt {
+            result: result,
+            confident: false,
+        }
+    }
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Consumes the test engine, executing a parser once per test case and a solver up to
+    /// `policy`'s maximum number of attempts per test case.
+    ///
+    /// The solver is passed the data for a test case and the attempt number, starting from zero,
+    /// which it can use to escalate its resources on each retry, e.g. by raising an iteration
+    /// budget or switching to a slower but exact algorithm. If the solver returns
+    /// `Attempt::unsure()`, or the attempt exceeds the policy's soft deadline, the test case is
+    /// retried with the next attempt number, and `case_rng()` returns a differently seeded
+    /// generator on each attempt. The result of the first attempt that is neither unsure nor late
+    /// is written, or that of the last attempt allowed.
+    ///
+    /// Each retry is written to standard error as it happens, and after all test cases have been
+    /// solved, the test cases that needed more than one attempt are listed.
+    ///
+    /// # Panics
+    ///
+    /// This method panics in the event of an I/O error.
+    pub fn run_with_retry<
+        D: Sized + Send + Sync,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader) -> D,
+        S: Fn(&D, usize) -> Attempt<R> + Sync
+    >
+        (
+        self,
+        policy: RetryPolicy,
+        p: P,
+        s: S,
+    ) {
+        let mut retried = Vec::new();
+        let _ = self.execute(
+            |reader| Some(p(reader)),
+            |d| {
+                let mut attempt = 0;
+                loop {
+                    let start = Instant::now();
+                    let Attempt { result, confident } =
+                        rng::with_attempt(attempt, || s(d, attempt));
+                    attempt += 1;
+                    let elapsed = start.elapsed();
+                    let late = policy.soft_deadline.is_some_and(|deadline| elapsed > deadline);
+                    if (confident && !late) || attempt >= policy.max_attempts {
+                        return Retried {
+                            result: result,
+                            attempts: attempt,
+                        };
+                    }
+                    if late {
+                        write_debug(format_args!(
+                            "attempt {} took {}, exceeding the soft deadline; retrying",
+                            attempt,
+                            format_duration(elapsed)
+                        ));
+                    } else {
+                        write_debug(format_args!(
+                            "attempt {} produced an unsure result; retrying",
+                            attempt
+                        ));
+                    }
+                }
+            },
+            |case, r, _| {
+                if r.attempts > 1 {
+                    retried.push((case, r.attempts));
+                }
+            },
+        );
+        for &(case, attempts) in &retried {
+            eprintln!("Case #{}: accepted after {} attempts", case, attempts);
+        }
+    }
+}
+
+impl<R: Display> Display for Retried<R> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.result.fmt(f)
+    }
+}
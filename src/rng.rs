@@ -0,0 +1,201 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Reproducible random number generation for test cases.
+//!
+//! Each test case gets its own generator, seeded from the run's seed and the test case number, so
+//! randomised solvers produce the same results on every run with the same seed, regardless of how
+//! many threads solve test cases or in what order. Repeated runs of the solver on one test case,
+//! as made by `TestEngine::run_scored()` and `TestEngine::run_with_retry()`, are also seeded from
+//! the run number, so each one draws different random numbers.
+
+use super::{logging, Input, Output, TestEngine};
+use std::cell::Cell;
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "rand")]
+use rand_core::RngCore;
+
+/// The environment variable read for the run's seed, if none is set on the test engine.
+pub const SEED_VAR: &str = "GCJ_SEED";
+
+/// The seed of the current run.
+static RUN_SEED: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// Which repeated run of the solver on the current test case is executing on this thread.
+    static ATTEMPT: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A small, fast pseudo-random number generator (xoshiro256**) seeded per test case.
+///
+/// This is not suitable for cryptography. If the `rand` feature is enabled, this implements
+/// `rand_core::RngCore`, so it can be used with the distributions and helpers in `rand`.
+#[derive(Clone, Copy, Debug)]
+pub struct CaseRng {
+    /// The generator state.
+    state: [u64; 4],
+}
+
+/// Records a repeated run of the solver as executing on this thread, restoring the previous one
+/// when dropped, even if the run panics.
+#[derive(Debug)]
+struct AttemptScope {
+    /// The run recorded before this one.
+    previous: usize,
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Sets the seed used to seed the random number generator returned by `case_rng()`.
+    ///
+    /// By default, the seed is read from the `GCJ_SEED` environment variable, or is zero if the
+    /// variable is not set. The seed is included in JSON reports.
+    pub fn seed(mut self, seed: u64) -> TestEngine<I, O> {
+        self.settings.seed = Some(seed);
+        self
+    }
+}
+
+impl CaseRng {
+    /// Creates a generator for the given run seed and test case number.
+    pub fn new(seed: u64, case: usize) -> CaseRng {
+        let mut x = seed ^ splitmix64(&mut (case as u64));
+        let mut state = [0; 4];
+        for s in &mut state {
+            *s = splitmix64(&mut x);
+        }
+        CaseRng { state: state }
+    }
+
+    /// Returns the next random `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// Returns the next random `u32`.
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Returns a random `f64` in the range `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a random integer in the range `[low, high)`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the range is empty.
+    pub fn gen_range(&mut self, low: u64, high: u64) -> u64 {
+        assert!(low < high, "cannot sample from an empty range");
+        let span = high - low;
+        let zone = u64::MAX - (u64::MAX - span + 1) % span;
+        loop {
+            let x = self.next_u64();
+            if x <= zone {
+                return low + x % span;
+            }
+        }
+    }
+
+    /// Shuffles a slice into a random order.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.gen_range(0, i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl RngCore for CaseRng {
+    fn next_u32(&mut self) -> u32 {
+        CaseRng::next_u32(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        CaseRng::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        for chunk in dst.chunks_mut(8) {
+            let bytes = CaseRng::next_u64(self).to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+impl Drop for AttemptScope {
+    fn drop(&mut self) {
+        ATTEMPT.with(|a| a.set(self.previous));
+    }
+}
+
+/// Returns a random number generator for the test case being parsed or solved on the current
+/// thread, seeded from the run's seed and the test case number.
+///
+/// Each call returns a new generator starting from the same state, so a solver should obtain one
+/// generator per test case and reuse it. Outside of a parser or solver, the generator is seeded as
+/// if for test case 0.
+///
+/// When the solver is run more than once on a test case, by `TestEngine::run_scored()` or
+/// `TestEngine::run_with_retry()`, the generator is also seeded from the run number, so each run
+/// draws different random numbers, while the whole sequence of runs is still the same on every
+/// run with the same seed. The first run draws the same numbers as a solver run only once.
+pub fn case_rng() -> CaseRng {
+    let mut seed = RUN_SEED.load(Ordering::Relaxed);
+    let attempt = ATTEMPT.with(|a| a.get());
+    if attempt > 0 {
+        seed ^= splitmix64(&mut (attempt as u64 ^ 0xa076_1d64_78bd_642f)).rotate_left(32);
+    }
+    CaseRng::new(seed, logging::current_case().unwrap_or(0))
+}
+
+/// Calls `f` with the given run number, starting from zero, recorded as the repeated run of the
+/// solver on the current test case that is executing on this thread.
+pub fn with_attempt<T, F: FnOnce() -> T>(attempt: usize, f: F) -> T {
+    let _scope = AttemptScope {
+        previous: ATTEMPT.with(|a| a.replace(attempt)),
+    };
+    f()
+}
+
+/// Sets the seed of the current run, returning it.
+///
+/// # Panics
+///
+/// This function panics if no seed is given and the `GCJ_SEED` environment variable is set, but
+/// is not a valid `u64`.
+pub fn start_run(seed: Option<u64>) -> u64 {
+    let seed = seed.unwrap_or_else(|| match env::var(SEED_VAR) {
+        Ok(s) => s.trim()
+            .parse()
+            .expect("could not parse GCJ_SEED environment variable"),
+        Err(_) => 0,
+    });
+    RUN_SEED.store(seed, Ordering::Relaxed);
+    seed
+}
+
+/// Advances a SplitMix64 generator, returning its next output.
+fn splitmix64(x: &mut u64) -> u64 {
+    *x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *x;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,242 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checking solutions against sample inputs and outputs.
+
+use super::{FromReader, Input, InputReader, Output, Settings, TestEngine, ToWriter};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+/// A sample input file and its expected output.
+///
+/// Samples are usually embedded into the binary with `include_samples!`.
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    /// A name identifying the sample in reports, e.g. its file name.
+    pub name: &'static str,
+    /// The contents of the sample input file.
+    pub input: &'static str,
+    /// The expected contents of the output file.
+    pub expected: &'static str,
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Executes a parser and solver over each sample, using the same settings as this test
+    /// engine, and compares the results against the expected outputs.
+    ///
+    /// Summaries, reports and other files written by a run, such as the run history, are not
+    /// written for the samples. A line stating whether each sample passed is written to standard
+    /// error, followed by the expected and actual results of any mismatched test cases. Line
+    /// endings and trailing whitespace are ignored when comparing results. Returns `true` if
+    /// every sample passed.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the solver produces output that is not valid UTF-8.
+    pub fn check_samples<
+        D: Sized + Send + Sync,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader) -> D,
+        S: Fn(&D) -> R + Sync
+    >
+        (
+        &self,
+        samples: &[Sample],
+        p: &P,
+        s: &S,
+    ) -> bool {
+        run_samples(&self.settings, samples, &|reader: &mut InputReader| Some(p(reader)), s)
+    }
+
+    /// Checks the samples before the input file whenever this test engine is run, only solving
+    /// the input file if every sample passes.
+    ///
+    /// Each sample is executed and compared as by `TestEngine::check_samples()`, with the same
+    /// parser, solver and settings as the run itself, before the output file is opened. If any
+    /// sample fails, the run panics without solving the input file or touching the output file,
+    /// which prevents a time-limited attempt being spent on a build that fails the samples.
+    pub fn sample_gate(mut self, samples: &'static [Sample]) -> TestEngine<I, O> {
+        self.settings.sample_gate = Some(samples);
+        self
+    }
+}
+
+/// Checks the samples of the sample gate in `settings`, if any, removing the gate so that the
+/// samples themselves are not gated.
+///
+/// # Panics
+///
+/// This function panics if any sample fails, or if the solver produces output that is not valid
+/// UTF-8.
+pub fn check_gate<
+    'a,
+    D: Sized + Send + Sync + 'a,
+    R: Display + Sized + Send,
+    P: Fn(&mut InputReader<'a>) -> Option<D>,
+    S: Fn(&D) -> R + Sync
+>
+    (
+    settings: &mut Settings,
+    p: &P,
+    s: &S,
+) {
+    if let Some(samples) = settings.sample_gate.take() {
+        if !run_samples(settings, samples, p, s) {
+            panic!("not solving the input file, as the samples failed");
+        }
+    }
+}
+
+/// Executes a parser and solver over each sample with the given settings, and compares the
+/// results against the expected outputs, returning `true` if every sample passed.
+///
+/// Settings that write reports or other files are cleared for the sample runs, so that they
+/// describe only the run over the input file.
+///
+/// The parser and solver are trait objects, as the runs executed here check a sample gate of
+/// their own, which would otherwise instantiate this function again for references to them.
+///
+/// # Panics
+///
+/// This function panics if the solver produces output that is not valid UTF-8.
+fn run_samples<'a, D: Sized + Send + Sync + 'a, R: Display + Sized + Send>(
+    settings: &Settings,
+    samples: &[Sample],
+    p: &dyn Fn(&mut InputReader<'a>) -> Option<D>,
+    s: &(dyn Fn(&D) -> R + Sync),
+) -> bool {
+    let mut all_passed = true;
+    for sample in samples {
+        let mut output = Vec::new();
+        let mut settings = settings.clone();
+        settings.sample_gate = None;
+        settings.summary = false;
+        settings.json_report = None;
+        settings.timings_csv = None;
+        settings.timing_baseline = None;
+        settings.case_index = None;
+        settings.annotations_file = None;
+        settings.run_history = None;
+        settings.case_files = None;
+        settings.auto_tune = None;
+        let _ = TestEngine::<FromReader<&[u8]>, ToWriter<&mut Vec<u8>>>::execute_text(
+            ToWriter(&mut output),
+            settings,
+            sample.input,
+            p,
+            s,
+            |_, _, _| {},
+            || None,
+        );
+        let actual = String::from_utf8(output).expect("solver output is not valid UTF-8");
+        let mismatches = mismatched_cases(sample.expected, &actual);
+        if mismatches.is_empty() {
+            eprintln!("sample {}: ok", sample.name);
+        } else {
+            all_passed = false;
+            eprintln!("sample {}: FAILED", sample.name);
+            for (expected, actual) in mismatches {
+                eprintln!("  expected: {:?}", expected);
+                eprintln!("    actual: {:?}", actual);
+            }
+        }
+    }
+    all_passed
+}
+
+/// Splits an output file into test results, each beginning with a `"Case #N:"` header.
+///
+/// Any text before the first header is returned as a separate block. If the output contains no
+/// headers, the entire output is returned as one block.
+pub fn split_cases(output: &str) -> Vec<&str> {
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    for line in output.split_terminator('\n') {
+        if line.starts_with("Case #") && offset > start {
+            blocks.push(&output[start..offset]);
+            start = offset;
+        }
+        offset += line.len() + 1;
+    }
+    if start < output.len() {
+        blocks.push(&output[start..]);
+    }
+    blocks
+}
+
+/// Splits expected and actual output into test results as by `split_cases()`, and pairs them by
+/// the number in their `"Case #N:"` headers, in order of case number.
+///
+/// A test result missing from one side is paired with `None`, so that a missing or extra test
+/// result does not offset the comparison of later ones. Text before the first header, or an
+/// output without headers, is paired with that of the other side, ahead of every test result. If
+/// a case number appears more than once, each repeat is paired with the corresponding repeat on
+/// the other side.
+pub fn pair_cases<'e, 'a>(
+    expected: &'e str,
+    actual: &'a str,
+) -> Vec<(Option<&'e str>, Option<&'a str>)> {
+    let mut pairs = BTreeMap::new();
+    for (key, block) in keyed_cases(expected) {
+        pairs.entry(key).or_insert((None, None)).0 = Some(block);
+    }
+    for (key, block) in keyed_cases(actual) {
+        pairs.entry(key).or_insert((None, None)).1 = Some(block);
+    }
+    pairs.into_values().collect()
+}
+
+/// Compares expected and actual output case by case, returning each mismatched pair of test
+/// results. A missing test result is represented by an empty string.
+///
+/// Test results are paired by their case numbers, as by `pair_cases()`. Line endings and
+/// trailing whitespace are ignored when comparing results.
+pub fn mismatched_cases(expected: &str, actual: &str) -> Vec<(String, String)> {
+    let expected = normalize(expected);
+    let actual = normalize(actual);
+    pair_cases(&expected, &actual)
+        .into_iter()
+        .map(|(e, a)| (e.map_or("", str::trim_end), a.map_or("", str::trim_end)))
+        .filter(|&(e, a)| e != a)
+        .map(|(e, a)| (e.to_owned(), a.to_owned()))
+        .collect()
+}
+
+/// Returns the case number of a test result, parsed from its `"Case #N:"` header, if it has one.
+fn case_number(block: &str) -> Option<usize> {
+    block
+        .strip_prefix("Case #")
+        .and_then(|rest| rest.split(':').next())
+        .and_then(|n| n.trim().parse().ok())
+}
+
+/// Splits an output into test results, keyed by their case number and by how many earlier
+/// test results had the same case number.
+fn keyed_cases(output: &str) -> Vec<((Option<usize>, usize), &str)> {
+    let mut seen: BTreeMap<Option<usize>, usize> = BTreeMap::new();
+    split_cases(output)
+        .into_iter()
+        .map(|block| {
+            let number = case_number(block);
+            let repeat = seen.entry(number).or_insert(0);
+            *repeat += 1;
+            ((number, *repeat - 1), block)
+        })
+        .collect()
+}
+
+/// Normalizes line endings to `"\n"`, and removes trailing whitespace from each line.
+fn normalize(output: &str) -> String {
+    let mut normalized = String::with_capacity(0);
+    normalized.reserve_exact(output.len());
+    for line in output.lines() {
+        normalized.push_str(line.trim_end());
+        normalized.push('\n');
+    }
+    normalized
+}
@@ -0,0 +1,93 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Support for scored optimisation problems.
+
+use super::{rng, Input, InputReader, Output, TestEngine};
+use std::fmt::{self, Display, Formatter};
+
+/// A test result paired with the score it was awarded.
+struct Scored<R> {
+    /// The test result.
+    result: R,
+    /// The score awarded to the test result.
+    score: f64,
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Consumes the test engine, executing a parser and solver once per test case and awarding
+    /// each test result a score.
+    ///
+    /// This is intended for open-ended optimisation problems, where there is no single correct
+    /// answer. `score` is called with the data for a test case and the solver's result, and
+    /// returns the score awarded to that result. The solver is executed `runs` times per test
+    /// case, and only the highest-scoring result is written to the output file; this is useful for
+    /// randomised solvers, where repeated runs may produce better results, and `case_rng()` returns
+    /// a differently seeded generator on each run.
+    ///
+    /// After all test cases have been solved, the score for each test case and the total score are
+    /// written to standard error.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `runs` is zero, or in the event of an I/O error.
+    pub fn run_scored<
+        D: Sized + Send + Sync,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader) -> D,
+        S: Fn(&D) -> R + Sync,
+        C: Fn(&D, &R) -> f64 + Sync
+    >
+        (
+        self,
+        runs: usize,
+        p: P,
+        s: S,
+        score: C,
+    ) {
+        assert!(runs > 0, "solver must be executed at least once per test case");
+        let mut scores = Vec::new();
+        let _ = self.execute(
+            |reader| Some(p(reader)),
+            |d| {
+                let mut best: Option<Scored<R>> = None;
+                for run in 0..runs {
+                    let result = rng::with_attempt(run, || s(d));
+                    let result_score = score(d, &result);
+                    let is_better = match best {
+                        Some(ref b) => result_score > b.score,
+                        None => true,
+                    };
+                    if is_better {
+                        best = Some(Scored {
+                            result: result,
+                            score: result_score,
+                        });
+                    }
+                }
+                match best {
+                    Some(b) => b,
+                    None => unreachable!(),
+                }
+            },
+            |case, r, _| scores.push((case, r.score)),
+        );
+        for &(case, case_score) in &scores {
+            eprintln!("Case #{}: score {}", case, case_score);
+        }
+        eprintln!(
+            "Total score: {}",
+            scores.iter().map(|&(_, case_score)| case_score).sum::<f64>()
+        );
+    }
+}
+
+impl<R: Display> Display for Scored<R> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.result.fmt(f)
+    }
+}
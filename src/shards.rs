@@ -0,0 +1,76 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Input files split across several shards.
+
+use super::{Input, InputFile};
+use std::io::{self, Read};
+use std::path::Path;
+use std::vec;
+
+/// Several input file paths that together form one logical input file, for use as an `Input`.
+///
+/// The shards are read in order as a single continuous stream, so the number of test cases is
+/// only expected at the start of the first shard, and test cases may span shards. If a shard does
+/// not end with an end-of-line marker, one is inserted before the next shard. Like other input
+/// file paths, shards ending in `.gz` or `.zip` are decompressed if the `compression` feature is
+/// enabled.
+#[derive(Clone, Debug)]
+pub struct Shards<P: AsRef<Path>>(pub Vec<P>);
+
+/// Reads several input files in order as a single stream.
+#[derive(Debug)]
+pub struct ShardReader {
+    /// The shards that have not yet been read.
+    files: vec::IntoIter<InputFile>,
+    /// The shard currently being read, if any.
+    current: Option<InputFile>,
+    /// Whether the last byte read from the current shard was an end-of-line marker, or `None` if
+    /// nothing has been read from it.
+    ended_line: Option<bool>,
+}
+
+impl<P: AsRef<Path>> Input for Shards<P> {
+    type Reader = ShardReader;
+
+    fn open(self) -> io::Result<ShardReader> {
+        let mut files = Vec::with_capacity(0);
+        files.reserve_exact(self.0.len());
+        for path in self.0 {
+            files.push(InputFile::open(path.as_ref())?);
+        }
+        let mut files = files.into_iter();
+        Ok(ShardReader {
+            current: files.next(),
+            files: files,
+            ended_line: None,
+        })
+    }
+}
+
+impl Read for ShardReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let n = match self.current {
+                Some(ref mut file) => file.read(buf)?,
+                None => return Ok(0),
+            };
+            if n > 0 {
+                self.ended_line = Some(buf[n - 1] == b'\n');
+                return Ok(n);
+            }
+            self.current = self.files.next();
+            if self.ended_line.take() == Some(false) && self.current.is_some() {
+                buf[0] = b'\n';
+                return Ok(1);
+            }
+        }
+    }
+}
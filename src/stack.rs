@@ -0,0 +1,98 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Solving test cases on a dedicated thread with a large stack.
+
+use super::{Input, Output, TestEngine};
+#[cfg(not(feature = "parallel"))]
+use std::panic;
+#[cfg(not(feature = "parallel"))]
+use std::sync::mpsc::{self, Receiver, Sender};
+#[cfg(not(feature = "parallel"))]
+use std::thread::{self, Scope, ScopedJoinHandle};
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Sets the stack size, in bytes, of a dedicated thread that solves every test case.
+    ///
+    /// By default, test cases are solved on the thread calling `TestEngine::run()`, whose stack is
+    /// often too small for deeply recursive solutions. Once set, test cases are instead handed to
+    /// a thread with the given stack size, one at a time; parsing and writing still happen on the
+    /// calling thread. This setting has no effect in parallel mode, where test cases are solved on
+    /// the thread pool's worker threads, or on WASI targets without threads, where the stack size
+    /// is fixed when the module is linked.
+    pub fn stack_size(mut self, bytes: usize) -> TestEngine<I, O> {
+        self.settings.stack_size = Some(bytes);
+        self
+    }
+}
+
+/// A thread that solves test cases one at a time.
+#[cfg(not(feature = "parallel"))]
+#[derive(Debug)]
+pub struct SolverThread<'scope, D: Send, R: Send> {
+    /// Sends test cases to the thread.
+    cases: Option<Sender<(usize, D)>>,
+    /// Receives test results from the thread.
+    results: Receiver<R>,
+    /// The thread, until it has been joined.
+    handle: Option<ScopedJoinHandle<'scope, ()>>,
+}
+
+#[cfg(not(feature = "parallel"))]
+impl<'scope, D: Send + 'scope, R: Send + 'scope> SolverThread<'scope, D, R> {
+    /// Spawns a thread with the given stack size within `scope`, solving test cases with `solve`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the thread cannot be spawned.
+    pub fn spawn<'env, S: Fn(usize, &D) -> R + Sync>(
+        scope: &'scope Scope<'scope, 'env>,
+        stack_size: usize,
+        solve: &'env S,
+    ) -> SolverThread<'scope, D, R> {
+        let (cases, case_receiver) = mpsc::channel::<(usize, D)>();
+        let (result_sender, results) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name("gcj-solver".to_owned())
+            .stack_size(stack_size)
+            .spawn_scoped(scope, move || {
+                for (case, d) in case_receiver {
+                    if result_sender.send(solve(case, &d)).is_err() {
+                        break;
+                    }
+                }
+            })
+            .expect("could not spawn solver thread");
+        SolverThread {
+            cases: Some(cases),
+            results: results,
+            handle: Some(handle),
+        }
+    }
+
+    /// Solves a test case on the thread, waiting for the result.
+    ///
+    /// # Panics
+    ///
+    /// If the solver panics, the panic is resumed on the calling thread.
+    pub fn solve(&mut self, case: usize, d: D) -> R {
+        let sent = match self.cases {
+            Some(ref cases) => cases.send((case, d)).is_ok(),
+            None => false,
+        };
+        if sent {
+            if let Ok(r) = self.results.recv() {
+                return r;
+            }
+        }
+        self.cases = None;
+        match self.handle.take().map(|handle| handle.join()) {
+            Some(Err(payload)) => panic::resume_unwind(payload),
+            _ => panic!("solver thread exited unexpectedly"),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Submission packaging for upload-based contests.
+
+use std::fs::{self, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// The DOS date stored for each archived file (1980-01-01).
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+/// Bundles an output file and a set of source files into a zip archive, ready to upload.
+///
+/// Contests such as Meta Hacker Cup require both the output file and the source code used to
+/// produce it. The archive is written alongside the output file, with the same name but a `.zip`
+/// extension (e.g. `foo.out` is bundled into `foo.zip`), and its path is returned. Files are
+/// stored uncompressed at the root of the archive, under their file names.
+///
+/// # Panics
+///
+/// This function panics in the event of an I/O error, or if any file is too large to be stored in
+/// a zip archive without ZIP64 extensions.
+pub fn package_submission<O: AsRef<Path>, S: AsRef<Path>>(
+    output_file: O,
+    sources: &[S],
+) -> PathBuf {
+    let output_file = output_file.as_ref();
+    let zip_path = output_file.with_extension("zip");
+    let mut entries = Vec::with_capacity(0);
+    entries.reserve_exact(sources.len() + 1);
+    for path in Some(output_file)
+        .into_iter()
+        .chain(sources.iter().map(|s| s.as_ref()))
+    {
+        let name = path.file_name()
+            .and_then(|name| name.to_str())
+            .expect("could not determine file name for submission")
+            .to_owned();
+        let data = fs::read(path).expect("could not read file for submission");
+        entries.push((name, data));
+    }
+    let mut zip = BufWriter::new(
+        OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .create(true)
+            .open(&zip_path)
+            .expect("could not open submission archive for writing"),
+    );
+    let mut central_directory = Vec::new();
+    let mut offset = 0u32;
+    for (name, data) in &entries {
+        let crc = crc32(data);
+        let size = to_u32(data.len());
+        let name_len = to_u16(name.len());
+        let mut header = Vec::new();
+        push_u32(&mut header, 0x0403_4b50);
+        push_common_header(&mut header, crc, size, name_len);
+        header.extend_from_slice(name.as_bytes());
+        zip.write_all(&header)
+            .and_then(|_| zip.write_all(data))
+            .expect("could not write submission archive");
+        push_u32(&mut central_directory, 0x0201_4b50);
+        push_u16(&mut central_directory, 20);
+        push_common_header(&mut central_directory, crc, size, name_len);
+        // Comment length, disk number, internal and external attributes.
+        push_u16(&mut central_directory, 0);
+        push_u16(&mut central_directory, 0);
+        push_u16(&mut central_directory, 0);
+        push_u32(&mut central_directory, 0);
+        push_u32(&mut central_directory, offset);
+        central_directory.extend_from_slice(name.as_bytes());
+        offset = offset
+            .checked_add(to_u32(header.len()))
+            .and_then(|o| o.checked_add(size))
+            .expect("submission archive is too large");
+    }
+    let entry_count = to_u16(entries.len());
+    let mut end = Vec::new();
+    push_u32(&mut end, 0x0605_4b50);
+    // Disk numbers.
+    push_u16(&mut end, 0);
+    push_u16(&mut end, 0);
+    push_u16(&mut end, entry_count);
+    push_u16(&mut end, entry_count);
+    push_u32(&mut end, to_u32(central_directory.len()));
+    push_u32(&mut end, offset);
+    // Comment length.
+    push_u16(&mut end, 0);
+    zip.write_all(&central_directory)
+        .and_then(|_| zip.write_all(&end))
+        .and_then(|_| zip.flush())
+        .expect("could not write submission archive");
+    zip_path
+}
+
+/// Appends the fields shared by local file headers and central directory headers.
+fn push_common_header(buf: &mut Vec<u8>, crc: u32, size: u32, name_len: u16) {
+    // Version needed to extract, flags, compression method (stored), time and date.
+    push_u16(buf, 20);
+    push_u16(buf, 0);
+    push_u16(buf, 0);
+    push_u16(buf, 0);
+    push_u16(buf, DOS_DATE);
+    push_u32(buf, crc);
+    push_u32(buf, size);
+    push_u32(buf, size);
+    push_u16(buf, name_len);
+    // Extra field length.
+    push_u16(buf, 0);
+}
+
+/// Appends a little-endian `u16`.
+fn push_u16(buf: &mut Vec<u8>, n: u16) {
+    buf.extend_from_slice(&n.to_le_bytes());
+}
+
+/// Appends a little-endian `u32`.
+fn push_u32(buf: &mut Vec<u8>, n: u32) {
+    buf.extend_from_slice(&n.to_le_bytes());
+}
+
+/// Converts a length to a `u16`, panicking if it does not fit.
+fn to_u16(n: usize) -> u16 {
+    assert!(n <= u16::MAX as usize, "submission archive has too many or too long entries");
+    n as u16
+}
+
+/// Converts a length to a `u32`, panicking if it does not fit.
+fn to_u32(n: usize) -> u32 {
+    assert!(n <= u32::MAX as usize, "submission archive is too large");
+    n as u32
+}
+
+/// Computes the CRC-32 checksum of a byte slice, as used by the zip format.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
@@ -0,0 +1,71 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Writing test results to several outputs at once.
+
+use super::{Input, Output, TestEngine};
+use std::io::{self, Write};
+
+/// Two outputs written to simultaneously, for use as an `Output`.
+///
+/// Everything written is written to both outputs, in order. More than two outputs can be combined
+/// by nesting, e.g. `Tee(a, Tee(b, c))`.
+#[derive(Clone, Debug)]
+pub struct Tee<A: Output, B: Output>(pub A, pub B);
+
+/// Writes everything written to it to two writers.
+#[derive(Debug)]
+pub struct TeeWriter<A: Write, B: Write> {
+    /// The first writer.
+    first: A,
+    /// The second writer.
+    second: B,
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Adds another output that test results are written to, alongside the current output.
+    ///
+    /// For example, `TestEngine::new("a.in", "a.out").tee(Stdio)` writes test results both to
+    /// `a.out` and to standard output. The solver is still only run once per test case.
+    pub fn tee<P: Output>(self, output: P) -> TestEngine<I, Tee<O, P>> {
+        TestEngine {
+            input: self.input,
+            output: Tee(self.output, output),
+            settings: self.settings,
+        }
+    }
+}
+
+impl<A: Output, B: Output> Output for Tee<A, B> {
+    type Writer = TeeWriter<A::Writer, B::Writer>;
+
+    fn open(self) -> io::Result<TeeWriter<A::Writer, B::Writer>> {
+        Ok(TeeWriter {
+            first: self.0.open()?,
+            second: self.1.open()?,
+        })
+    }
+
+    fn sync(writer: &mut TeeWriter<A::Writer, B::Writer>) -> io::Result<bool> {
+        let first = A::sync(&mut writer.first)?;
+        let second = B::sync(&mut writer.second)?;
+        Ok(first && second)
+    }
+}
+
+impl<A: Write, B: Write> Write for TeeWriter<A, B> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.first.write_all(buf)?;
+        self.second.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.first.flush()?;
+        self.second.flush()
+    }
+}
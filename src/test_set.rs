@@ -0,0 +1,231 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Support for problems with multiple test sets.
+
+use super::{format_duration, progress, Input, InputReader, Output, TestEngine};
+use super::clock::{Clock, SystemClock};
+use std::fmt::Display;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+/// Runs the same parser and solver over several test sets in one invocation.
+///
+/// Each test set has its own `TestEngine`, and thus its own input and output files. After every
+/// test set has been solved, a summary is written to standard error stating how many test cases
+/// each test set contained and how long it took, compared against its time limit if one was given.
+#[derive(Debug)]
+pub struct TestSets<I: Input, O: Output> {
+    /// The test sets to run, in order.
+    sets: Vec<TestSet<I, O>>,
+}
+
+/// A single named test set.
+#[derive(Debug)]
+struct TestSet<I: Input, O: Output> {
+    /// A human-readable name used in the summary, e.g. `"Test Set 1"`.
+    name: String,
+    /// The test engine used to solve this test set.
+    engine: TestEngine<I, O>,
+    /// The expected time limit for this test set, if any.
+    time_limit: Option<Duration>,
+}
+
+impl<I: Input, O: Output> TestSets<I, O> {
+    /// Creates an empty collection of test sets.
+    pub fn new() -> TestSets<I, O> {
+        TestSets { sets: Vec::new() }
+    }
+
+    /// Adds a test set, solved using the given test engine.
+    ///
+    /// Test sets are run in the order they are added. If `time_limit` is specified, the summary
+    /// states whether the test set was solved within that limit, and the test engine warns as the
+    /// limit is approached (see `TestEngine::time_limit()`).
+    pub fn add<N: Into<String>>(
+        mut self,
+        name: N,
+        engine: TestEngine<I, O>,
+        time_limit: Option<Duration>,
+    ) -> TestSets<I, O> {
+        let engine = match time_limit {
+            Some(limit) => engine.time_limit(limit),
+            None => engine,
+        };
+        self.sets.push(TestSet {
+            name: name.into(),
+            engine: engine,
+            time_limit: time_limit,
+        });
+        self
+    }
+
+    /// Consumes the test sets, executing a parser and solver once per test case in each test set,
+    /// then writes a summary to standard error.
+    ///
+    /// # Panics
+    ///
+    /// This method panics in the event of an I/O error.
+    pub fn run<
+        D: Sized + Send + Sync,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader) -> D,
+        S: Fn(&D) -> R + Sync
+    >
+        (
+        self,
+        p: P,
+        s: S,
+    ) {
+        let mut results = Vec::with_capacity(0);
+        results.reserve_exact(self.sets.len());
+        for set in self.sets {
+            let clock = set.engine.settings.clock.clone();
+            let start = clock.monotonic();
+            let case_count = set.engine.execute(|reader| Some(p(reader)), &s, |_, _, _| {});
+            let elapsed = clock.monotonic().saturating_sub(start);
+            results.push((set.name, case_count, elapsed, set.time_limit));
+        }
+        summarize(&results);
+    }
+}
+
+impl<I: Input + Send, O: Output + Send> TestSets<I, O> {
+    /// Consumes the test sets, executing a parser and solver once per test case in each test set,
+    /// solving different test sets concurrently, then writes a summary to standard error.
+    ///
+    /// Each test set is solved on a thread of its own, with its own reader and writer, so that
+    /// independent input files (e.g. the small and large inputs of a practice problem, or several
+    /// Hash Code inputs) finish sooner than if solved one after another; if the `parallel` feature
+    /// is enabled, their test cases share one thread pool. While test sets are being solved, a
+    /// combined progress report stating how many test cases of each test set have been written is
+    /// periodically written to standard error. The summary additionally states the wall time of
+    /// the whole invocation, as measured by the clock of the first test set.
+    ///
+    /// Settings that affect the whole process, such as `TestEngine::handle_interrupt()`,
+    /// `TestEngine::stall_watchdog()`, `TestEngine::isolate_stdout()` and
+    /// `TestEngine::capture_debug()`, should not be enabled for test sets solved concurrently,
+    /// since each run would enable and disable them for every other run. Per-test-set progress
+    /// reports would interleave with the combined one, so `TestEngine::progress()` is best left
+    /// disabled too.
+    ///
+    /// # Panics
+    ///
+    /// This method panics in the event of an I/O error, or if a test set's thread cannot be
+    /// started or panics.
+    pub fn run_concurrent<
+        D: Sized + Send + Sync,
+        R: Display + Sized + Send,
+        P: Fn(&mut InputReader) -> D + Sync,
+        S: Fn(&D) -> R + Sync
+    >
+        (
+        self,
+        p: P,
+        s: S,
+    ) {
+        let clock: Arc<dyn Clock> = match self.sets.first() {
+            Some(set) => set.engine.settings.clock.clone(),
+            None => Arc::new(SystemClock::new()),
+        };
+        let start = clock.monotonic();
+        let written: Vec<AtomicUsize> = self.sets.iter().map(|_| AtomicUsize::new(0)).collect();
+        let mut finished = Vec::with_capacity(0);
+        finished.reserve_exact(self.sets.len());
+        thread::scope(|scope| {
+            let (sender, receiver) = mpsc::channel();
+            let mut names = Vec::with_capacity(0);
+            names.reserve_exact(self.sets.len());
+            for (i, set) in self.sets.into_iter().enumerate() {
+                names.push(set.name.clone());
+                let (sender, written, p, s) = (sender.clone(), &written[i], &p, &s);
+                let _ = thread::Builder::new()
+                    .name(set.name.clone())
+                    .spawn_scoped(scope, move || {
+                        let set_clock = set.engine.settings.clock.clone();
+                        let set_start = set_clock.monotonic();
+                        let case_count = set.engine.execute(
+                            |reader| Some(p(reader)),
+                            s,
+                            |_, _, _| {
+                                let _ = written.fetch_add(1, Ordering::SeqCst);
+                            },
+                        );
+                        let elapsed = set_clock.monotonic().saturating_sub(set_start);
+                        let _ = sender.send((i, (set.name, case_count, elapsed, set.time_limit)));
+                    })
+                    .expect("could not start test set thread");
+            }
+            drop(sender);
+            loop {
+                match receiver.recv_timeout(progress::REPORT_INTERVAL) {
+                    Ok(result) => finished.push(result),
+                    Err(RecvTimeoutError::Timeout) => {
+                        let sets: Vec<String> = names
+                            .iter()
+                            .enumerate()
+                            .map(|(i, name)| match finished.iter().find(|r| r.0 == i) {
+                                Some(_) => format!("{}: done", name),
+                                None => format!(
+                                    "{}: {} cases",
+                                    name,
+                                    written[i].load(Ordering::SeqCst)
+                                ),
+                            })
+                            .collect();
+                        eprintln!(
+                            "progress: {}, {} elapsed",
+                            sets.join(", "),
+                            format_duration(clock.monotonic().saturating_sub(start))
+                        );
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
+        finished.sort_by_key(|r| r.0);
+        let results: Vec<_> = finished.into_iter().map(|(_, result)| result).collect();
+        summarize(&results);
+        eprintln!(
+            "all test sets: {} cases in {}",
+            results.iter().map(|r| r.1).sum::<usize>(),
+            format_duration(clock.monotonic().saturating_sub(start))
+        );
+    }
+}
+
+impl<I: Input, O: Output> Default for TestSets<I, O> {
+    fn default() -> TestSets<I, O> {
+        Self::new()
+    }
+}
+
+/// Writes a summary line to standard error for each test set's name, number of test cases, wall
+/// time and time limit.
+fn summarize(results: &[(String, usize, Duration, Option<Duration>)]) {
+    for &(ref name, case_count, elapsed, time_limit) in results {
+        match time_limit {
+            Some(limit) => eprintln!(
+                "{}: {} cases in {} (limit {}{})",
+                name,
+                case_count,
+                format_duration(elapsed),
+                format_duration(limit),
+                if elapsed > limit { ", exceeded" } else { "" },
+            ),
+            None => eprintln!(
+                "{}: {} cases in {}",
+                name,
+                case_count,
+                format_duration(elapsed),
+            ),
+        }
+    }
+}
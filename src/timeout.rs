@@ -0,0 +1,144 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Abandoning test cases that exceed a hard time limit.
+
+use super::{format_duration, logging, report, Input, InputReader, Output, TestEngine};
+use std::cell::RefCell;
+use std::fmt::{self, Display, Formatter};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// The text written in place of the test result of an abandoned test case.
+pub const TIME_LIMIT_EXCEEDED_ANSWER: &str = " TLE\n";
+
+thread_local! {
+    /// Whether the test case being solved on this thread has been abandoned, if it is being solved
+    /// by `TestEngine::run_with_timeout()`.
+    static CANCELLED: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+/// A test result, or the placeholder written for an abandoned test case.
+enum Limited<R> {
+    /// The solver finished within the time limit.
+    Solved(R),
+    /// The solver was abandoned.
+    TimeLimitExceeded,
+}
+
+impl<R: Display> Display for Limited<R> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Limited::Solved(ref result) => result.fmt(f),
+            Limited::TimeLimitExceeded => f.write_str(TIME_LIMIT_EXCEEDED_ANSWER),
+        }
+    }
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Consumes the test engine, executing a parser and solver once per test case, abandoning
+    /// any test case whose solver runs for longer than `limit`.
+    ///
+    /// Each test case is solved on a worker thread of its own, with the stack size set by
+    /// `TestEngine::stack_size()` if any. If the solver has not returned once `limit` has
+    /// elapsed, the test case is abandoned: `" TLE\n"` is written as its test result, a warning
+    /// naming it is written to standard error and included in the run summary and JSON report,
+    /// and the run continues with the next test case, rather than hanging on one pathological
+    /// test case. Threads cannot be killed safely, so an abandoned solver keeps running in the
+    /// background until it returns or the process exits; a solver that polls `case_cancelled()`
+    /// can return early instead, freeing its thread.
+    ///
+    /// Since an abandoned solver may outlive the run, the solver and the data for each test case
+    /// must be `'static`, and the data is cloned for its worker thread.
+    ///
+    /// # Panics
+    ///
+    /// This method panics in the event of an I/O error, if a worker thread cannot be started, or
+    /// if the solver panics within the time limit.
+    pub fn run_with_timeout<
+        D: Clone + Sized + Send + Sync + 'static,
+        R: Display + Sized + Send + 'static,
+        P: Fn(&mut InputReader) -> D,
+        S: Fn(&D) -> R + Send + Sync + 'static
+    >
+        (
+        self,
+        limit: Duration,
+        p: P,
+        s: S,
+    ) {
+        let stack_size = self.settings.stack_size;
+        let s = Arc::new(s);
+        let _ = self.execute(
+            |reader| Some(p(reader)),
+            |d| {
+                let case = logging::current_case();
+                let (sender, receiver) = mpsc::channel();
+                let cancelled = Arc::new(AtomicBool::new(false));
+                let (s, d, flag) = (s.clone(), d.clone(), cancelled.clone());
+                let mut builder = thread::Builder::new().name("gcj-helper timed solver".to_owned());
+                if let Some(size) = stack_size {
+                    builder = builder.stack_size(size);
+                }
+                let worker = builder
+                    .spawn(move || {
+                        CANCELLED.with(|c| *c.borrow_mut() = Some(flag));
+                        let result = match case {
+                            Some(case) => logging::with_case(case, || s(&d)),
+                            None => s(&d),
+                        };
+                        let _ = sender.send(result);
+                    })
+                    .expect("could not start solver thread");
+                match receiver.recv_timeout(limit) {
+                    Ok(result) => Limited::Solved(result),
+                    Err(RecvTimeoutError::Disconnected) => match worker.join() {
+                        Err(payload) => panic::resume_unwind(payload),
+                        Ok(()) => unreachable!("solver thread exited without a test result"),
+                    },
+                    Err(RecvTimeoutError::Timeout) => {
+                        cancelled.store(true, Ordering::SeqCst);
+                        abandoned(case, limit);
+                        Limited::TimeLimitExceeded
+                    }
+                }
+            },
+            |_, _, _| {},
+        );
+    }
+}
+
+/// Returns `true` if the test case being solved on the current thread has been abandoned by
+/// `TestEngine::run_with_timeout()` for exceeding its time limit, so that the solver can return
+/// early rather than running in the background until the process exits.
+///
+/// The solver's test result is discarded once the test case has been abandoned, so any value may
+/// be returned. Outside of `TestEngine::run_with_timeout()`, this always returns `false`.
+pub fn case_cancelled() -> bool {
+    CANCELLED.with(|c| {
+        c.borrow()
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::SeqCst))
+    })
+}
+
+/// Reports that a test case has been abandoned after exceeding the given time limit.
+fn abandoned(case: Option<usize>, limit: Duration) {
+    let case = case.map_or_else(|| "a test case".to_owned(), |case| format!("Case #{}", case));
+    let warning = format!(
+        "{} exceeded the hard time limit of {} and was abandoned",
+        case,
+        format_duration(limit)
+    );
+    eprintln!("warning: {}", warning);
+    logging::warn(&warning);
+    report::defer_warning(warning);
+}
@@ -0,0 +1,94 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Integration with the `tracing` crate.
+//!
+//! When the `tracing` feature is enabled, each test case is given a `case` span with `case`,
+//! `parse_bytes` and `duration_us` fields, containing `parse`, `solve` and `write` spans for each
+//! phase of processing the test case. Otherwise, `CaseSpan` does nothing.
+
+use super::CaseTime;
+
+/// The span covering one test case.
+#[cfg(feature = "tracing")]
+#[derive(Clone, Debug)]
+pub struct CaseSpan {
+    /// The underlying span.
+    span: ::tracing::Span,
+}
+
+/// The span covering one test case.
+#[cfg(not(feature = "tracing"))]
+#[derive(Clone, Copy, Debug)]
+pub struct CaseSpan;
+
+#[cfg(feature = "tracing")]
+impl CaseSpan {
+    /// Creates the span for a test case.
+    pub fn new(case: usize) -> CaseSpan {
+        CaseSpan {
+            span: ::tracing::info_span!(
+                target: "gcj_helper",
+                "case",
+                case = case as u64,
+                parse_bytes = ::tracing::field::Empty,
+                duration_us = ::tracing::field::Empty,
+            ),
+        }
+    }
+
+    /// Calls `f` within a `parse` span for this test case.
+    pub fn parse<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        self.span
+            .in_scope(|| ::tracing::debug_span!(target: "gcj_helper", "parse").in_scope(f))
+    }
+
+    /// Calls `f` within a `solve` span for this test case.
+    pub fn solve<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        self.span
+            .in_scope(|| ::tracing::debug_span!(target: "gcj_helper", "solve").in_scope(f))
+    }
+
+    /// Calls `f` within a `write` span for this test case.
+    pub fn write<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        self.span
+            .in_scope(|| ::tracing::debug_span!(target: "gcj_helper", "write").in_scope(f))
+    }
+
+    /// Records the number of bytes parsed and the time taken by this test case.
+    pub fn record(&self, parse_bytes: usize, time: &CaseTime) {
+        let duration = time.parse + time.solve;
+        let _ = self.span.record("parse_bytes", parse_bytes as u64);
+        let _ = self.span.record("duration_us", duration.as_micros() as u64);
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+impl CaseSpan {
+    /// Creates the span for a test case.
+    pub fn new(_: usize) -> CaseSpan {
+        CaseSpan
+    }
+
+    /// Calls `f` within a `parse` span for this test case.
+    pub fn parse<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        f()
+    }
+
+    /// Calls `f` within a `solve` span for this test case.
+    pub fn solve<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        f()
+    }
+
+    /// Calls `f` within a `write` span for this test case.
+    pub fn write<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        f()
+    }
+
+    /// Records the number of bytes parsed and the time taken by this test case.
+    pub fn record(&self, _: usize, _: &CaseTime) {}
+}
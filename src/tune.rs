@@ -0,0 +1,167 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Choosing the number of threads used in parallel mode by timing a sample of test cases.
+
+use super::{Input, Output, TestEngine};
+#[cfg(feature = "parallel")]
+use super::{format_duration, manifest, paths, Manifest};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "parallel")]
+use rayon::{Configuration, ThreadPool};
+#[cfg(feature = "parallel")]
+use std::fs;
+use std::path::PathBuf;
+#[cfg(feature = "parallel")]
+use std::thread;
+#[cfg(feature = "parallel")]
+use std::time::Instant;
+
+/// How `TestEngine::auto_tune()` chooses the number of threads used in parallel mode.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AutoTune {
+    /// The number of test cases timed with each candidate thread count.
+    sample: usize,
+    /// The manifest file the chosen thread count is remembered in, if any.
+    manifest: Option<PathBuf>,
+}
+
+impl AutoTune {
+    /// Creates a configuration timing `sample` test cases, spread evenly across the input file,
+    /// with each candidate thread count.
+    ///
+    /// The candidates are one thread, where the sample is solved sequentially, and powers of two
+    /// up to the number of logical CPUs, which is always included. The sample should contain at
+    /// least as many test cases as there are logical CPUs, or the larger thread counts are left
+    /// partly idle.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `sample` is zero.
+    pub fn new(sample: usize) -> AutoTune {
+        assert!(sample > 0, "auto-tuning must sample at least one test case");
+        AutoTune {
+            sample: sample,
+            manifest: None,
+        }
+    }
+
+    /// Remembers the chosen thread count in a manifest file, such as `gcj.toml`, so that later
+    /// runs of the same problem skip the sampling.
+    ///
+    /// If the manifest already sets `threads`, that thread count is used without sampling;
+    /// otherwise, once the fastest configuration has been chosen, a `threads` key is added to the
+    /// manifest, which is created if it does not exist. The rest of the manifest is kept intact.
+    /// Delete the key to tune again, e.g. after changing the solver.
+    pub fn remember<P: Into<PathBuf>>(mut self, manifest: P) -> AutoTune {
+        self.manifest = Some(manifest.into());
+        self
+    }
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Chooses the number of threads used in parallel mode by timing a sample of test cases
+    /// before the run, as described by `tune`.
+    ///
+    /// Once every test case has been parsed, the sampled test cases are solved sequentially and
+    /// then with each candidate thread count, and the fastest configuration is used for the run,
+    /// overriding `TestEngine::threads()`. The choice and the time taken by each candidate are
+    /// written to standard error. Since the sampled test cases are solved once per candidate and
+    /// again during the run, the solver should have no side effects besides its test result.
+    ///
+    /// This setting has no effect unless the `parallel` feature is enabled.
+    ///
+    /// # Panics
+    ///
+    /// The run panics if the thread count cannot be remembered in the manifest file given to
+    /// `AutoTune::remember()`.
+    pub fn auto_tune(mut self, tune: AutoTune) -> TestEngine<I, O> {
+        self.settings.auto_tune = Some(tune);
+        self
+    }
+}
+
+/// Chooses the number of threads used to solve `cases` as described by `tune`, by timing `solve`
+/// on a sample of them with each candidate thread count, unless a thread count has been
+/// remembered.
+///
+/// # Panics
+///
+/// This function panics if the chosen thread count cannot be remembered in the manifest file.
+#[cfg(feature = "parallel")]
+pub fn choose_threads<T: Sync, F: Fn(usize, &T) + Sync>(
+    tune: &AutoTune,
+    cases: &[T],
+    solve: F,
+) -> usize {
+    let max = thread::available_parallelism().map_or(1, |n| n.get());
+    if cases.is_empty() {
+        return max;
+    }
+    let manifest = tune.manifest.as_ref().map(|path| paths::expand_home(path));
+    if let Some(ref path) = manifest {
+        let remembered = fs::read_to_string(path)
+            .ok()
+            .and_then(|source| source.parse::<Manifest>().ok())
+            .and_then(|manifest| manifest.threads);
+        if let Some(threads) = remembered {
+            eprintln!(
+                "auto-tune: using {} threads, as remembered in `{}`",
+                threads,
+                path.display()
+            );
+            return threads;
+        }
+    }
+    let step = (cases.len() / tune.sample).max(1);
+    let sample: Vec<(usize, &T)> = cases
+        .iter()
+        .enumerate()
+        .step_by(step)
+        .take(tune.sample)
+        .collect();
+    let mut candidates = vec![1];
+    while candidates[candidates.len() - 1] < max {
+        let next = (candidates[candidates.len() - 1] * 2).min(max);
+        candidates.push(next);
+    }
+    let mut timings = Vec::with_capacity(0);
+    timings.reserve_exact(candidates.len());
+    for &threads in &candidates {
+        let start = Instant::now();
+        if threads == 1 {
+            for &(i, d) in &sample {
+                solve(i, d);
+            }
+        } else {
+            let config = Configuration::new().set_num_threads(threads);
+            ThreadPool::new(config)
+                .expect("could not create thread pool")
+                .install(|| sample.par_iter().for_each(|&(i, d)| solve(i, d)));
+        }
+        timings.push((threads, start.elapsed()));
+    }
+    let best = timings
+        .iter()
+        .min_by_key(|&&(_, time)| time)
+        .map_or(1, |&(threads, _)| threads);
+    let timings: Vec<String> = timings
+        .iter()
+        .map(|&(threads, time)| format!("{}: {}", threads, format_duration(time)))
+        .collect();
+    eprintln!(
+        "auto-tune: using {} threads ({} over {} test cases)",
+        best,
+        timings.join(", "),
+        sample.len()
+    );
+    if let Some(path) = manifest {
+        manifest::remember_threads(&path, best).expect("could not update manifest file");
+    }
+    best
+}
@@ -0,0 +1,94 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Coordinate compression.
+
+/// A sorted, deduplicated set of values, mapping each value to its rank.
+///
+/// This is the usual first step of sweep-line and offline algorithms, replacing sparse values
+/// (e.g. coordinates up to 10^18) with dense indices `0..len()`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Compression<T: Ord> {
+    /// The distinct values, in ascending order.
+    values: Vec<T>,
+}
+
+impl<T: Ord + Clone> Compression<T> {
+    /// Creates a compression of the distinct values in `values`.
+    pub fn new(values: &[T]) -> Compression<T> {
+        let mut values = values.to_vec();
+        values.sort_unstable();
+        values.dedup();
+        Compression { values: values }
+    }
+}
+
+impl<T: Ord> Compression<T> {
+    /// Returns the number of distinct values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the distinct values, in ascending order.
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Returns the value with the given index.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `index` is not less than `len()`.
+    pub fn value(&self, index: usize) -> &T {
+        &self.values[index]
+    }
+
+    /// Returns the index of `value`, or `None` if it is not one of the compressed values.
+    pub fn index_of(&self, value: &T) -> Option<usize> {
+        self.values.binary_search(value).ok()
+    }
+
+    /// Returns the index of `value`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `value` is not one of the compressed values.
+    pub fn index(&self, value: &T) -> usize {
+        self.index_of(value)
+            .expect("value is not one of the compressed values")
+    }
+
+    /// Returns the number of compressed values less than `value`, which need not be one of the
+    /// compressed values.
+    ///
+    /// This is the index of the first compressed value greater than or equal to `value`.
+    pub fn rank(&self, value: &T) -> usize {
+        self.values.partition_point(|v| v < value)
+    }
+
+    /// Returns the index of each of `values`, each of which must be one of the compressed values.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if any value is not one of the compressed values.
+    pub fn indices(&self, values: &[T]) -> Vec<usize> {
+        values.iter().map(|v| self.index(v)).collect()
+    }
+}
+
+/// Compresses `values`, returning the sorted, deduplicated values along with the index of each
+/// of `values` among them.
+pub fn compress<T: Ord + Clone>(values: &[T]) -> (Compression<T>, Vec<usize>) {
+    let compression = Compression::new(values);
+    let indices = compression.indices(values);
+    (compression, indices)
+}
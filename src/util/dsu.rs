@@ -0,0 +1,123 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Disjoint-set forests.
+
+/// A disjoint-set forest (union-find) over the elements `0..n`, with path compression and union
+/// by size.
+///
+/// Every operation takes amortised near-constant time.
+#[derive(Clone, Debug)]
+pub struct Dsu {
+    /// The parent of each element; a root is its own parent.
+    parent: Vec<usize>,
+    /// The number of elements in the set rooted at each element, or zero for non-roots.
+    size: Vec<usize>,
+    /// The number of disjoint sets.
+    sets: usize,
+}
+
+impl Dsu {
+    /// Creates a forest of `n` singleton sets, `{0}, {1}, ..., {n - 1}`.
+    pub fn new(n: usize) -> Dsu {
+        Dsu {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            sets: n,
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    /// Returns `true` if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Returns the representative of the set containing `x`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `x` is not an element.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut x = x;
+        while self.parent[x] != root {
+            let next = self.parent[x];
+            self.parent[x] = root;
+            x = next;
+        }
+        root
+    }
+
+    /// Merges the sets containing `a` and `b`, returning `true` if they were previously disjoint.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if either `a` or `b` is not an element.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            ::std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.size[b] = 0;
+        self.sets -= 1;
+        true
+    }
+
+    /// Returns `true` if `a` and `b` are in the same set.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if either `a` or `b` is not an element.
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Returns the number of elements in the set containing `x`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `x` is not an element.
+    pub fn set_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    /// Returns the number of disjoint sets.
+    pub fn set_count(&self) -> usize {
+        self.sets
+    }
+
+    /// Returns every set, each as a list of its elements in ascending order, ordered by their
+    /// smallest element.
+    pub fn sets(&mut self) -> Vec<Vec<usize>> {
+        let mut index = vec![usize::MAX; self.len()];
+        let mut sets: Vec<Vec<usize>> = Vec::with_capacity(0);
+        sets.reserve_exact(self.sets);
+        for x in 0..self.len() {
+            let root = self.find(x);
+            if index[root] == usize::MAX {
+                index[root] = sets.len();
+                sets.push(Vec::new());
+            }
+            sets[index[root]].push(x);
+        }
+        sets
+    }
+}
@@ -0,0 +1,270 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Two-dimensional grids.
+
+use super::super::{GridAnswer, GridCell, InputReader};
+use std::ops::{Index, IndexMut};
+
+/// The offsets of the four orthogonally adjacent cells, in the order up, left, right, down.
+const OFFSETS_4: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// The offsets of the eight adjacent cells, including diagonals, in row-major order.
+const OFFSETS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rectangular grid of cells, indexed by `(row, col)`.
+///
+/// Cells are stored contiguously in row-major order. Grids of bytes are commonly read from an
+/// input file with `Grid::read()`, or converted from the result of `InputReader::read_grid()`;
+/// grids containing non-ASCII symbols can be read as characters with `Grid::read_chars()`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Grid<T> {
+    /// The number of rows.
+    rows: usize,
+    /// The number of columns.
+    cols: usize,
+    /// The cells, in row-major order.
+    cells: Vec<T>,
+}
+
+/// An iterator over the positions of the cells adjacent to a cell, within the bounds of a grid.
+#[derive(Clone, Debug)]
+pub struct Neighbors {
+    /// The position of the cell whose neighbours are yielded.
+    center: (usize, usize),
+    /// The number of rows in the grid.
+    rows: usize,
+    /// The number of columns in the grid.
+    cols: usize,
+    /// The offsets of the neighbours to yield.
+    offsets: &'static [(isize, isize)],
+    /// The index of the next offset to try.
+    next: usize,
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid with the given dimensions, with every cell set to `value`.
+    pub fn new(rows: usize, cols: usize, value: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            rows: rows,
+            cols: cols,
+            cells: vec![value; rows * cols],
+        }
+    }
+
+    /// Creates a grid from a list of rows.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the rows are not all the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Grid<T> {
+        let cols = rows.first().map_or(0, |row| row.len());
+        let row_count = rows.len();
+        let mut cells = Vec::with_capacity(0);
+        cells.reserve_exact(row_count * cols);
+        for (i, row) in rows.into_iter().enumerate() {
+            assert_eq!(row.len(), cols, "grid row {} has the wrong number of columns", i);
+            cells.extend(row);
+        }
+        Grid {
+            rows: row_count,
+            cols: cols,
+            cells: cells,
+        }
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns `true` if `(row, col)` lies within the grid.
+    ///
+    /// This accepts signed coordinates, so that positions computed by adding offsets can be
+    /// checked before they are converted to indices.
+    pub fn contains(&self, row: isize, col: isize) -> bool {
+        row >= 0 && col >= 0 && (row as usize) < self.rows && (col as usize) < self.cols
+    }
+
+    /// Returns a reference to the cell at `(row, col)`, or `None` if it lies outside the grid.
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row < self.rows && col < self.cols {
+            Some(&self.cells[row * self.cols + col])
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the cell at `(row, col)`, or `None` if it lies outside the
+    /// grid.
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        if row < self.rows && col < self.cols {
+            Some(&mut self.cells[row * self.cols + col])
+        } else {
+            None
+        }
+    }
+
+    /// Returns the cells of a row.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the row lies outside the grid.
+    pub fn row(&self, row: usize) -> &[T] {
+        assert!(row < self.rows, "row {} is outside a grid of {} rows", row, self.rows);
+        &self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /// Returns an iterator over the positions of the up to four cells orthogonally adjacent to
+    /// `(row, col)` that lie within the grid.
+    pub fn neighbors4(&self, row: usize, col: usize) -> Neighbors {
+        self.neighbors(row, col, &OFFSETS_4)
+    }
+
+    /// Returns an iterator over the positions of the up to eight cells adjacent to `(row, col)`,
+    /// including diagonally, that lie within the grid.
+    pub fn neighbors8(&self, row: usize, col: usize) -> Neighbors {
+        self.neighbors(row, col, &OFFSETS_8)
+    }
+
+    /// Returns an iterator over the position and value of every cell, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let cols = self.cols;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| ((i / cols, i % cols), cell))
+    }
+
+    /// Returns the position of the first cell, in row-major order, satisfying `predicate`.
+    pub fn position<P: FnMut(&T) -> bool>(&self, predicate: P) -> Option<(usize, usize)> {
+        self.cells
+            .iter()
+            .position(predicate)
+            .map(|i| (i / self.cols, i % self.cols))
+    }
+
+    /// Creates a grid of the same dimensions by applying `f` to every cell.
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            rows: self.rows,
+            cols: self.cols,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Returns an iterator over the neighbours of a cell with the given offsets.
+    fn neighbors(&self, row: usize, col: usize, offsets: &'static [(isize, isize)]) -> Neighbors {
+        Neighbors {
+            center: (row, col),
+            rows: self.rows,
+            cols: self.cols,
+            offsets: offsets,
+            next: 0,
+        }
+    }
+}
+
+impl Grid<u8> {
+    /// Reads `rows` lines of text from the input file as a grid of bytes, one row per line.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the lines are not all the same length.
+    pub fn read(reader: &mut InputReader, rows: usize) -> Grid<u8> {
+        Grid::from_rows(reader.read_grid(rows))
+    }
+}
+
+impl Grid<char> {
+    /// Reads `rows` lines of text from the input file as a grid of characters, one row per line.
+    ///
+    /// Unlike `Grid::read()`, each `char` occupies one cell, for grids containing non-ASCII
+    /// symbols.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the lines do not all contain the same number of characters.
+    pub fn read_chars(reader: &mut InputReader, rows: usize) -> Grid<char> {
+        Grid::from_rows(reader.read_char_grid(rows))
+    }
+}
+
+impl<T> From<Vec<Vec<T>>> for Grid<T> {
+    fn from(rows: Vec<Vec<T>>) -> Grid<T> {
+        Grid::from_rows(rows)
+    }
+}
+
+impl<'a, T: GridCell> From<&'a Grid<T>> for GridAnswer<'a, T> {
+    fn from(grid: &'a Grid<T>) -> GridAnswer<'a, T> {
+        GridAnswer::from_rows((0..grid.rows).map(|row| grid.row(row)).collect())
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        assert!(
+            row < self.rows && col < self.cols,
+            "({}, {}) is outside a {}x{} grid",
+            row,
+            col,
+            self.rows,
+            self.cols
+        );
+        &self.cells[row * self.cols + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        assert!(
+            row < self.rows && col < self.cols,
+            "({}, {}) is outside a {}x{} grid",
+            row,
+            col,
+            self.rows,
+            self.cols
+        );
+        &mut self.cells[row * self.cols + col]
+    }
+}
+
+impl Iterator for Neighbors {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        while let Some(&(dr, dc)) = self.offsets.get(self.next) {
+            self.next += 1;
+            let row = self.center.0 as isize + dr;
+            let col = self.center.1 as isize + dc;
+            if row >= 0 && col >= 0 && (row as usize) < self.rows && (col as usize) < self.cols {
+                return Some((row as usize, col as usize));
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,106 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Rolling string hashes.
+
+use std::ops::Range;
+
+/// The two moduli of a double hash.
+const MODULI: [u64; 2] = [1_000_000_007, 998_244_353];
+/// The polynomial base used with each modulus.
+const BASES: [u64; 2] = [911_382_323, 972_663_749];
+
+/// A polynomial double hash of every prefix of a byte string, for comparing substrings in
+/// constant time.
+///
+/// Each hash is a pair of residues modulo two different primes, which makes an accidental
+/// collision between unequal substrings vanishingly unlikely. The bases are fixed, so hashes from
+/// different `RollingHash`es, or from `RollingHash::hash()`, can be compared with each other.
+#[derive(Clone, Debug)]
+pub struct RollingHash {
+    /// The hash of each prefix of the string, from the empty prefix to the whole string.
+    prefixes: Vec<[u64; 2]>,
+    /// The powers of each base, from zero up to the length of the string.
+    powers: Vec<[u64; 2]>,
+}
+
+impl RollingHash {
+    /// Computes the prefix hashes of `s`.
+    pub fn new<S: AsRef<[u8]>>(s: S) -> RollingHash {
+        let s = s.as_ref();
+        let mut prefixes = Vec::with_capacity(0);
+        let mut powers = Vec::with_capacity(0);
+        prefixes.reserve_exact(s.len() + 1);
+        powers.reserve_exact(s.len() + 1);
+        prefixes.push([0, 0]);
+        powers.push([1, 1]);
+        for (i, &b) in s.iter().enumerate() {
+            let (prefix, power) = (prefixes[i], powers[i]);
+            prefixes.push([push(prefix, b, 0), push(prefix, b, 1)]);
+            powers.push([
+                power[0] * BASES[0] % MODULI[0],
+                power[1] * BASES[1] % MODULI[1],
+            ]);
+        }
+        RollingHash {
+            prefixes: prefixes,
+            powers: powers,
+        }
+    }
+
+    /// Returns the hash of the whole of `s`, without storing its prefix hashes.
+    pub fn hash<S: AsRef<[u8]>>(s: S) -> (u64, u64) {
+        let hash = s
+            .as_ref()
+            .iter()
+            .fold([0, 0], |h, &b| [push(h, b, 0), push(h, b, 1)]);
+        (hash[0], hash[1])
+    }
+
+    /// Returns the length of the hashed string.
+    pub fn len(&self) -> usize {
+        self.prefixes.len() - 1
+    }
+
+    /// Returns `true` if the hashed string is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the hash of the substring occupying the byte range `range`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `range` is reversed or extends past the end of the string.
+    pub fn substring(&self, range: Range<usize>) -> (u64, u64) {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "substring {:?} is outside a string of length {}",
+            range,
+            self.len()
+        );
+        let (start, end) = (self.prefixes[range.start], self.prefixes[range.end]);
+        let power = self.powers[range.end - range.start];
+        let h = |i: usize| (end[i] + MODULI[i] - start[i] * power[i] % MODULI[i]) % MODULI[i];
+        (h(0), h(1))
+    }
+
+    /// Returns `true` if the substrings occupying the byte ranges `a` and `b` are (almost
+    /// certainly) equal.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if either range is reversed or extends past the end of the string.
+    pub fn substrings_equal(&self, a: Range<usize>, b: Range<usize>) -> bool {
+        a.len() == b.len() && self.substring(a) == self.substring(b)
+    }
+}
+
+/// Extends a hash by one byte, using the `i`th modulus and base.
+fn push(hash: [u64; 2], b: u8, i: usize) -> u64 {
+    (hash[i] * BASES[i] + u64::from(b) + 1) % MODULI[i]
+}
@@ -0,0 +1,115 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Basic number theory.
+//!
+//! These functions operate on `u64`s, using `u128` intermediates where a product could overflow.
+//! `ModInt` builds on them for arithmetic under a fixed modulus.
+
+/// Returns the greatest common divisor of `a` and `b`; `gcd(0, 0)` is 0.
+pub fn gcd(a: u64, b: u64) -> u64 {
+    let (mut a, mut b) = (a, b);
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+/// Returns the least common multiple of `a` and `b`; the result is 0 if either is 0.
+///
+/// # Panics
+///
+/// This function panics if the result does not fit in a `u64`.
+pub fn lcm(a: u64, b: u64) -> u64 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    (a / gcd(a, b))
+        .checked_mul(b)
+        .unwrap_or_else(|| panic!("lcm({}, {}) overflowed", a, b))
+}
+
+/// Returns `base` raised to the power `exponent`, modulo `modulus`.
+///
+/// # Panics
+///
+/// This function panics if `modulus` is zero.
+pub fn mod_pow(base: u64, exponent: u64, modulus: u64) -> u64 {
+    assert!(modulus != 0, "modulus must be non-zero");
+    let modulus = u128::from(modulus);
+    let mut base = u128::from(base) % modulus;
+    let mut exponent = exponent;
+    let mut result = 1 % modulus;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exponent >>= 1;
+    }
+    result as u64
+}
+
+/// Returns the multiplicative inverse of `a` modulo `modulus`, or `None` if `a` and `modulus` are
+/// not coprime.
+///
+/// The inverse is found with the extended Euclidean algorithm, so `modulus` need not be prime.
+///
+/// # Panics
+///
+/// This function panics if `modulus` is zero.
+pub fn mod_inverse(a: u64, modulus: u64) -> Option<u64> {
+    assert!(modulus != 0, "modulus must be non-zero");
+    let (mut a, mut b) = (i128::from(a % modulus), i128::from(modulus));
+    let (mut x, mut y) = (1i128, 0i128);
+    while b != 0 {
+        let q = a / b;
+        a -= q * b;
+        x -= q * y;
+        ::std::mem::swap(&mut a, &mut b);
+        ::std::mem::swap(&mut x, &mut y);
+    }
+    if a == 1 {
+        Some(x.rem_euclid(i128::from(modulus)) as u64)
+    } else {
+        None
+    }
+}
+
+/// Returns a table of length `n + 1` whose `i`th entry is `true` if `i` is prime, computed with
+/// the sieve of Eratosthenes.
+pub fn sieve(n: usize) -> Vec<bool> {
+    let mut is_prime = vec![true; n + 1];
+    is_prime[0] = false;
+    if n >= 1 {
+        is_prime[1] = false;
+    }
+    let mut i = 2;
+    while i * i <= n {
+        if is_prime[i] {
+            let mut j = i * i;
+            while j <= n {
+                is_prime[j] = false;
+                j += i;
+            }
+        }
+        i += 1;
+    }
+    is_prime
+}
+
+/// Returns every prime less than or equal to `n`, in ascending order.
+pub fn primes(n: usize) -> Vec<usize> {
+    sieve(n)
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, is_prime)| is_prime)
+        .map(|(i, _)| i)
+        .collect()
+}
@@ -0,0 +1,30 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Data structures and algorithms that contest solutions commonly need.
+//!
+//! This module is only available if the `util` feature is enabled.
+
+mod compress;
+mod dsu;
+mod grid;
+mod hash;
+pub mod math;
+mod modint;
+mod prefix;
+mod rational;
+mod search;
+
+pub use self::compress::{compress, Compression};
+pub use self::dsu::Dsu;
+pub use self::grid::{Grid, Neighbors};
+pub use self::hash::RollingHash;
+pub use self::modint::{ModInt, ModInt1000000007, ModInt998244353};
+pub use self::prefix::{PrefixSum, PrefixSum2d};
+pub use self::rational::Rational;
+pub use self::search::{binary_search_answer, binary_search_answer_f64, binary_search_max,
+                       FLOAT_ITERATIONS};
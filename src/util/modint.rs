@@ -0,0 +1,200 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Integers modulo a constant.
+
+use super::super::{FromInput, InputReader};
+use super::math::mod_inverse;
+use std::fmt::{self, Display, Formatter};
+use std::iter::{Product, Sum};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// An integer modulo `M`, for problems asking for an answer "modulo 10^9 + 7" or similar.
+///
+/// Arithmetic operators wrap around modulo `M`; division multiplies by the modular inverse, and
+/// so panics if the divisor has no inverse (which, for prime `M`, only happens for zero). Values
+/// are written without any decoration, e.g. `5`, and read from a single token, which may be
+/// negative or larger than `M`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ModInt<const M: u64> {
+    /// The value, in the range `[0, M)`.
+    value: u64,
+}
+
+/// An integer modulo 10^9 + 7.
+pub type ModInt1000000007 = ModInt<1_000_000_007>;
+
+/// An integer modulo 998244353.
+pub type ModInt998244353 = ModInt<998_244_353>;
+
+impl<const M: u64> ModInt<M> {
+    /// Creates an integer modulo `M` from `value`, reducing it if necessary.
+    pub fn new(value: u64) -> ModInt<M> {
+        ModInt { value: value % M }
+    }
+
+    /// Creates an integer modulo `M` from a signed value, reducing it if necessary.
+    pub fn from_signed(value: i128) -> ModInt<M> {
+        ModInt {
+            value: value.rem_euclid(i128::from(M)) as u64,
+        }
+    }
+
+    /// Returns the value, in the range `[0, M)`.
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    /// Raises this value to the power of `exponent`.
+    pub fn pow(self, mut exponent: u64) -> ModInt<M> {
+        let mut base = self;
+        let mut result = ModInt::new(1);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            exponent >>= 1;
+        }
+        result
+    }
+
+    /// Returns the multiplicative inverse of this value, or `None` if it has none (i.e. this value
+    /// and `M` are not coprime).
+    pub fn checked_inv(self) -> Option<ModInt<M>> {
+        mod_inverse(self.value, M).map(ModInt::new)
+    }
+
+    /// Returns the multiplicative inverse of this value.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if this value has no inverse modulo `M`.
+    pub fn inv(self) -> ModInt<M> {
+        self.checked_inv()
+            .unwrap_or_else(|| panic!("{} has no inverse modulo {}", self.value, M))
+    }
+}
+
+impl<const M: u64> From<u64> for ModInt<M> {
+    fn from(value: u64) -> ModInt<M> {
+        ModInt::new(value)
+    }
+}
+
+impl<const M: u64> From<i64> for ModInt<M> {
+    fn from(value: i64) -> ModInt<M> {
+        ModInt::from_signed(i128::from(value))
+    }
+}
+
+impl<const M: u64> From<usize> for ModInt<M> {
+    fn from(value: usize) -> ModInt<M> {
+        ModInt::new(value as u64)
+    }
+}
+
+impl<const M: u64> Display for ModInt<M> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Display::fmt(&self.value, f)
+    }
+}
+
+impl<const M: u64> FromInput for ModInt<M> {
+    fn from_input(reader: &mut InputReader) -> ModInt<M> {
+        let token = reader.read_token();
+        match token.parse::<i128>() {
+            Ok(value) => ModInt::from_signed(value),
+            Err(_) => panic!("could not parse {:?} as an integer modulo {}", token, M),
+        }
+    }
+}
+
+impl<const M: u64> Add for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn add(self, rhs: ModInt<M>) -> ModInt<M> {
+        let (sum, overflowed) = self.value.overflowing_add(rhs.value);
+        if overflowed || sum >= M {
+            ModInt {
+                value: sum.wrapping_sub(M),
+            }
+        } else {
+            ModInt { value: sum }
+        }
+    }
+}
+
+impl<const M: u64> Sub for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn sub(self, rhs: ModInt<M>) -> ModInt<M> {
+        if self.value >= rhs.value {
+            ModInt {
+                value: self.value - rhs.value,
+            }
+        } else {
+            ModInt {
+                value: M - (rhs.value - self.value),
+            }
+        }
+    }
+}
+
+impl<const M: u64> Mul for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn mul(self, rhs: ModInt<M>) -> ModInt<M> {
+        ModInt {
+            value: (u128::from(self.value) * u128::from(rhs.value) % u128::from(M)) as u64,
+        }
+    }
+}
+
+impl<const M: u64> Div for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn div(self, rhs: ModInt<M>) -> ModInt<M> {
+        Mul::mul(self, rhs.inv())
+    }
+}
+
+impl<const M: u64> Neg for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn neg(self) -> ModInt<M> {
+        ModInt::new(0) - self
+    }
+}
+
+/// Implements a compound assignment operator in terms of the corresponding binary operator.
+macro_rules! impl_assign_op {
+    ($assign_trait:ident, $assign_fn:ident, $op:tt) => {
+        impl<const M: u64> $assign_trait for ModInt<M> {
+            fn $assign_fn(&mut self, rhs: ModInt<M>) {
+                *self = *self $op rhs;
+            }
+        }
+    };
+}
+
+impl_assign_op!(AddAssign, add_assign, +);
+impl_assign_op!(SubAssign, sub_assign, -);
+impl_assign_op!(MulAssign, mul_assign, *);
+impl_assign_op!(DivAssign, div_assign, /);
+
+impl<const M: u64> Sum for ModInt<M> {
+    fn sum<I: Iterator<Item = ModInt<M>>>(iter: I) -> ModInt<M> {
+        iter.fold(ModInt::new(0), |a, b| a + b)
+    }
+}
+
+impl<const M: u64> Product for ModInt<M> {
+    fn product<I: Iterator<Item = ModInt<M>>>(iter: I) -> ModInt<M> {
+        iter.fold(ModInt::new(1), |a, b| a * b)
+    }
+}
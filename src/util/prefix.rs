@@ -0,0 +1,147 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Prefix sums.
+
+use super::Grid;
+use std::ops::{Add, Sub};
+
+/// The prefix sums of a sequence, for summing any contiguous range in constant time.
+///
+/// Any type with zero as its `Default` value and well-behaved addition and subtraction can be
+/// summed, such as the primitive integers or `ModInt`. Ranges are inclusive at both ends.
+#[derive(Clone, Debug)]
+pub struct PrefixSum<T> {
+    /// The sum of the first `i` values at index `i`, from zero values up to all of them.
+    sums: Vec<T>,
+}
+
+/// The two-dimensional prefix sums of a grid, for summing any rectangle in constant time.
+///
+/// Any type with zero as its `Default` value and well-behaved addition and subtraction can be
+/// summed. Rectangles are given by their inclusive top-left and bottom-right corners.
+#[derive(Clone, Debug)]
+pub struct PrefixSum2d<T> {
+    /// The number of columns in the grid, plus one.
+    stride: usize,
+    /// The sum of the first `r` rows and `c` columns at index `r * stride + c`.
+    sums: Vec<T>,
+}
+
+impl<T: Copy + Default + Add<Output = T> + Sub<Output = T>> PrefixSum<T> {
+    /// Computes the prefix sums of `values`.
+    pub fn new(values: &[T]) -> PrefixSum<T> {
+        let mut sums = Vec::with_capacity(0);
+        sums.reserve_exact(values.len() + 1);
+        let mut total = T::default();
+        sums.push(total);
+        for &value in values {
+            total = total + value;
+            sums.push(total);
+        }
+        PrefixSum { sums: sums }
+    }
+
+    /// Returns the number of values.
+    pub fn len(&self) -> usize {
+        self.sums.len() - 1
+    }
+
+    /// Returns `true` if there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the sum of the values with indices `first` to `last` inclusive, or zero if `first`
+    /// is greater than `last`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the range is non-empty and `last` is not less than `len()`.
+    pub fn sum(&self, first: usize, last: usize) -> T {
+        if first > last {
+            return T::default();
+        }
+        assert!(
+            last < self.len(),
+            "index {} is outside a sequence of length {}",
+            last,
+            self.len()
+        );
+        self.sums[last + 1] - self.sums[first]
+    }
+
+    /// Returns the sum of all the values.
+    pub fn total(&self) -> T {
+        self.sums[self.len()]
+    }
+}
+
+impl<T: Copy + Default + Add<Output = T> + Sub<Output = T>> PrefixSum2d<T> {
+    /// Computes the prefix sums of a grid given as a list of rows.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the rows are not all the same length.
+    pub fn new(rows: &[Vec<T>]) -> PrefixSum2d<T> {
+        let cols = rows.first().map_or(0, |row| row.len());
+        let stride = cols + 1;
+        let mut sums = vec![T::default(); (rows.len() + 1) * stride];
+        for (r, row) in rows.iter().enumerate() {
+            assert_eq!(row.len(), cols, "grid row {} has the wrong number of columns", r);
+            for (c, &value) in row.iter().enumerate() {
+                let (above, here) = (r * stride + c, (r + 1) * stride + c);
+                sums[here + 1] = sums[here] + sums[above + 1] - sums[above] + value;
+            }
+        }
+        PrefixSum2d {
+            stride: stride,
+            sums: sums,
+        }
+    }
+
+    /// Computes the prefix sums of a `Grid`.
+    pub fn from_grid(grid: &Grid<T>) -> PrefixSum2d<T> {
+        let rows: Vec<Vec<T>> = (0..grid.rows()).map(|r| grid.row(r).to_vec()).collect();
+        PrefixSum2d::new(&rows)
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {
+        self.sums.len() / self.stride - 1
+    }
+
+    /// Returns the number of columns.
+    pub fn cols(&self) -> usize {
+        self.stride - 1
+    }
+
+    /// Returns the sum of the rectangle with top-left corner `(first_row, first_col)` and
+    /// bottom-right corner `(last_row, last_col)`, inclusive, or zero if the rectangle is empty.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the rectangle is non-empty and extends outside the grid.
+    pub fn sum(&self, first_row: usize, first_col: usize, last_row: usize, last_col: usize) -> T {
+        if first_row > last_row || first_col > last_col {
+            return T::default();
+        }
+        assert!(
+            last_row < self.rows() && last_col < self.cols(),
+            "({}, {}) is outside a {}x{} grid",
+            last_row,
+            last_col,
+            self.rows(),
+            self.cols()
+        );
+        let at = |r: usize, c: usize| self.sums[r * self.stride + c];
+        // Adding before subtracting keeps every intermediate value non-negative for unsigned types.
+        at(last_row + 1, last_col + 1) + at(first_row, first_col)
+            - at(first_row, last_col + 1)
+            - at(last_row + 1, first_col)
+    }
+}
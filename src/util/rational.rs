@@ -0,0 +1,284 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Exact rational numbers.
+
+use super::super::{FromInput, InputReader};
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// An exact rational number, for geometry and probability problems where comparing floating-point
+/// numbers is unsafe.
+///
+/// The numerator and denominator are stored as `i128`s, always in lowest terms with a positive
+/// denominator. Arithmetic operators panic on overflow, like integer arithmetic in debug builds;
+/// the `checked_*` methods return `None` instead. Comparison is exact and never overflows.
+///
+/// Rationals are written as `"p/q"`, e.g. `"3/4"` or `"-2/1"`, and are read from a single token
+/// written either that way, as an integer, or as a decimal number such as `"0.125"`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Rational {
+    /// The numerator.
+    num: i128,
+    /// The denominator, which is always positive.
+    den: i128,
+}
+
+impl Rational {
+    /// Creates the rational number `num / den`, reduced to lowest terms.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `den` is zero, or if the result cannot be represented.
+    pub fn new(num: i128, den: i128) -> Rational {
+        Rational::checked_new(num, den)
+            .expect("rational number has a zero denominator or overflowed")
+    }
+
+    /// Creates the rational number `num / den`, reduced to lowest terms, or returns `None` if
+    /// `den` is zero or the result cannot be represented.
+    pub fn checked_new(num: i128, den: i128) -> Option<Rational> {
+        if den == 0 {
+            return None;
+        }
+        let g = gcd(num, den);
+        let (mut num, mut den) = (num / g, den / g);
+        if den < 0 {
+            num = num.checked_neg()?;
+            den = den.checked_neg()?;
+        }
+        Some(Rational { num: num, den: den })
+    }
+
+    /// Creates a rational number equal to the integer `n`.
+    pub fn from_integer(n: i128) -> Rational {
+        Rational { num: n, den: 1 }
+    }
+
+    /// Returns the numerator, in lowest terms.
+    pub fn numer(self) -> i128 {
+        self.num
+    }
+
+    /// Returns the denominator, in lowest terms; this is always positive.
+    pub fn denom(self) -> i128 {
+        self.den
+    }
+
+    /// Returns the nearest `f64` to this number.
+    pub fn to_f64(self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    /// Returns the largest integer less than or equal to this number.
+    pub fn floor(self) -> i128 {
+        self.num.div_euclid(self.den)
+    }
+
+    /// Returns the smallest integer greater than or equal to this number.
+    pub fn ceil(self) -> i128 {
+        let floor = self.floor();
+        if self.num.rem_euclid(self.den) == 0 {
+            floor
+        } else {
+            floor + 1
+        }
+    }
+
+    /// Returns the absolute value of this number.
+    pub fn abs(self) -> Rational {
+        Rational {
+            num: self.num.abs(),
+            den: self.den,
+        }
+    }
+
+    /// Returns the reciprocal of this number, or `None` if this number is zero.
+    pub fn checked_recip(self) -> Option<Rational> {
+        Rational::checked_new(self.den, self.num)
+    }
+
+    /// Adds two rational numbers, returning `None` on overflow.
+    pub fn checked_add(self, rhs: Rational) -> Option<Rational> {
+        let g = gcd(self.den, rhs.den);
+        let left = self.num.checked_mul(rhs.den / g)?;
+        let right = rhs.num.checked_mul(self.den / g)?;
+        Rational::checked_new(left.checked_add(right)?, (self.den / g).checked_mul(rhs.den)?)
+    }
+
+    /// Subtracts two rational numbers, returning `None` on overflow.
+    pub fn checked_sub(self, rhs: Rational) -> Option<Rational> {
+        self.checked_add(Rational {
+            num: rhs.num.checked_neg()?,
+            den: rhs.den,
+        })
+    }
+
+    /// Multiplies two rational numbers, returning `None` on overflow.
+    pub fn checked_mul(self, rhs: Rational) -> Option<Rational> {
+        let g1 = gcd(self.num, rhs.den);
+        let g2 = gcd(rhs.num, self.den);
+        Rational::checked_new(
+            (self.num / g1).checked_mul(rhs.num / g2)?,
+            (self.den / g2).checked_mul(rhs.den / g1)?,
+        )
+    }
+
+    /// Divides two rational numbers, returning `None` on overflow or division by zero.
+    pub fn checked_div(self, rhs: Rational) -> Option<Rational> {
+        self.checked_mul(rhs.checked_recip()?)
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Rational {
+        Rational::from_integer(i128::from(n))
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.num, self.den)
+    }
+}
+
+impl FromStr for Rational {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Rational, String> {
+        let invalid = || format!("could not parse {:?} as a rational number", s);
+        if let Some(i) = s.find('/') {
+            let num = s[..i].parse().map_err(|_| invalid())?;
+            let den = s[i + 1..].parse().map_err(|_| invalid())?;
+            return Rational::checked_new(num, den).ok_or_else(invalid);
+        }
+        let (int_part, frac_part) = match s.find('.') {
+            Some(i) => (&s[..i], &s[i + 1..]),
+            None => (s, ""),
+        };
+        if !frac_part.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let (negative, int_part) = match int_part.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, int_part.strip_prefix('+').unwrap_or(int_part)),
+        };
+        let digits = format!("{}{}", int_part, frac_part);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let mut num: i128 = digits.parse().map_err(|_| invalid())?;
+        if negative {
+            num = -num;
+        }
+        let den = 10i128
+            .checked_pow(frac_part.len() as u32)
+            .ok_or_else(invalid)?;
+        Rational::checked_new(num, den).ok_or_else(invalid)
+    }
+}
+
+impl FromInput for Rational {
+    fn from_input(reader: &mut InputReader) -> Rational {
+        let token = reader.read_token();
+        token.parse().unwrap_or_else(|e: String| panic!("{}", e))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Rational) -> Ordering {
+        // Compares continued fraction expansions, so that no intermediate value overflows.
+        let (mut a, mut b, mut c, mut d) = (self.num, self.den, other.num, other.den);
+        let mut reversed = false;
+        loop {
+            let (q1, q2) = (a.div_euclid(b), c.div_euclid(d));
+            if q1 != q2 {
+                let ordering = q1.cmp(&q2);
+                return if reversed { ordering.reverse() } else { ordering };
+            }
+            let (r1, r2) = (a.rem_euclid(b), c.rem_euclid(d));
+            match (r1 == 0, r2 == 0) {
+                (true, true) => return Ordering::Equal,
+                (true, false) => {
+                    return if reversed { Ordering::Greater } else { Ordering::Less };
+                }
+                (false, true) => {
+                    return if reversed { Ordering::Less } else { Ordering::Greater };
+                }
+                (false, false) => {}
+            }
+            // a/b = q + r1/b, so comparing a/b with c/d reduces to comparing b/r1 with d/r2 in
+            // the opposite direction.
+            a = b;
+            b = r1;
+            c = d;
+            d = r2;
+            reversed = !reversed;
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Rational) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Implements an arithmetic operator and its compound assignment form in terms of a checked
+/// method, panicking on overflow.
+macro_rules! impl_op {
+    ($op_trait:ident, $op_fn:ident, $assign_trait:ident, $assign_fn:ident, $checked:ident) => {
+        impl $op_trait for Rational {
+            type Output = Rational;
+
+            fn $op_fn(self, rhs: Rational) -> Rational {
+                self.$checked(rhs).unwrap_or_else(|| {
+                    panic!("rational arithmetic overflowed or divided by zero")
+                })
+            }
+        }
+
+        impl $assign_trait for Rational {
+            fn $assign_fn(&mut self, rhs: Rational) {
+                *self = $op_trait::$op_fn(*self, rhs);
+            }
+        }
+    };
+}
+
+impl_op!(Add, add, AddAssign, add_assign, checked_add);
+impl_op!(Sub, sub, SubAssign, sub_assign, checked_sub);
+impl_op!(Mul, mul, MulAssign, mul_assign, checked_mul);
+impl_op!(Div, div, DivAssign, div_assign, checked_div);
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational {
+            num: self.num.checked_neg().expect("rational arithmetic overflowed"),
+            den: self.den,
+        }
+    }
+}
+
+/// Returns the greatest common divisor of `a` and `b`, which is positive unless both are zero;
+/// `gcd(0, 0)` is 1, so that dividing by it is always safe.
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    match a {
+        0 => 1,
+        a => a as i128,
+    }
+}
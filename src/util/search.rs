@@ -0,0 +1,71 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Binary search over the answer.
+
+/// The number of bisections performed by `binary_search_answer_f64()` when asked for a fixed
+/// precision, which is enough to exhaust the precision of an `f64` over any finite range.
+pub const FLOAT_ITERATIONS: u32 = 100;
+
+/// Returns the smallest integer `x` in `lo..hi` for which `predicate(x)` is `true`, or `hi` if
+/// there is none.
+///
+/// `predicate` must be monotonic: `false` for every integer below some threshold, and `true` from
+/// the threshold onwards. It is called `O(log(hi - lo))` times, never with `hi` itself.
+pub fn binary_search_answer<P: FnMut(i64) -> bool>(lo: i64, hi: i64, mut predicate: P) -> i64 {
+    let (mut lo, mut hi) = (lo, hi);
+    while lo < hi {
+        let mid = ((i128::from(lo) + i128::from(hi)) >> 1) as i64;
+        if predicate(mid) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    lo
+}
+
+/// Returns the largest integer `x` in `lo..hi` for which `predicate(x)` is `true`, or `None` if
+/// there is none.
+///
+/// `predicate` must be monotonic: `true` for every integer up to some threshold, and `false`
+/// afterwards. This is the mirror image of `binary_search_answer()`, for maximisation problems.
+pub fn binary_search_max<P: FnMut(i64) -> bool>(lo: i64, hi: i64, mut predicate: P) -> Option<i64> {
+    let first_false = binary_search_answer(lo, hi, |x| !predicate(x));
+    if first_false > lo {
+        Some(first_false - 1)
+    } else {
+        None
+    }
+}
+
+/// Returns an approximation of the threshold in `[lo, hi]` at which `predicate` becomes `true`,
+/// after bisecting the range `iterations` times.
+///
+/// `predicate` must be monotonic: `false` below the threshold, and `true` above it. Each
+/// iteration halves the uncertainty; `FLOAT_ITERATIONS` iterations reach the limit of `f64`
+/// precision. If `predicate` is `false` everywhere, the result approaches `hi`.
+pub fn binary_search_answer_f64<P: FnMut(f64) -> bool>(
+    lo: f64,
+    hi: f64,
+    iterations: u32,
+    mut predicate: P,
+) -> f64 {
+    let (mut lo, mut hi) = (lo, hi);
+    for _ in 0..iterations {
+        let mid = lo + (hi - lo) / 2.0;
+        if mid <= lo || mid >= hi {
+            break;
+        }
+        if predicate(mid) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    lo + (hi - lo) / 2.0
+}
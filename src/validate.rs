@@ -0,0 +1,92 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Structural validation of output files.
+
+/// Checks the structure of an output file against the input file it was produced from, returning
+/// a description of each problem found.
+///
+/// The input file must begin with the number of test cases, as in `CaseMode::Counted`. The output
+/// file is expected to contain exactly one `"Case #N:"` header per test case, numbered from 1 in
+/// strictly increasing order, with no text before the first header, and to end with a newline.
+/// Test results themselves are not checked. An empty result means the output file is
+/// structurally sound.
+pub fn validate_output(input: &str, output: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    let expected = input
+        .lines()
+        .next()
+        .and_then(|line| line.trim().parse::<usize>().ok());
+    if expected.is_none() {
+        problems.push("input file does not begin with a test case count".to_owned());
+    }
+    let mut last = 0;
+    for (i, line) in output.lines().enumerate() {
+        let line_number = i + 1;
+        let rest = match line.strip_prefix("Case #") {
+            Some(rest) => rest,
+            None => {
+                if last == 0 && !line.trim().is_empty() {
+                    problems.push(format!("line {}: text before the first header", line_number));
+                }
+                continue;
+            }
+        };
+        let case = match rest.split(':').next().and_then(|n| n.parse::<usize>().ok()) {
+            Some(case) if rest.contains(':') => case,
+            _ => {
+                problems.push(format!("line {}: malformed header {:?}", line_number, line));
+                continue;
+            }
+        };
+        if case <= last {
+            problems.push(format!(
+                "line {}: Case #{} follows Case #{}",
+                line_number, case, last
+            ));
+            continue;
+        }
+        if case > last + 1 {
+            problems.push(format!(
+                "line {}: {} missing before Case #{}",
+                line_number,
+                case_range(last + 1, case - 1),
+                case
+            ));
+        }
+        if let Some(expected) = expected {
+            if case > expected {
+                problems.push(format!(
+                    "line {}: extra Case #{}; the input file has {} test cases",
+                    line_number, case, expected
+                ));
+            }
+        }
+        last = case;
+    }
+    if let Some(expected) = expected {
+        if last < expected {
+            problems.push(format!(
+                "{} missing at the end of the output file",
+                case_range(last + 1, expected)
+            ));
+        }
+    }
+    if !output.is_empty() && !output.ends_with('\n') {
+        problems.push("output file does not end with a newline".to_owned());
+    }
+    problems
+}
+
+/// Describes an inclusive range of test cases, e.g. `"Case #3"` or `"Case #3 to #5"`.
+fn case_range(first: usize, last: usize) -> String {
+    if first == last {
+        format!("Case #{}", first)
+    } else {
+        format!("Case #{} to #{}", first, last)
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Detecting runs in which no test case has finished for a long time.
+
+use super::{activity, format_duration, logging, Input, Output, TestEngine};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Watches a run for stalls, if enabled.
+#[derive(Debug)]
+pub struct Watchdog {
+    /// If enabled, a channel that stops the watchdog thread when dropped, and the thread itself.
+    thread: Option<(Sender<()>, JoinHandle<()>)>,
+}
+
+impl<I: Input, O: Output> TestEngine<I, O> {
+    /// Sets how long the run may go without any test case finishing before the test cases still
+    /// being solved are reported.
+    ///
+    /// A watchdog thread checks the run a few times per interval. Whenever no test case has
+    /// finished for `interval`, it writes a warning to standard error listing each test case
+    /// being solved and how long it has been running, longest first, which makes a test case
+    /// stuck in an infinite loop obvious, even during a parallel run. The warning is repeated
+    /// after every further `interval` without progress. By default, there is no watchdog.
+    ///
+    /// This setting has no effect on WASI targets without threads.
+    pub fn stall_watchdog(mut self, interval: Duration) -> TestEngine<I, O> {
+        self.settings.stall_watchdog = Some(interval);
+        self
+    }
+}
+
+impl Watchdog {
+    /// Starts watching the run for stalls of the given length, if any, until `Watchdog::stop()`
+    /// is called.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the watchdog thread cannot be started.
+    pub fn start(interval: Option<Duration>) -> Watchdog {
+        // WASI targets without threads cannot run a watchdog thread.
+        let interval = match interval {
+            Some(interval) if !cfg!(all(target_os = "wasi", not(target_feature = "atomics"))) => {
+                interval
+            }
+            _ => return Watchdog { thread: None },
+        };
+        let (sender, receiver) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("gcj-helper stall watchdog".to_owned())
+            .spawn(move || {
+                let mut solved = activity::solved();
+                let mut last_progress = Instant::now();
+                loop {
+                    match receiver.recv_timeout(interval / 4) {
+                        Err(RecvTimeoutError::Timeout) => {}
+                        _ => return,
+                    }
+                    if activity::solved() != solved {
+                        solved = activity::solved();
+                        last_progress = Instant::now();
+                    } else if last_progress.elapsed() >= interval {
+                        stalled(last_progress.elapsed());
+                        last_progress = Instant::now();
+                    }
+                }
+            })
+            .expect("could not start stall watchdog thread");
+        Watchdog {
+            thread: Some((sender, thread)),
+        }
+    }
+
+    /// Stops watching the run.
+    pub fn stop(self) {
+        if let Some((sender, thread)) = self.thread {
+            drop(sender);
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Reports that no test case has finished for the given time.
+fn stalled(time: Duration) {
+    let warning = format!(
+        "no test case has finished in {} ({}); still running: {}",
+        format_duration(time),
+        activity::describe_progress(),
+        activity::describe_running(10)
+    );
+    eprintln!("warning: {}", warning);
+    logging::warn(&warning);
+}
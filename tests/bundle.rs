@@ -0,0 +1,51 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checks that `cargo gcj bundle` produces a single source file that compiles on its own.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn every_example_bundles_and_compiles() {
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let dir = env::temp_dir().join(format!("gcj-helper-bundle-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    for entry in fs::read_dir(&examples).unwrap() {
+        let example = entry.unwrap().path();
+        if example.extension() != Some("rs".as_ref()) {
+            continue;
+        }
+        let name = example.file_stem().unwrap().to_str().unwrap().to_owned();
+        let bundle = dir.join(format!("{}.rs", name));
+        let status = Command::new(env!("CARGO_BIN_EXE_cargo-gcj"))
+            .args(["gcj", "bundle"])
+            .arg(&example)
+            .arg(&bundle)
+            .status()
+            .unwrap();
+        assert!(status.success(), "could not bundle {}", example.display());
+        let output = Command::new(&rustc)
+            .arg("--crate-name")
+            .arg(name.replace('-', "_"))
+            .arg("-o")
+            .arg(dir.join(&name))
+            .arg(&bundle)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "bundle of {} does not compile:\n{}",
+            example.display(),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
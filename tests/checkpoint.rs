@@ -0,0 +1,100 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checks that checkpointed runs resume only from checkpoints saved for the same input file.
+
+extern crate gcj_helper;
+
+use gcj_helper::{Checkpoint, TestEngine};
+use std::env;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// The input file solved by every test.
+const INPUT: &str = "2\nx\ny\n";
+
+/// Returns a checkpoint file path unique to the given test.
+fn checkpoint_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("gcj-helper-checkpoint-{}-{}", name, std::process::id()))
+}
+
+/// Computes the 64-bit FNV-1a hash of some bytes.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Solves `INPUT` with a checkpoint at `path`, returning the output file and the number of times
+/// the solver was called.
+fn run_checkpointed(path: &PathBuf) -> (Vec<u8>, usize) {
+    let calls = AtomicUsize::new(0);
+    let mut output = Vec::new();
+    TestEngine::with_io(INPUT.as_bytes(), &mut output).run_checkpointed(
+        Checkpoint::new(path),
+        |reader| reader.read_next_line().to_owned(),
+        |line| {
+            let _ = calls.fetch_add(1, Ordering::SeqCst);
+            format!(" {}\n", line)
+        },
+    );
+    (output, calls.into_inner())
+}
+
+#[test]
+fn matching_checkpoint_is_resumed() {
+    let path = checkpoint_path("matching");
+    let saved = format!(
+        "gcj-helper checkpoint 1\ninput {:016x}\npending 2\ncase 1 16\n saved\nline two\n\n",
+        fnv1a(INPUT.as_bytes())
+    );
+    fs::write(&path, saved).unwrap();
+    let (output, calls) = run_checkpointed(&path);
+    assert_eq!(output, &b"Case #1: saved\nline two\nCase #2: y\n"[..]);
+    assert_eq!(calls, 1);
+    assert!(!path.exists());
+}
+
+#[test]
+fn mismatched_checkpoint_is_discarded() {
+    let path = checkpoint_path("mismatched");
+    let saved = format!(
+        "gcj-helper checkpoint 1\ninput {:016x}\npending\ncase 1 7\n saved\n\n",
+        fnv1a(b"2\nx\nz\n")
+    );
+    fs::write(&path, saved).unwrap();
+    let (output, calls) = run_checkpointed(&path);
+    assert_eq!(output, &b"Case #1: x\nCase #2: y\n"[..]);
+    assert_eq!(calls, 2);
+    assert!(!path.exists());
+}
+
+#[test]
+fn interrupted_run_resumes_with_multi_line_answers() {
+    let path = checkpoint_path("interrupted");
+    let crashed = panic::catch_unwind(AssertUnwindSafe(|| {
+        TestEngine::with_io(INPUT.as_bytes(), Vec::new())
+            .threads(1)
+            .run_checkpointed(
+                Checkpoint::new(&path).interval(Duration::from_secs(0)),
+                |reader| reader.read_next_line().to_owned(),
+                |line| match line.as_str() {
+                    "y" => panic!("simulated crash"),
+                    _ => format!(" {}\n{}\n", line, line),
+                },
+            );
+    }));
+    assert!(crashed.is_err());
+    assert!(path.exists());
+    let (output, calls) = run_checkpointed(&path);
+    assert_eq!(output, &b"Case #1: x\nx\nCase #2: y\n"[..]);
+    assert_eq!(calls, 1);
+    assert!(!path.exists());
+}
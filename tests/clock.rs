@@ -0,0 +1,55 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checks that time-based features read the clock set on the test engine.
+
+extern crate gcj_helper;
+
+use gcj_helper::{FakeClock, TestEngine};
+use std::env;
+use std::fs;
+use std::time::{Duration, UNIX_EPOCH};
+
+#[test]
+fn run_budget_is_measured_by_the_fake_clock() {
+    let clock = FakeClock::new(UNIX_EPOCH);
+    let mut output = Vec::new();
+    TestEngine::with_io(&b"6\n1\n2\n3\n4\n5\n6\n"[..], &mut output)
+        .clock(clock.clone())
+        .threads(1)
+        .run_budget(Duration::from_secs(3))
+        .placeholder_answer(" skipped\n")
+        .run(
+            |reader| reader.read_next_line().to_owned(),
+            |line| {
+                clock.advance(Duration::from_secs(1));
+                format!(" {}\n", line)
+            },
+        );
+    let expected: &[u8] = b"Case #1: 1\nCase #2: 2\nCase #3: 3\n\
+Case #4: skipped\nCase #5: skipped\nCase #6: skipped\n";
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn wall_time_is_measured_by_the_fake_clock() {
+    let clock = FakeClock::new(UNIX_EPOCH);
+    let path = env::temp_dir().join(format!("gcj-helper-clock-{}.json", std::process::id()));
+    TestEngine::with_io(&b"3\n1\n2\n3\n"[..], Vec::new())
+        .clock(clock.clone())
+        .json_report(&path)
+        .run(
+            |reader| {
+                clock.advance(Duration::from_millis(1500));
+                reader.read_next_line().to_owned()
+            },
+            |line| format!(" {}\n", line),
+        );
+    let report = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(report.contains("\"wall_time\": 4.500000,"), "{}", report);
+}
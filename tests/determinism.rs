@@ -0,0 +1,70 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checks that output files are identical byte for byte, regardless of platform or parallelism.
+
+extern crate gcj_helper;
+
+use gcj_helper::TestEngine;
+use std::thread;
+use std::time::Duration;
+
+/// Runs a parser and solver over an in-memory input file, returning the output file.
+fn run_to_bytes<F: Fn(&str) -> String + Sync>(input: &str, solve: F) -> Vec<u8> {
+    let mut output = Vec::new();
+    TestEngine::with_io(input.as_bytes(), &mut output)
+        .run(|reader| reader.read_next_line().to_owned(), |line| solve(line));
+    output
+}
+
+#[test]
+fn line_endings_and_numbers_are_platform_independent() {
+    let output = run_to_bytes("3\n1\n2\n3\n", |line| {
+        let n: u32 = line.parse().unwrap();
+        format!(" {} {:.3} {:e}\n", n * 1000, f64::from(n) / 3.0, f64::from(n) * 1e10)
+    });
+    let expected: &[u8] = b"Case #1: 1000 0.333 1e10\n\
+Case #2: 2000 0.667 2e10\n\
+Case #3: 3000 1.000 3e10\n";
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn output_stays_in_order_when_solved_out_of_order() {
+    let mut input = String::from("50\n");
+    for i in 0..50 {
+        input.push_str(&format!("{}\n", i));
+    }
+    let output = run_to_bytes(&input, |line| {
+        let n: u64 = line.parse().unwrap();
+        // Later test cases finish first, if they are solved in parallel.
+        thread::sleep(Duration::from_millis(50 - n));
+        format!(" {}\n", n * n)
+    });
+    let expected: String = (0..50u64)
+        .map(|n| format!("Case #{}: {}\n", n + 1, n * n))
+        .collect();
+    assert_eq!(output, expected.as_bytes());
+}
+
+#[test]
+fn carriage_returns_are_kept_by_default() {
+    let output = run_to_bytes("1\nabc\r\n", |line| format!(" {}\n", line));
+    assert_eq!(output, b"Case #1: abc\r\n");
+}
+
+#[test]
+fn carriage_returns_are_removed_when_normalizing() {
+    let mut output = Vec::new();
+    TestEngine::with_io(&b"2\nabc\r\ndef\r\n"[..], &mut output)
+        .normalize_newlines(true)
+        .run(
+            |reader| reader.read_next_line().to_owned(),
+            |line| format!(" {}\n{}\r\n", line, line.trim_end()),
+        );
+    assert_eq!(output, b"Case #1: abc\nabc\nCase #2: def\ndef\n");
+}
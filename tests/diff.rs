@@ -0,0 +1,36 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checks that `cargo gcj diff` pairs test cases by their case numbers.
+
+use std::env;
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn missing_case_does_not_offset_later_cases() {
+    let dir = env::temp_dir().join(format!("gcj-helper-diff-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let expected = dir.join("expected.out");
+    let actual = dir.join("actual.out");
+    fs::write(&expected, "Case #1: a\nCase #2: b\nCase #3: c\nCase #4: d\n").unwrap();
+    fs::write(&actual, "Case #1: a\nCase #3: c\nCase #4: x\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-gcj"))
+        .args(["gcj", "diff"])
+        .arg(&expected)
+        .arg(&actual)
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stdout.contains("Case #4:"), "{}", stdout);
+    assert!(!stdout.contains("Case #3:"), "{}", stdout);
+    assert!(stdout.contains(": Case #2\n"), "{}", stdout);
+    assert!(stderr.contains("1 of 4 cases differ, 1 missing, 0 extra"), "{}", stderr);
+}
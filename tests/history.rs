@@ -0,0 +1,57 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checks that the run history records the size and hash of every byte of the output file.
+
+extern crate gcj_helper;
+
+use gcj_helper::{RunRecord, TestEngine};
+use std::env;
+use std::fs;
+
+/// Computes the 64-bit FNV-1a hash of some bytes.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Solves the given input with the given separator, returning the output file and the runs
+/// recorded in the run history.
+fn run_with_separator(separator: &str, name: &str) -> (Vec<u8>, Vec<RunRecord>) {
+    let path = env::temp_dir().join(format!("gcj-helper-history-{}-{}", name, std::process::id()));
+    let mut output = Vec::new();
+    TestEngine::with_io(&b"3\nab\ncd\nef\n"[..], &mut output)
+        .case_separator(separator)
+        .run_history(&path)
+        .run(
+            |reader| reader.read_next_line().to_owned(),
+            |line| format!(" {}", line),
+        );
+    let history = RunRecord::load_history(&path);
+    fs::remove_file(&path).unwrap();
+    (output, history)
+}
+
+#[test]
+fn history_records_the_output_file() {
+    let (output, history) = run_with_separator("", "plain");
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].status, "ok");
+    assert_eq!(history[0].cases, 3);
+    assert_eq!(history[0].output_bytes, output.len());
+    assert_eq!(history[0].output_hash, fnv1a(&output));
+}
+
+#[test]
+fn history_hashes_case_separators() {
+    let (output, history) = run_with_separator("--\n", "separated");
+    assert_eq!(output, &b"Case #1: ab\n--\nCase #2: cd\n--\nCase #3: ef"[..]);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].output_bytes, output.len());
+    assert_eq!(history[0].output_hash, fnv1a(&output));
+}
@@ -0,0 +1,38 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checks that the input reader handles the edges of lines and input files.
+
+extern crate gcj_helper;
+
+use gcj_helper::TestEngine;
+
+#[test]
+fn peeking_past_a_final_line_without_a_newline_finds_nothing() {
+    let mut output = Vec::new();
+    TestEngine::with_io(&b"1\n5"[..], &mut output).run(
+        |reader| {
+            let line = reader.read_next_line().to_owned();
+            assert_eq!(reader.peek_token(), None);
+            assert!(reader.peek_n_tokens(2).is_empty());
+            assert!(reader.is_eof());
+            line
+        },
+        |line| format!(" {}\n", line),
+    );
+    assert_eq!(output, &b"Case #1: 5\n"[..]);
+}
+
+#[test]
+fn blank_lines_are_read_as_empty_lines() {
+    let mut output = Vec::new();
+    TestEngine::with_io(&b"2\n\nab\n"[..], &mut output).run(
+        |reader| reader.read_next_line().to_owned(),
+        |line| format!(" [{}]\n", line),
+    );
+    assert_eq!(output, &b"Case #1: []\nCase #2: [ab]\n"[..]);
+}
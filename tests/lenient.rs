@@ -0,0 +1,72 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checks that lenient runs resynchronise at the next test case after a malformed one.
+
+extern crate gcj_helper;
+
+use gcj_helper::{CaseBoundary, InputReader, TestEngine};
+
+/// Parses a line holding a single number.
+fn number(reader: &mut InputReader) -> u64 {
+    reader.read_next_line().trim().parse().unwrap()
+}
+
+/// Leniently solves an input file whose test cases are parsed by `p`, writing the sum of each
+/// test case's numbers.
+fn run_to_bytes<P: Fn(&mut InputReader) -> Vec<u64>>(
+    input: &str,
+    boundary: CaseBoundary,
+    p: P,
+) -> Vec<u8> {
+    let mut output = Vec::new();
+    TestEngine::with_io(input.as_bytes(), &mut output).run_lenient(boundary, p, |numbers| {
+        format!(" {}\n", numbers.iter().sum::<u64>())
+    });
+    output
+}
+
+#[test]
+fn fixed_line_counts_resynchronise() {
+    let output = run_to_bytes("3\n1\n2\nbad\n3\n3\n4\n", CaseBoundary::Lines(2), |reader| {
+        vec![number(reader), number(reader)]
+    });
+    assert_eq!(output, &b"Case #1: 3\nCase #2: MALFORMED\nCase #3: 7\n"[..]);
+}
+
+#[test]
+fn counted_lines_resynchronise() {
+    let counted = |reader: &mut InputReader| {
+        let n = number(reader);
+        (0..n).map(|_| number(reader)).collect()
+    };
+    let input = "3\n2\n1\n2\n3\n1\nx\n5\n1\n4\n";
+    let output = run_to_bytes(input, CaseBoundary::CountedLines, counted);
+    assert_eq!(output, &b"Case #1: 3\nCase #2: MALFORMED\nCase #3: 4\n"[..]);
+    let output = run_to_bytes("2\n?\n1\n5\n", CaseBoundary::CountedLines, counted);
+    assert_eq!(output, &b"Case #1: MALFORMED\nCase #2: 5\n"[..]);
+}
+
+#[test]
+fn blank_lines_resynchronise() {
+    let tokens = |reader: &mut InputReader| {
+        (0..2).map(|_| reader.read_token().parse().unwrap()).collect()
+    };
+    let output = run_to_bytes("3\n1\n2\n\nx\n7\n\n\n4\n5\n", CaseBoundary::BlankLine, tokens);
+    assert_eq!(output, &b"Case #1: 3\nCase #2: MALFORMED\nCase #3: 9\n"[..]);
+}
+
+#[test]
+fn blank_lines_read_by_the_parser_resynchronise() {
+    let lines = |reader: &mut InputReader| {
+        let numbers = vec![number(reader), number(reader)];
+        assert!(reader.read_next_line().is_empty());
+        numbers
+    };
+    let output = run_to_bytes("3\n1\n2\n\nx\n7\n\n\n4\n5\n\n", CaseBoundary::BlankLine, lines);
+    assert_eq!(output, &b"Case #1: 3\nCase #2: MALFORMED\nCase #3: 9\n"[..]);
+}
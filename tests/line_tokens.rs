@@ -0,0 +1,43 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checks that streaming the tokens of a line leaves the input reader on the following line.
+
+extern crate gcj_helper;
+
+use gcj_helper::TestEngine;
+
+#[test]
+fn exhausted_line_is_followed_by_the_next_line() {
+    let mut output = Vec::new();
+    TestEngine::with_io(&b"2\n1 2 3\nalice\n4  5\t\nbob\n"[..], &mut output).run(
+        |reader| {
+            let total: u64 = reader.line_tokens().map(|t| t.parse::<u64>().unwrap()).sum();
+            (total, reader.read_next_line().to_owned())
+        },
+        |&(total, ref name)| format!(" {} {}\n", name, total),
+    );
+    assert_eq!(output, &b"Case #1: alice 6\nCase #2: bob 9\n"[..]);
+}
+
+#[test]
+fn last_token_taken_is_followed_by_the_next_line() {
+    let mut output = Vec::new();
+    TestEngine::with_io(&b"2\n3\n1 2 3\nalice\n2\n4 5 \nbob\n"[..], &mut output).run(
+        |reader| {
+            let n = reader.read_next_line().parse::<usize>().unwrap();
+            let total: u64 = reader
+                .line_tokens()
+                .take(n)
+                .map(|t| t.parse::<u64>().unwrap())
+                .sum();
+            (total, reader.read_next_line().to_owned())
+        },
+        |&(total, ref name)| format!(" {} {}\n", name, total),
+    );
+    assert_eq!(output, &b"Case #1: alice 6\nCase #2: bob 9\n"[..]);
+}
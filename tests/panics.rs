@@ -0,0 +1,34 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checks that the test case recorded on a thread is restored when a solver panics.
+
+extern crate gcj_helper;
+
+use gcj_helper::{case_rng, current_case, CaseRng, TestEngine};
+use std::panic::{self, AssertUnwindSafe};
+
+#[test]
+fn current_case_is_restored_after_a_solver_panics() {
+    let crashed = panic::catch_unwind(AssertUnwindSafe(|| {
+        TestEngine::with_io(&b"2\nx\ny\n"[..], Vec::new())
+            .seed(5)
+            .threads(1)
+            .run_scored(
+                2,
+                |reader| reader.read_next_line().to_owned(),
+                |line| match line.as_str() {
+                    "y" => panic!("simulated crash"),
+                    _ => format!(" {}\n", line),
+                },
+                |_, _| 0.0,
+            );
+    }));
+    assert!(crashed.is_err());
+    assert_eq!(current_case(), None);
+    assert_eq!(case_rng().next_u64(), CaseRng::new(5, 0).next_u64());
+}
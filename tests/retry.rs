@@ -0,0 +1,37 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checks that test cases are retried while the solver is unsure, up to the policy's limit.
+
+extern crate gcj_helper;
+
+use gcj_helper::{Attempt, RetryPolicy, TestEngine};
+use std::sync::Mutex;
+
+#[test]
+fn unsure_results_are_retried_up_to_max_attempts() {
+    let attempts = Mutex::new(Vec::new());
+    let mut output = Vec::new();
+    TestEngine::with_io(&b"3\n0\n2\n9\n"[..], &mut output).run_with_retry(
+        RetryPolicy::new(4),
+        |reader| reader.read_next_line().parse::<usize>().unwrap(),
+        |&needed, attempt| {
+            attempts.lock().unwrap().push((needed, attempt));
+            let result = format!(" {}\n", attempt);
+            if attempt >= needed {
+                Attempt::confident(result)
+            } else {
+                Attempt::unsure(result)
+            }
+        },
+    );
+    assert_eq!(output, &b"Case #1: 0\nCase #2: 2\nCase #3: 3\n"[..]);
+    let mut attempts = attempts.into_inner().unwrap();
+    attempts.sort();
+    let expected = [(0, 0), (2, 0), (2, 1), (2, 2), (9, 0), (9, 1), (9, 2), (9, 3)];
+    assert_eq!(attempts, expected);
+}
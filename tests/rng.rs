@@ -0,0 +1,72 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checks that repeated runs of a solver draw different, but reproducible, random numbers.
+
+extern crate gcj_helper;
+
+use gcj_helper::{case_rng, Attempt, RetryPolicy, TestEngine};
+use std::sync::Mutex;
+
+/// The seed used by every test, as the run's seed is shared by the whole process.
+const SEED: u64 = 2017;
+
+/// Runs a scored solver drawing one random number per run, returning the numbers drawn for each
+/// test case, in the order they were drawn.
+fn scored_draws() -> Vec<(usize, Vec<u64>)> {
+    let draws = Mutex::new(Vec::new());
+    TestEngine::with_io(&b"2\n1\n2\n"[..], Vec::new())
+        .seed(SEED)
+        .run_scored(
+            4,
+            |reader| reader.read_next_line().parse::<usize>().unwrap(),
+            |_| case_rng().next_u64(),
+            |&case, &x| {
+                draws.lock().unwrap().push((case, x));
+                x as f64
+            },
+        );
+    let draws = draws.into_inner().unwrap();
+    (1..3)
+        .map(|case| {
+            let drawn = draws.iter().filter(|d| d.0 == case).map(|d| d.1).collect();
+            (case, drawn)
+        })
+        .collect()
+}
+
+#[test]
+fn scored_runs_differ_but_are_reproducible() {
+    let first = scored_draws();
+    for (_, drawn) in &first {
+        assert_eq!(drawn.len(), 4);
+        for (i, x) in drawn.iter().enumerate() {
+            assert!(!drawn[..i].contains(x), "{:?}", drawn);
+        }
+    }
+    assert_ne!(first[0].1, first[1].1);
+    assert_eq!(first, scored_draws());
+}
+
+#[test]
+fn retried_attempts_differ() {
+    let draws = Mutex::new(Vec::new());
+    TestEngine::with_io(&b"1\nx\n"[..], Vec::new())
+        .seed(SEED)
+        .run_with_retry(
+            RetryPolicy::new(3),
+            |reader| reader.read_next_line().to_owned(),
+            |_, _| {
+                let x = case_rng().next_u64();
+                draws.lock().unwrap().push(x);
+                Attempt::unsure(x)
+            },
+        );
+    let draws = draws.into_inner().unwrap();
+    assert_eq!(draws.len(), 3);
+    assert!(draws[0] != draws[1] && draws[1] != draws[2] && draws[0] != draws[2]);
+}
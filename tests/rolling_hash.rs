@@ -0,0 +1,90 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Compares `RollingHash` against brute-force substring comparison.
+
+extern crate gcj_helper;
+
+use gcj_helper::util::RollingHash;
+use gcj_helper::CaseRng;
+
+/// Returns a random string of `len` bytes drawn from the first `alphabet` lowercase letters.
+fn random_string(rng: &mut CaseRng, len: usize, alphabet: u64) -> Vec<u8> {
+    (0..len)
+        .map(|_| b'a' + rng.gen_range(0, alphabet) as u8)
+        .collect()
+}
+
+#[test]
+fn substring_hashes_match_brute_force() {
+    let mut rng = CaseRng::new(0, 1);
+    for &(len, alphabet) in &[(0, 1), (1, 1), (12, 1), (30, 2), (40, 3), (60, 26)] {
+        let s = random_string(&mut rng, len, alphabet);
+        let hash = RollingHash::new(&s);
+        assert_eq!(hash.len(), len);
+        for a in 0..len + 1 {
+            for b in a..len + 1 {
+                for c in 0..len + 1 {
+                    let d = c + (b - a);
+                    if d > len {
+                        break;
+                    }
+                    assert_eq!(
+                        hash.substrings_equal(a..b, c..d),
+                        s[a..b] == s[c..d],
+                        "{:?}: {}..{} vs {}..{}",
+                        String::from_utf8_lossy(&s),
+                        a,
+                        b,
+                        c,
+                        d
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn substring_hashes_match_whole_string_hashes() {
+    let mut rng = CaseRng::new(0, 2);
+    let s = random_string(&mut rng, 50, 4);
+    let hash = RollingHash::new(&s);
+    for a in 0..s.len() + 1 {
+        for b in a..s.len() + 1 {
+            assert_eq!(hash.substring(a..b), RollingHash::hash(&s[a..b]));
+        }
+    }
+}
+
+#[test]
+fn finds_pattern_occurrences() {
+    let mut rng = CaseRng::new(0, 3);
+    let text = random_string(&mut rng, 200, 2);
+    let pattern = b"abba";
+    let hash = RollingHash::new(&text);
+    let target = RollingHash::hash(pattern);
+    let found: Vec<usize> = (0..text.len() - pattern.len() + 1)
+        .filter(|&i| hash.substring(i..i + pattern.len()) == target)
+        .collect();
+    let expected: Vec<usize> = text
+        .windows(pattern.len())
+        .enumerate()
+        .filter(|&(_, w)| w == pattern)
+        .map(|(i, _)| i)
+        .collect();
+    assert!(!expected.is_empty());
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn unequal_lengths_are_never_equal() {
+    let hash = RollingHash::new("aaaa");
+    assert!(hash.substrings_equal(0..2, 2..4));
+    assert!(!hash.substrings_equal(0..2, 1..4));
+    assert!(hash.substrings_equal(1..1, 3..3));
+}
@@ -0,0 +1,80 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checks that the sample gate only lets a run solve the input file once every sample passes.
+
+extern crate gcj_helper;
+
+use gcj_helper::{mismatched_cases, pair_cases, RunRecord, Sample, TestEngine};
+use std::env;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+
+static SAMPLES: &[Sample] = &[
+    Sample {
+        name: "sample.in",
+        input: "2\n1 2\n3 4\n",
+        expected: "Case #1: 3\nCase #2: 7\n",
+    },
+];
+
+#[test]
+fn passing_samples_let_the_input_file_be_solved() {
+    let path = env::temp_dir().join(format!("gcj-helper-samples-{}.csv", std::process::id()));
+    let mut output = Vec::new();
+    TestEngine::with_io(&b"3\n5 5\n0 1\n2 2\n"[..], &mut output)
+        .sample_gate(SAMPLES)
+        .run_history(&path)
+        .run(
+            |reader| reader.read_next_line().to_owned(),
+            |line| {
+                let total: u64 = line.split_whitespace().map(|t| t.parse::<u64>().unwrap()).sum();
+                format!(" {}\n", total)
+            },
+        );
+    let history = RunRecord::load_history(&path);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(output, &b"Case #1: 10\nCase #2: 1\nCase #3: 4\n"[..]);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].cases, 3);
+}
+
+#[test]
+fn failing_samples_leave_the_output_file_untouched() {
+    let mut output = Vec::new();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        TestEngine::with_io(&b"1\n5 5\n"[..], &mut output)
+            .sample_gate(SAMPLES)
+            .run(|reader| reader.read_next_line().to_owned(), |_| " 0\n");
+    }));
+    assert!(result.is_err());
+    assert!(output.is_empty());
+}
+
+#[test]
+fn cases_are_paired_by_case_number() {
+    let expected = "Case #1: a\nCase #2: b\nCase #3: c\n";
+    let actual = "Case #1: a\nCase #3: c\nCase #4: d\n";
+    let pairs = pair_cases(expected, actual);
+    assert_eq!(
+        pairs,
+        [
+            (Some("Case #1: a\n"), Some("Case #1: a\n")),
+            (Some("Case #2: b\n"), None),
+            (Some("Case #3: c\n"), Some("Case #3: c\n")),
+            (None, Some("Case #4: d\n")),
+        ]
+    );
+    let mismatches = mismatched_cases(expected, actual);
+    assert_eq!(
+        mismatches,
+        [
+            ("Case #2: b".to_owned(), String::new()),
+            (String::new(), "Case #4: d".to_owned()),
+        ]
+    );
+}
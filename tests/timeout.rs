@@ -0,0 +1,46 @@
+// Copyright (c) 2017 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT
+// or http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checks that test cases past the hard time limit are abandoned without ending the run.
+
+extern crate gcj_helper;
+
+use gcj_helper::{case_cancelled, TestEngine};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn slow_test_case_is_abandoned_and_the_run_continues() {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let observed = cancelled.clone();
+    let mut output = Vec::new();
+    TestEngine::with_io(&b"3\nfast\nslow\nfast\n"[..], &mut output).run_with_timeout(
+        Duration::from_millis(100),
+        |reader| reader.read_next_line().to_owned(),
+        move |line| {
+            if line == "slow" {
+                let start = Instant::now();
+                while start.elapsed() < Duration::from_secs(10) {
+                    if case_cancelled() {
+                        observed.store(true, Ordering::SeqCst);
+                        break;
+                    }
+                    thread::sleep(Duration::from_millis(5));
+                }
+            }
+            format!(" {}\n", line)
+        },
+    );
+    assert_eq!(output, &b"Case #1: fast\nCase #2: TLE\nCase #3: fast\n"[..]);
+    let start = Instant::now();
+    while !cancelled.load(Ordering::SeqCst) && start.elapsed() < Duration::from_secs(5) {
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert!(cancelled.load(Ordering::SeqCst));
+}